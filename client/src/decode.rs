@@ -0,0 +1,352 @@
+//! Instruction decoding against discovered schemas.
+//!
+//! Turns raw [`Instruction`]s back into named, typed arguments and named
+//! accounts using the schema a program advertises through `list_tools`.
+
+use mcpsol_core::{
+    ArgType, DecodeError, DecodedInstruction, McpAccountMeta, McpArg, McpError, McpSchema,
+    McpTool,
+};
+use serde_json::Value;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{McpClientError, ParsedSchema, ParsedTool, Result};
+
+impl ParsedSchema {
+    /// Convert a parsed (compact or verbose) schema into a core [`McpSchema`].
+    ///
+    /// Account suffixes from the compact format are stripped back to the
+    /// declared names. Parameter order follows the `required` list for
+    /// compact schemas and declaration order for verbose ones.
+    pub fn to_schema(&self) -> Result<McpSchema> {
+        let tools = self
+            .tools
+            .iter()
+            .map(ParsedTool::to_tool)
+            .collect::<Result<Vec<_>>>()?;
+
+        let errors = self
+            .errors
+            .iter()
+            .map(|e| McpError {
+                code: e.code,
+                name: e.name.clone(),
+                message: e.message.clone(),
+            })
+            .collect();
+
+        Ok(McpSchema {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            tools,
+            errors,
+            discriminator_len: self.discriminator_len.unwrap_or(mcpsol_core::DISCRIMINATOR_LEN),
+        })
+    }
+}
+
+impl ParsedTool {
+    /// Convert this tool into a core [`McpTool`].
+    pub fn to_tool(&self) -> Result<McpTool> {
+        let compact = !self.required.is_empty();
+        let mut accounts = Vec::new();
+        let mut args = Vec::new();
+
+        for param in self.required_params() {
+            let description = self.get_param_description(param).map(str::to_string);
+
+            if self.is_account(param) {
+                let name = if compact { Self::base_name(param) } else { param };
+                accounts.push(McpAccountMeta {
+                    name: name.to_string(),
+                    description,
+                    is_signer: self.is_signer(param),
+                    is_writable: self.is_writable(param),
+                    is_optional: self.is_optional(param),
+                });
+                continue;
+            }
+
+            let type_name = self.get_param_type(param).unwrap_or("str");
+            let arg_type = ArgType::from_compact_name(type_name).ok_or_else(|| {
+                McpClientError::ParseSchema(format!(
+                    "Unknown type '{}' for parameter '{}'",
+                    type_name, param
+                ))
+            })?;
+            args.push(McpArg {
+                name: param.to_string(),
+                description,
+                arg_type,
+            });
+        }
+
+        let remaining_accounts = self.remaining_accounts_param().map(|param| McpAccountMeta {
+            name: if compact { Self::base_name(param) } else { param }.to_string(),
+            description: self.get_param_description(param).map(str::to_string),
+            is_signer: self.is_signer(param),
+            is_writable: self.is_writable(param),
+            is_optional: false,
+        });
+
+        // A short tag is the start of the name's hash
+        let tag = self.tag_bytes()?;
+        let mut discriminator = mcpsol_core::instruction_discriminator(&self.name);
+        discriminator[..tag.len()].copy_from_slice(&tag);
+
+        Ok(McpTool {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            discriminator,
+            discriminator_len: tag.len(),
+            accounts,
+            remaining_accounts,
+            args,
+            aliases: self.aliases.keys().cloned().collect(),
+            returns: self.returns.as_ref().map(Value::to_string),
+        })
+    }
+
+    /// Decode the return data of a call to this tool by its advertised layout.
+    ///
+    /// Integers up to 64 bits become JSON numbers and wider ones strings,
+    /// pubkeys base58 strings, arrays JSON arrays and structs objects in
+    /// field order.
+    pub fn decode_return(&self, data: &[u8]) -> Result<Value> {
+        let layout = self.returns.as_ref().ok_or_else(|| {
+            McpClientError::ParseSchema(format!("tool '{}' declares no return data", self.name))
+        })?;
+        let mut offset = 0;
+        let value = decode_layout(layout, "return", data, &mut offset)?;
+        if offset < data.len() {
+            return Err(DecodeError::TrailingBytes { count: data.len() - offset }.into());
+        }
+        Ok(value)
+    }
+}
+
+/// Read one value of `layout` at `*offset`, named `name` in errors.
+fn decode_layout(layout: &Value, name: &str, data: &[u8], offset: &mut usize) -> Result<Value> {
+    let ty = match layout {
+        Value::Object(fields) => {
+            let mut object = serde_json::Map::new();
+            for (field, layout) in fields {
+                object.insert(field.clone(), decode_layout(layout, field, data, offset)?);
+            }
+            return Ok(Value::Object(object));
+        }
+        Value::String(ty) => ty.as_str(),
+        _ => return Err(McpClientError::ParseSchema(format!("Invalid return layout: {}", layout))),
+    };
+
+    // Fixed-size arrays are written `[type;len]`
+    if let Some((elem, len)) = ty
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .and_then(|t| t.rsplit_once(';'))
+    {
+        let len: usize = len.trim().parse().map_err(|_| {
+            McpClientError::ParseSchema(format!("Invalid array length in '{}'", ty))
+        })?;
+        let elem = Value::String(elem.trim().to_string());
+        return (0..len)
+            .map(|_| decode_layout(&elem, name, data, offset))
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array);
+    }
+
+    let arg_type = ArgType::from_compact_name(ty)
+        .filter(|t| !matches!(t, ArgType::String | ArgType::Bytes))
+        .ok_or_else(|| McpClientError::ParseSchema(format!("Unknown return type '{}'", ty)))?;
+    let size = match arg_type {
+        ArgType::U8 | ArgType::I8 | ArgType::Bool => 1,
+        ArgType::U16 | ArgType::I16 => 2,
+        ArgType::U32 | ArgType::I32 => 4,
+        ArgType::U64 | ArgType::I64 => 8,
+        ArgType::U128 | ArgType::I128 => 16,
+        _ => 32,
+    };
+    let bytes = data.get(*offset..*offset + size).ok_or_else(|| DecodeError::MissingBytes {
+        arg: name.to_string(),
+        needed: size,
+        available: data.len().saturating_sub(*offset),
+    })?;
+    *offset += size;
+
+    macro_rules! int {
+        ($ty:ty) => {
+            <$ty>::from_le_bytes(fixed(bytes))
+        };
+    }
+    Ok(match arg_type {
+        ArgType::U8 => int!(u8).into(),
+        ArgType::U16 => int!(u16).into(),
+        ArgType::U32 => int!(u32).into(),
+        ArgType::U64 => int!(u64).into(),
+        ArgType::U128 => int!(u128).to_string().into(),
+        ArgType::I8 => int!(i8).into(),
+        ArgType::I16 => int!(i16).into(),
+        ArgType::I32 => int!(i32).into(),
+        ArgType::I64 => int!(i64).into(),
+        ArgType::I128 => int!(i128).to_string().into(),
+        ArgType::Bool => match bytes[0] {
+            0 => false.into(),
+            1 => true.into(),
+            value => return Err(DecodeError::InvalidBool { arg: name.to_string(), value }.into()),
+        },
+        _ => Pubkey::new_from_array(fixed(bytes)).to_string().into(),
+    })
+}
+
+/// Copy a slice already cut to length `N` into an array
+fn fixed<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut out = [0; N];
+    out.copy_from_slice(bytes);
+    out
+}
+
+/// Decode an instruction into its tool, named arguments and named accounts.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let schema = client.list_tools_full(&program_id)?.to_schema()?;
+/// let decoded = mcpsol_client::decode_instruction(&schema, &ix)?;
+/// println!("{}", decoded.tool.name);
+/// for arg in &decoded.args {
+///     println!("  {} = {:?}", arg.name, arg.value);
+/// }
+/// ```
+pub fn decode_instruction<'a>(
+    schema: &'a McpSchema,
+    ix: &Instruction,
+) -> Result<DecodedInstruction<'a>> {
+    let keys: Vec<[u8; 32]> = ix.accounts.iter().map(|m| m.pubkey.to_bytes()).collect();
+    Ok(mcpsol_core::decode_instruction(schema, &ix.data, &keys)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcpsol_core::ArgValue;
+    use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+    #[test]
+    fn test_compact_schema_round_trip() {
+        let json = r#"{
+            "v": "2024-11-05",
+            "name": "counter",
+            "tools": [
+                {
+                    "n": "increment",
+                    "d": "0b12680968ae3b21",
+                    "p": {"counter_w": "pubkey", "authority_s": "pubkey", "amount": "u64"},
+                    "r": ["counter_w", "authority_s", "amount"]
+                }
+            ]
+        }"#;
+        let parsed: ParsedSchema = serde_json::from_str(json).unwrap();
+        let schema = parsed.to_schema().unwrap();
+
+        let counter = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut data = schema.tools[0].discriminator.to_vec();
+        data.extend_from_slice(&100u64.to_le_bytes());
+        let ix = Instruction {
+            program_id: Pubkey::new_unique(),
+            accounts: vec![
+                AccountMeta::new(counter, false),
+                AccountMeta::new_readonly(authority, true),
+            ],
+            data,
+        };
+
+        let decoded = decode_instruction(&schema, &ix).unwrap();
+        assert_eq!(decoded.tool.name, "increment");
+        assert_eq!(decoded.arg("amount"), Some(&ArgValue::U64(100)));
+        assert_eq!(decoded.accounts[0].meta.name, "counter");
+        assert!(decoded.accounts[0].meta.is_writable);
+        assert_eq!(decoded.account("authority"), Some(&authority.to_bytes()));
+    }
+
+    #[test]
+    fn test_verbose_schema_keeps_declaration_order() {
+        let json = r#"{
+            "v": "2024-11-05",
+            "name": "pool",
+            "tools": [
+                {
+                    "name": "swap",
+                    "discriminator": "0b12680968ae3b21",
+                    "parameters": {
+                        "user": {"type": "pubkey", "signer": true, "description": "Trader"},
+                        "min_out": {"type": "u64"},
+                        "amount_in": {"type": "u32"}
+                    }
+                }
+            ]
+        }"#;
+        let parsed: ParsedSchema = serde_json::from_str(json).unwrap();
+        let tool = parsed.tools[0].to_tool().unwrap();
+
+        assert_eq!(tool.accounts[0].description.as_deref(), Some("Trader"));
+        assert_eq!(tool.args[0].name, "min_out");
+        assert_eq!(tool.args[1].arg_type, ArgType::U32);
+    }
+
+    #[test]
+    fn test_unknown_type_rejected() {
+        let json = r#"{
+            "v": "2024-11-05",
+            "name": "x",
+            "tools": [{"n": "t", "d": "0b12680968ae3b21", "p": {"v": "f64"}, "r": ["v"]}]
+        }"#;
+        let parsed: ParsedSchema = serde_json::from_str(json).unwrap();
+        assert!(parsed.to_schema().is_err());
+    }
+
+    #[test]
+    fn test_decode_return_data() {
+        let json = r#"{
+            "v": "2024-11-05",
+            "name": "vault",
+            "tools": [
+                {"n": "balance", "d": "0b12680968ae3b21", "o": "u64"},
+                {
+                    "n": "info",
+                    "d": "0b12680968ae3b21",
+                    "o": {"owner": "pubkey", "balance": "u128", "bump": "u8", "flags": "[bool;2]"}
+                }
+            ]
+        }"#;
+        let parsed: ParsedSchema = serde_json::from_str(json).unwrap();
+        let balance = parsed.tool("balance").unwrap();
+        let info = parsed.tool("info").unwrap();
+
+        assert_eq!(balance.decode_return(&7u64.to_le_bytes()).unwrap(), 7);
+        assert!(balance.decode_return(&[0; 9]).is_err());
+        assert!(balance.decode_return(&[0; 7]).is_err());
+
+        let owner = Pubkey::new_unique();
+        let mut data = owner.to_bytes().to_vec();
+        data.extend_from_slice(&u128::MAX.to_le_bytes());
+        data.extend_from_slice(&[254, 1, 0]);
+        let value = info.decode_return(&data).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "owner": owner.to_string(),
+                "balance": u128::MAX.to_string(),
+                "bump": 254,
+                "flags": [true, false],
+            })
+        );
+        // Field order follows the layout
+        let keys: Vec<_> = value.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["owner", "balance", "bump", "flags"]);
+
+        assert_eq!(info.to_tool().unwrap().returns.as_deref(), Some(
+            r#"{"owner":"pubkey","balance":"u128","bump":"u8","flags":"[bool;2]"}"#
+        ));
+    }
+}
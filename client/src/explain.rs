@@ -0,0 +1,544 @@
+//! Human-readable transaction explanations.
+//!
+//! Renders every instruction of a transaction using the schemas the invoked
+//! programs advertise, so a reviewer sees
+//! `counter.increment(amount=100) — counter (writable), authority (signer)`
+//! instead of base64 before an agent signs.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use mcpsol_core::{ArgValue, DecodedInstruction, McpSchema};
+use serde::Serialize;
+use solana_sdk::{
+    instruction::CompiledInstruction,
+    message::Message,
+    pubkey::Pubkey,
+    transaction::Transaction,
+};
+
+use crate::McpClient;
+
+/// Program schemas keyed by program id.
+///
+/// Programs that failed discovery are remembered as unknown so they are not
+/// queried again.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaCache {
+    schemas: HashMap<Pubkey, Option<McpSchema>>,
+}
+
+impl SchemaCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a known schema for a program
+    pub fn insert(&mut self, program_id: Pubkey, schema: McpSchema) {
+        self.schemas.insert(program_id, Some(schema));
+    }
+
+    /// Record that a program exposes no MCP schema
+    pub fn insert_unknown(&mut self, program_id: Pubkey) {
+        self.schemas.insert(program_id, None);
+    }
+
+    /// Get the schema for a program, if known
+    pub fn get(&self, program_id: &Pubkey) -> Option<&McpSchema> {
+        self.schemas.get(program_id).and_then(Option::as_ref)
+    }
+
+    /// Whether discovery has already been attempted for a program
+    pub fn contains(&self, program_id: &Pubkey) -> bool {
+        self.schemas.contains_key(program_id)
+    }
+
+    /// Discover schemas for every program invoked by `message` that is not cached yet.
+    ///
+    /// Programs without a usable `list_tools` response are recorded as unknown.
+    pub fn fetch_missing(&mut self, client: &McpClient, message: &Message) {
+        for ix in &message.instructions {
+            let Some(program_id) = message.account_keys.get(ix.program_id_index as usize) else {
+                continue;
+            };
+            if self.contains(program_id) {
+                continue;
+            }
+            let schema = client
+                .list_tools_full(program_id)
+                .and_then(|parsed| parsed.to_schema())
+                .ok();
+            self.schemas.insert(*program_id, schema);
+        }
+    }
+}
+
+/// Explanation of every instruction in a transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionExplanation {
+    pub instructions: Vec<InstructionExplanation>,
+}
+
+/// Explanation of a single instruction.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstructionExplanation {
+    /// Invoked program id (base58)
+    pub program_id: String,
+    /// Program name from its schema, if known
+    pub program: Option<String>,
+    /// Matched tool name, if decoded
+    pub tool: Option<String>,
+    /// Tool description from the schema
+    pub description: Option<String>,
+    /// Decoded arguments in declaration order
+    pub args: Vec<ExplainedArg>,
+    /// Accounts with their declared names and roles in this transaction
+    pub accounts: Vec<ExplainedAccount>,
+    /// Anything a reviewer should look at before signing
+    pub warnings: Vec<String>,
+}
+
+/// A decoded argument ready for display.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedArg {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub arg_type: String,
+    pub value: serde_json::Value,
+}
+
+/// An account reference with its role in the transaction.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedAccount {
+    /// Declared account name (None for unknown programs or extra accounts)
+    pub name: Option<String>,
+    pub pubkey: String,
+    pub signer: bool,
+    pub writable: bool,
+}
+
+impl TransactionExplanation {
+    /// Render as a JSON value
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
+    }
+
+    /// Whether any instruction produced a warning
+    pub fn has_warnings(&self) -> bool {
+        self.instructions.iter().any(|ix| !ix.warnings.is_empty())
+    }
+}
+
+impl fmt::Display for TransactionExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, ix) in self.instructions.iter().enumerate() {
+            write!(f, "#{} ", i + 1)?;
+            ix.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for InstructionExplanation {
+    /// Plain-text form:
+    ///
+    /// ```text
+    /// counter.increment(amount=100) — counter (writable), authority (signer)
+    ///     Add amount to counter value
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let program = self.program.as_deref().unwrap_or(&self.program_id);
+        match &self.tool {
+            Some(tool) => {
+                let args: Vec<String> = self
+                    .args
+                    .iter()
+                    .map(|a| format!("{}={}", a.name, a.value))
+                    .collect();
+                write!(f, "{}.{}({})", program, tool, args.join(", "))?;
+            }
+            None => write!(f, "{}: <unknown instruction>", program)?,
+        }
+
+        let accounts: Vec<String> = self
+            .accounts
+            .iter()
+            .map(|a| {
+                let label = a.name.clone().unwrap_or_else(|| a.pubkey.clone());
+                match (a.signer, a.writable) {
+                    (true, true) => format!("{} (signer, writable)", label),
+                    (true, false) => format!("{} (signer)", label),
+                    (false, true) => format!("{} (writable)", label),
+                    (false, false) => label,
+                }
+            })
+            .collect();
+        if !accounts.is_empty() {
+            write!(f, " — {}", accounts.join(", "))?;
+        }
+        writeln!(f)?;
+
+        if let Some(desc) = &self.description {
+            writeln!(f, "    {}", desc)?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "    warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// Explain every instruction of a transaction using cached schemas.
+pub fn explain_transaction(tx: &Transaction, cache: &SchemaCache) -> TransactionExplanation {
+    explain_message(&tx.message, cache)
+}
+
+/// Explain every instruction of a message using cached schemas.
+///
+/// Instructions of programs without a cached schema are listed with their
+/// raw accounts and a warning.
+pub fn explain_message(message: &Message, cache: &SchemaCache) -> TransactionExplanation {
+    TransactionExplanation {
+        instructions: message
+            .instructions
+            .iter()
+            .map(|ix| explain_compiled(message, ix, cache))
+            .collect(),
+    }
+}
+
+impl McpClient {
+    /// Explain a transaction, discovering schemas for uncached programs first.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let mut cache = SchemaCache::new();
+    /// let explanation = client.explain_transaction(&tx, &mut cache);
+    /// println!("{}", explanation);
+    /// ```
+    pub fn explain_transaction(
+        &self,
+        tx: &Transaction,
+        cache: &mut SchemaCache,
+    ) -> TransactionExplanation {
+        cache.fetch_missing(self, &tx.message);
+        explain_transaction(tx, cache)
+    }
+}
+
+fn explain_compiled(
+    message: &Message,
+    ix: &CompiledInstruction,
+    cache: &SchemaCache,
+) -> InstructionExplanation {
+    let program_id = message
+        .account_keys
+        .get(ix.program_id_index as usize)
+        .copied()
+        .unwrap_or_default();
+
+    let mut accounts: Vec<ExplainedAccount> = ix
+        .accounts
+        .iter()
+        .map(|&idx| {
+            let idx = idx as usize;
+            ExplainedAccount {
+                name: None,
+                pubkey: message
+                    .account_keys
+                    .get(idx)
+                    .map(|k| k.to_string())
+                    .unwrap_or_default(),
+                signer: message.is_signer(idx),
+                writable: message.is_maybe_writable(idx, None),
+            }
+        })
+        .collect();
+
+    let mut explanation = InstructionExplanation {
+        program_id: program_id.to_string(),
+        program: None,
+        tool: None,
+        description: None,
+        args: Vec::new(),
+        accounts: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let Some(schema) = cache.get(&program_id) else {
+        explanation.warnings.push(format!(
+            "unknown program {}: {} accounts, {} data bytes not decoded",
+            program_id,
+            accounts.len(),
+            ix.data.len()
+        ));
+        explanation.accounts = accounts;
+        return explanation;
+    };
+    explanation.program = Some(schema.name.clone());
+
+    let keys: Vec<[u8; 32]> = ix
+        .accounts
+        .iter()
+        .map(|&idx| {
+            message
+                .account_keys
+                .get(idx as usize)
+                .map(|k| k.to_bytes())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    match mcpsol_core::decode_instruction(schema, &ix.data, &keys) {
+        Ok(decoded) => {
+            apply_decoded(&mut explanation, &mut accounts, &decoded, &program_id);
+        }
+        Err(e) => explanation.warnings.push(format!("could not decode: {}", e)),
+    }
+
+    explanation.accounts = accounts;
+    explanation
+}
+
+fn apply_decoded(
+    explanation: &mut InstructionExplanation,
+    accounts: &mut [ExplainedAccount],
+    decoded: &DecodedInstruction<'_>,
+    program_id: &Pubkey,
+) {
+    explanation.tool = Some(decoded.tool.name.clone());
+    explanation.description = decoded.tool.description.clone();
+    explanation.args = decoded
+        .args
+        .iter()
+        .map(|arg| {
+            let mut arg_type = String::new();
+            arg.arg_type.push_type_name(&mut arg_type);
+            ExplainedArg {
+                name: arg.name.to_string(),
+                arg_type,
+                value: arg_value_to_json(&arg.value),
+            }
+        })
+        .collect();
+
+    for (account, declared) in accounts.iter_mut().zip(&decoded.accounts) {
+        let meta = declared.meta;
+        account.name = Some(meta.name.clone());
+        // Optional accounts are left out by passing the program id
+        if meta.is_optional && declared.key == program_id.to_bytes() {
+            continue;
+        }
+        if meta.is_signer && !account.signer {
+            explanation
+                .warnings
+                .push(format!("'{}' must sign but is not a signer", meta.name));
+        }
+        if meta.is_writable && !account.writable {
+            explanation
+                .warnings
+                .push(format!("'{}' must be writable but is read-only", meta.name));
+        }
+    }
+
+    let extra = accounts.get_mut(decoded.accounts.len()..).unwrap_or_default();
+    match &decoded.tool.remaining_accounts {
+        Some(meta) => {
+            for account in extra {
+                account.name = Some(meta.name.clone());
+            }
+        }
+        None if !decoded.remaining_accounts.is_empty() => {
+            explanation.warnings.push(format!(
+                "{} accounts beyond those declared by '{}'",
+                decoded.remaining_accounts.len(),
+                decoded.tool.name
+            ));
+        }
+        None => {}
+    }
+}
+
+/// JSON form of an argument value.
+///
+/// 128-bit integers are rendered as strings since JSON numbers lose precision
+/// beyond 64 bits; bytes are base64 like the input format of `build_instruction`.
+fn arg_value_to_json(value: &ArgValue) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        ArgValue::U8(v) => Value::from(*v),
+        ArgValue::U16(v) => Value::from(*v),
+        ArgValue::U32(v) => Value::from(*v),
+        ArgValue::U64(v) => Value::from(*v),
+        ArgValue::I8(v) => Value::from(*v),
+        ArgValue::I16(v) => Value::from(*v),
+        ArgValue::I32(v) => Value::from(*v),
+        ArgValue::I64(v) => Value::from(*v),
+        ArgValue::U128(v) => Value::from(v.to_string()),
+        ArgValue::I128(v) => Value::from(v.to_string()),
+        ArgValue::Bool(v) => Value::from(*v),
+        ArgValue::Pubkey(bytes) => Value::from(Pubkey::from(*bytes).to_string()),
+        ArgValue::String(s) => Value::from(s.clone()),
+        ArgValue::Bytes(bytes) => Value::from(base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            bytes,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcpsol_core::{ArgType, McpSchemaBuilder, McpToolBuilder};
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+
+    fn counter_schema() -> McpSchema {
+        McpSchemaBuilder::new("counter")
+            .add_tool(
+                McpToolBuilder::new("increment")
+                    .description("Add amount to counter value")
+                    .writable("counter")
+                    .signer("authority")
+                    .arg("amount", ArgType::U64)
+                    .build(),
+            )
+            .build()
+    }
+
+    fn increment_ix(program_id: Pubkey, counter: Pubkey, authority: Pubkey) -> Instruction {
+        let mut data = mcpsol_core::instruction_discriminator("increment").to_vec();
+        data.extend_from_slice(&100u64.to_le_bytes());
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(counter, false),
+                AccountMeta::new_readonly(authority, true),
+            ],
+            data,
+        }
+    }
+
+    #[test]
+    fn test_explain_known_program() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let ix = increment_ix(program_id, Pubkey::new_unique(), authority);
+        let message = Message::new(&[ix], Some(&authority));
+
+        let mut cache = SchemaCache::new();
+        cache.insert(program_id, counter_schema());
+
+        let explanation = explain_message(&message, &cache);
+        let text = explanation.to_string();
+
+        assert!(text.contains(
+            "counter.increment(amount=100) — counter (writable), authority (signer, writable)"
+        ));
+        assert!(text.contains("Add amount to counter value"));
+        assert!(!explanation.has_warnings());
+
+        let json = explanation.to_json();
+        assert_eq!(json["instructions"][0]["tool"], "increment");
+        assert_eq!(json["instructions"][0]["args"][0]["value"], 100);
+        assert_eq!(json["instructions"][0]["accounts"][1]["name"], "authority");
+    }
+
+    #[test]
+    fn test_explain_unknown_program() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let ix = increment_ix(program_id, Pubkey::new_unique(), payer);
+        let message = Message::new(&[ix], Some(&payer));
+
+        let explanation = explain_message(&message, &SchemaCache::new());
+        let ix = &explanation.instructions[0];
+
+        assert!(ix.tool.is_none());
+        assert!(ix.warnings[0].contains("unknown program"));
+        assert_eq!(ix.accounts.len(), 2);
+    }
+
+    #[test]
+    fn test_explain_warns_on_missing_signer() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut ix = increment_ix(program_id, Pubkey::new_unique(), authority);
+        ix.accounts[1].is_signer = false;
+        let message = Message::new(&[ix], Some(&payer));
+
+        let mut cache = SchemaCache::new();
+        cache.insert(program_id, counter_schema());
+
+        let explanation = explain_message(&message, &cache);
+        assert!(explanation.instructions[0]
+            .warnings
+            .iter()
+            .any(|w| w.contains("'authority' must sign")));
+    }
+
+    #[test]
+    fn test_explain_absent_optional_account() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let schema = McpSchemaBuilder::new("pay")
+            .add_tool(
+                McpToolBuilder::new("pay")
+                    .signer_writable("payer")
+                    .optional_account("referrer", true, false)
+                    .build(),
+            )
+            .build();
+        let ix = Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(program_id, false),
+            ],
+            data: mcpsol_core::instruction_discriminator("pay").to_vec(),
+        };
+        let message = Message::new(&[ix], Some(&payer));
+
+        let mut cache = SchemaCache::new();
+        cache.insert(program_id, schema);
+
+        let explanation = explain_message(&message, &cache);
+        let ix = &explanation.instructions[0];
+        assert!(!explanation.has_warnings(), "{:?}", ix.warnings);
+        assert_eq!(ix.accounts[1].name.as_deref(), Some("referrer"));
+    }
+
+    #[test]
+    fn test_explain_declared_remaining_accounts() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let signers = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let schema = McpSchemaBuilder::new("multisig")
+            .add_tool(
+                McpToolBuilder::new("approve")
+                    .writable("proposal")
+                    .remaining_accounts("signers", true, false)
+                    .build(),
+            )
+            .build();
+        let mut accounts = vec![AccountMeta::new(Pubkey::new_unique(), false)];
+        accounts.extend(signers.iter().map(|s| AccountMeta::new_readonly(*s, true)));
+        let ix = Instruction {
+            program_id,
+            accounts,
+            data: mcpsol_core::instruction_discriminator("approve").to_vec(),
+        };
+        let message = Message::new(&[ix], Some(&payer));
+
+        let mut cache = SchemaCache::new();
+        cache.insert(program_id, schema);
+
+        let explanation = explain_message(&message, &cache);
+        let ix = &explanation.instructions[0];
+        assert!(!explanation.has_warnings(), "{:?}", ix.warnings);
+        assert_eq!(ix.accounts.len(), 3);
+        assert_eq!(ix.accounts[2].name.as_deref(), Some("signers"));
+        assert!(ix.accounts[2].signer);
+    }
+}
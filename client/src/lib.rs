@@ -38,7 +38,16 @@
 //! )?;
 //! ```
 
-use mcpsol_core::LIST_TOOLS_DISCRIMINATOR;
+mod decode;
+mod explain;
+
+pub use decode::decode_instruction;
+pub use explain::{
+    explain_message, explain_transaction, ExplainedAccount, ExplainedArg,
+    InstructionExplanation, SchemaCache, TransactionExplanation,
+};
+
+use mcpsol_core::{ArgType, LIST_TOOLS_DISCRIMINATOR};
 use serde::Deserialize;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
@@ -76,6 +85,9 @@ pub enum McpClientError {
 
     #[error("No return data from program")]
     NoReturnData,
+
+    #[error("Failed to decode instruction: {0}")]
+    Decode(#[from] mcpsol_core::DecodeError),
 }
 
 pub type Result<T> = std::result::Result<T, McpClientError>;
@@ -88,12 +100,59 @@ pub struct ParsedSchema {
     #[serde(rename = "v")]
     pub version: String,
     pub name: String,
+    /// Program description (verbose format only)
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Short discriminator length - "dl" (compact) or "discriminatorLength"
+    /// (verbose); absent when tools use full 8-byte discriminators
+    #[serde(rename = "discriminatorLength", alias = "dl", default)]
+    pub discriminator_len: Option<usize>,
     pub tools: Vec<ParsedTool>,
+    /// Custom program errors - "e" (compact) or "errors" (first verbose page)
+    #[serde(alias = "e", default)]
+    pub errors: Vec<ParsedError>,
     /// Pagination cursor for verbose format (None = last page or compact format)
     #[serde(rename = "nextCursor")]
     pub next_cursor: Option<String>,
 }
 
+impl ParsedSchema {
+    /// Find a tool by name, following a deprecated alias to its replacement
+    pub fn tool(&self, name: &str) -> Option<&ParsedTool> {
+        self.tools
+            .iter()
+            .find(|t| t.name == name)
+            .or_else(|| self.tools.iter().find(|t| t.aliases.contains_key(name)))
+    }
+
+    /// Find the error behind a `ProgramError::Custom(code)` failure
+    pub fn error(&self, code: u32) -> Option<&ParsedError> {
+        self.errors.iter().find(|e| e.code == code)
+    }
+}
+
+/// Custom program error from MCP schema.
+///
+/// Displays as `InsufficientFunds: vault balance too low`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParsedError {
+    #[serde(alias = "c")]
+    pub code: u32,
+    #[serde(alias = "n")]
+    pub name: String,
+    #[serde(alias = "m", default)]
+    pub message: Option<String>,
+}
+
+impl std::fmt::Display for ParsedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.message {
+            Some(ref message) => write!(f, "{}: {}", self.name, message),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
 /// Parsed tool from MCP schema.
 ///
 /// Supports both compact format (abbreviated keys) and verbose format (full keys).
@@ -114,6 +173,13 @@ pub struct ParsedTool {
     /// Required parameters (compact format only)
     #[serde(alias = "r", default)]
     pub required: Vec<String>,
+    /// Deprecated former names mapped to their discriminator hex - supports
+    /// "a" (compact) and "deprecatedAliases" (verbose)
+    #[serde(alias = "a", alias = "deprecatedAliases", default)]
+    pub aliases: serde_json::Map<String, serde_json::Value>,
+    /// Return data layout - supports "o" (compact) and "returns" (verbose)
+    #[serde(alias = "o", default)]
+    pub returns: Option<serde_json::Value>,
 }
 
 impl ParsedTool {
@@ -139,24 +205,37 @@ impl ParsedTool {
         Ok(bytes)
     }
 
+    /// Get the bytes instruction data for this tool starts with: the full
+    /// discriminator, or its first bytes for programs with short ones
+    pub fn tag_bytes(&self) -> Result<Vec<u8>> {
+        let decoded = hex::decode(&self.discriminator)
+            .map_err(|_| McpClientError::ParseSchema(
+                format!("Invalid discriminator hex: {}", self.discriminator)
+            ))?;
+
+        if decoded.is_empty() || decoded.len() > 8 {
+            return Err(McpClientError::ParseSchema(
+                format!("Discriminator must be 1 to 8 bytes, got {}", decoded.len())
+            ));
+        }
+        Ok(decoded)
+    }
+
     /// Check if a parameter is an account (pubkey type).
     ///
     /// Supports both compact format (value is "pubkey" string) and
-    /// verbose format (object with "type": "pubkey").
+    /// verbose format (object with "type": "pubkey"). Optional accounts,
+    /// typed "pubkey?", count as accounts too.
     pub fn is_account(&self, name: &str) -> bool {
-        self.params.get(name)
-            .map(|v| {
-                // Compact format: "pubkey"
-                if v.as_str() == Some("pubkey") {
-                    return true;
-                }
-                // Verbose format: {"type": "pubkey", ...}
-                if let Some(obj) = v.as_object() {
-                    return obj.get("type").and_then(|t| t.as_str()) == Some("pubkey");
-                }
-                false
-            })
-            .unwrap_or(false)
+        matches!(
+            self.get_param_type(name),
+            Some("pubkey") | Some(mcpsol_core::OPTIONAL_ACCOUNT_TYPE)
+        )
+    }
+
+    /// Check if an account may be left out by passing the program id.
+    pub fn is_optional(&self, name: &str) -> bool {
+        self.get_param_type(name) == Some(mcpsol_core::OPTIONAL_ACCOUNT_TYPE)
     }
 
     /// Check if an account is a signer.
@@ -228,6 +307,17 @@ impl ParsedTool {
             .trim_end_matches("_w")
     }
 
+    /// Get the variadic trailing account list, if the tool declares one.
+    ///
+    /// This is the parameter typed `"pubkey[]"`; any number of accounts may
+    /// be passed for it after the fixed accounts.
+    pub fn remaining_accounts_param(&self) -> Option<&str> {
+        self.params
+            .keys()
+            .map(String::as_str)
+            .find(|name| self.get_param_type(name) == Some(mcpsol_core::REMAINING_ACCOUNTS_TYPE))
+    }
+
     /// Get all parameter names (for building required list from verbose format).
     pub fn param_names(&self) -> Vec<&String> {
         self.params.keys().collect()
@@ -242,8 +332,13 @@ impl ParsedTool {
             // Compact format
             self.required.iter().map(|s| s.as_str()).collect()
         } else {
-            // Verbose format - all params are required
-            self.params.keys().map(|s| s.as_str()).collect()
+            // Verbose format - all params except the variadic list are required
+            let remaining = self.remaining_accounts_param();
+            self.params
+                .keys()
+                .map(|s| s.as_str())
+                .filter(|name| Some(*name) != remaining)
+                .collect()
         }
     }
 }
@@ -269,20 +364,27 @@ impl McpClient {
         Self { rpc }
     }
 
-    /// Discover available tools by calling list_tools (first page only).
+    /// Discover available tools by calling list_tools without a cursor.
     ///
+    /// Macro-generated programs answer with the compact schema (all tools);
+    /// hand-written paginated programs answer with their first verbose page.
     /// For paginated schemas, use [`list_tools_full`] to fetch all pages.
     pub fn list_tools(&self, program_id: &Pubkey) -> Result<ParsedSchema> {
-        self.list_tools_page(program_id, 0)
+        self.fetch_schema(program_id, None)
     }
 
-    /// Fetch a specific page of the schema.
+    /// Fetch a specific page of the verbose schema.
     ///
-    /// The cursor is the page number (0-indexed).
+    /// The cursor is the page number (0-indexed) and is always sent, so
+    /// programs that serve a compact schema by default return the verbose page.
     pub fn list_tools_page(&self, program_id: &Pubkey, cursor: u8) -> Result<ParsedSchema> {
+        self.fetch_schema(program_id, Some(cursor))
+    }
+
+    fn fetch_schema(&self, program_id: &Pubkey, cursor: Option<u8>) -> Result<ParsedSchema> {
         // Build list_tools instruction with optional cursor
         let mut data = LIST_TOOLS_DISCRIMINATOR.to_vec();
-        if cursor > 0 {
+        if let Some(cursor) = cursor {
             data.push(cursor);
         }
 
@@ -355,6 +457,9 @@ impl McpClient {
     /// Build an instruction from tool name and parameters.
     ///
     /// Supports both compact and verbose schema formats.
+    ///
+    /// Schemas type key arguments `pubkey`, like read-only accounts, so a
+    /// `pubkey` parameter given in `args` is encoded as data.
     pub fn build_instruction(
         &self,
         program_id: &Pubkey,
@@ -363,26 +468,36 @@ impl McpClient {
         args: &[(&str, &str)],
         schema: &ParsedSchema,
     ) -> Result<Instruction> {
-        // Find tool
-        let tool = schema.tools.iter()
-            .find(|t| t.name == tool_name)
+        // Deprecated names build the instruction of their replacement
+        let tool = schema.tool(tool_name)
             .ok_or_else(|| McpClientError::ToolNotFound(tool_name.to_string()))?;
 
         // Get required parameters (works for both formats)
         let required_params = tool.required_params();
+        let is_account = |name: &str| {
+            tool.is_account(name) && !args.iter().any(|(arg, _)| *arg == name)
+        };
 
         // Build account metas
         let mut account_metas = Vec::new();
         for required in &required_params {
-            if !tool.is_account(required) {
+            if !is_account(required) {
                 continue; // Skip non-account params
             }
 
             let base = ParsedTool::base_name(required);
             let pubkey = accounts.iter()
                 .find(|(name, _)| *name == base || *name == *required)
-                .map(|(_, pk)| *pk)
-                .ok_or_else(|| McpClientError::MissingParam((*required).to_string()))?;
+                .map(|(_, pk)| *pk);
+
+            // An optional account left out takes the program id's place
+            let Some(pubkey) = pubkey else {
+                if !tool.is_optional(required) {
+                    return Err(McpClientError::MissingParam((*required).to_string()));
+                }
+                account_metas.push(AccountMeta::new_readonly(*program_id, false));
+                continue;
+            };
 
             account_metas.push(AccountMeta {
                 pubkey,
@@ -391,12 +506,26 @@ impl McpClient {
             });
         }
 
+        // Variadic trailing accounts, in the order given
+        if let Some(remaining) = tool.remaining_accounts_param() {
+            let base = ParsedTool::base_name(remaining);
+            account_metas.extend(
+                accounts.iter()
+                    .filter(|(name, _)| *name == base || *name == remaining)
+                    .map(|(_, pubkey)| AccountMeta {
+                        pubkey: *pubkey,
+                        is_signer: tool.is_signer(remaining),
+                        is_writable: tool.is_writable(remaining),
+                    }),
+            );
+        }
+
         // Build instruction data
-        let mut data = tool.discriminator_bytes()?.to_vec();
+        let mut data = tool.tag_bytes()?;
 
         // Add args in order
         for required in &required_params {
-            if tool.is_account(required) {
+            if is_account(required) {
                 continue; // Skip account params
             }
 
@@ -408,7 +537,8 @@ impl McpClient {
                 .ok_or_else(|| McpClientError::MissingParam((*required).to_string()))?;
 
             // Serialize arg based on type
-            // Note: compact schema uses "int" for integers, "bool" for booleans, "str" for strings
+            // Note: compact schemas name exact widths (u8..i128); "int" from older
+            // schemas is read as u64
             match arg_type {
                 // Compact schema type - default to u64 for integers
                 "int" => {
@@ -461,6 +591,11 @@ impl McpClient {
                         .map_err(|_| McpClientError::InvalidArg((*required).to_string()))?;
                     data.extend_from_slice(&v.to_le_bytes());
                 }
+                "i128" => {
+                    let v: i128 = value.parse()
+                        .map_err(|_| McpClientError::InvalidArg((*required).to_string()))?;
+                    data.extend_from_slice(&v.to_le_bytes());
+                }
                 "bool" => {
                     let v: bool = value.parse()
                         .map_err(|_| McpClientError::InvalidArg((*required).to_string()))?;
@@ -486,6 +621,23 @@ impl McpClient {
                     data.extend_from_slice(&(decoded.len() as u32).to_le_bytes());
                     data.extend_from_slice(&decoded);
                 }
+                t if t.starts_with('[') => {
+                    // Base64 encoded bytes of exactly the declared length, no prefix
+                    let len = match ArgType::from_compact_name(t) {
+                        Some(ArgType::FixedBytes(len)) => len,
+                        _ => return Err(McpClientError::ParseSchema(
+                            format!("Unknown argument type '{}'", t),
+                        )),
+                    };
+                    let decoded = base64::Engine::decode(
+                        &base64::engine::general_purpose::STANDARD,
+                        value,
+                    ).map_err(|_| McpClientError::InvalidArg((*required).to_string()))?;
+                    if decoded.len() != len {
+                        return Err(McpClientError::InvalidArg((*required).to_string()));
+                    }
+                    data.extend_from_slice(&decoded);
+                }
                 _ => {
                     // Unknown type, try as string
                     let bytes = value.as_bytes();
@@ -552,6 +704,79 @@ mod tests {
         assert!(!tool.is_account("amount"));
     }
 
+    #[test]
+    fn test_parse_errors() {
+        let json = r#"{"v":"2024-11-05","name":"vault","tools":[],
+            "e":[{"c":6000,"n":"InsufficientFunds","m":"vault balance too low"},{"c":6001,"n":"Frozen"}]}"#;
+        let schema: ParsedSchema = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            schema.error(6000).unwrap().to_string(),
+            "InsufficientFunds: vault balance too low"
+        );
+        assert_eq!(schema.error(6001).unwrap().to_string(), "Frozen");
+        assert!(schema.error(0).is_none());
+        assert_eq!(schema.to_schema().unwrap().errors.len(), 2);
+
+        // Verbose first page uses full keys
+        let json = r#"{"v":"2024-11-05","name":"vault","tools":[],
+            "errors":[{"code":6000,"name":"InsufficientFunds"}]}"#;
+        let schema: ParsedSchema = serde_json::from_str(json).unwrap();
+        assert_eq!(schema.error(6000).unwrap().name, "InsufficientFunds");
+    }
+
+    #[test]
+    fn test_short_discriminators() {
+        let json = r#"{"v":"2024-11-05","name":"counter","dl":2,"tools":[
+            {"n":"increment","d":"0b12","p":{"counter_w":"pubkey","amount":"u64"},"r":["counter_w","amount"]},
+            {"n":"list_tools","d":"42195e6a55fd41c0"}]}"#;
+        let schema: ParsedSchema = serde_json::from_str(json).unwrap();
+        assert_eq!(schema.discriminator_len, Some(2));
+
+        let client = McpClient::new("http://localhost:8899");
+        let program_id = Pubkey::new_unique();
+        let counter = Pubkey::new_unique();
+        let ix = client
+            .build_instruction(&program_id, "increment", &[("counter", counter)], &[("amount", "3")], &schema)
+            .unwrap();
+        assert_eq!(ix.data, [&[0x0b, 0x12][..], &3u64.to_le_bytes()].concat());
+
+        let core = schema.to_schema().unwrap();
+        assert_eq!(core.discriminator_len, 2);
+        assert_eq!(core.tools[1].tag(), mcpsol_core::LIST_TOOLS_DISCRIMINATOR);
+        let decoded = mcpsol_core::decode_instruction(&core, &ix.data, &[counter.to_bytes()]).unwrap();
+        assert_eq!(decoded.tool.name, "increment");
+        assert_eq!(decoded.tool.discriminator, mcpsol_core::instruction_discriminator("increment"));
+    }
+
+    #[test]
+    fn test_aliases_resolve_to_replacement() {
+        let disc = mcpsol_core::instruction_discriminator("add");
+        let old = String::from_utf8(mcpsol_core::discriminator_to_hex(&disc).to_vec()).unwrap();
+        let json = format!(
+            r#"{{"v":"2024-11-05","name":"counter","tools":[{{"n":"increment","d":"0b12680968ae3b21",
+            "p":{{"counter_w":"pubkey","amount":"u64"}},"r":["counter_w","amount"],"a":{{"add":"{}"}}}}]}}"#,
+            old,
+        );
+        let schema: ParsedSchema = serde_json::from_str(&json).unwrap();
+        assert_eq!(schema.tool("add").unwrap().name, "increment");
+        assert!(schema.tool("sub").is_none());
+
+        let client = McpClient::new("http://localhost:8899");
+        let program_id = Pubkey::new_unique();
+        let counter = Pubkey::new_unique();
+        let ix = client
+            .build_instruction(&program_id, "add", &[("counter", counter)], &[("amount", "3")], &schema)
+            .unwrap();
+        assert_eq!(ix.data[..8], hex::decode("0b12680968ae3b21").unwrap());
+
+        // Data sent under the old discriminator still decodes as the new tool
+        let core = schema.to_schema().unwrap();
+        let mut data = disc.to_vec();
+        data.extend_from_slice(&3u64.to_le_bytes());
+        let decoded = mcpsol_core::decode_instruction(&core, &data, &[counter.to_bytes()]).unwrap();
+        assert_eq!(decoded.tool.name, "increment");
+    }
+
     #[test]
     fn test_discriminator_parse() {
         let tool = ParsedTool {
@@ -560,6 +785,8 @@ mod tests {
             discriminator: "0b12680968ae3b21".to_string(),
             params: serde_json::Map::new(),
             required: vec![],
+            aliases: serde_json::Map::new(),
+            returns: None,
         };
 
         let bytes = tool.discriminator_bytes().unwrap();
@@ -574,6 +801,8 @@ mod tests {
             discriminator: "invalid_hex".to_string(),
             params: serde_json::Map::new(),
             required: vec![],
+            aliases: serde_json::Map::new(),
+            returns: None,
         };
 
         assert!(tool.discriminator_bytes().is_err());
@@ -587,6 +816,8 @@ mod tests {
             discriminator: "0b1268".to_string(), // Only 3 bytes
             params: serde_json::Map::new(),
             required: vec![],
+            aliases: serde_json::Map::new(),
+            returns: None,
         };
 
         assert!(tool.discriminator_bytes().is_err());
@@ -771,6 +1002,100 @@ mod tests {
         assert!(required.contains(&"amount"));
     }
 
+    #[test]
+    fn test_remaining_accounts_appended_in_order() {
+        let json = r#"{
+            "v": "2024-11-05",
+            "name": "multisig",
+            "tools": [
+                {
+                    "n": "approve",
+                    "d": "0b12680968ae3b21",
+                    "p": {"proposal_w": "pubkey", "signers_s": "pubkey[]", "index": "u8"},
+                    "r": ["proposal_w", "index"]
+                }
+            ]
+        }"#;
+        let schema: ParsedSchema = serde_json::from_str(json).unwrap();
+        assert_eq!(schema.tools[0].remaining_accounts_param(), Some("signers_s"));
+
+        let (proposal, a, b) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = McpClient::new("http://localhost:8899")
+            .build_instruction(
+                &Pubkey::new_unique(),
+                "approve",
+                &[("signers", a), ("proposal", proposal), ("signers", b)],
+                &[("index", "2")],
+                &schema,
+            )
+            .unwrap();
+
+        let keys: Vec<_> = ix.accounts.iter().map(|m| (m.pubkey, m.is_signer, m.is_writable)).collect();
+        assert_eq!(keys, vec![(proposal, false, true), (a, true, false), (b, true, false)]);
+        assert_eq!(ix.data[8..], [2]);
+    }
+
+    #[test]
+    fn test_optional_account_defaults_to_program_id() {
+        let json = r#"{
+            "v": "2024-11-05",
+            "name": "market",
+            "tools": [
+                {
+                    "n": "swap",
+                    "d": "f8c69e91e17587c8",
+                    "p": {"user_s": "pubkey", "referrer_sw": "pubkey?"},
+                    "r": ["user_s", "referrer_sw"]
+                }
+            ]
+        }"#;
+        let schema: ParsedSchema = serde_json::from_str(json).unwrap();
+        let tool = &schema.tools[0];
+        assert!(tool.is_account("referrer_sw") && tool.is_optional("referrer_sw"));
+        assert!(!tool.is_optional("user_s"));
+
+        let (user, referrer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let program_id = Pubkey::new_unique();
+        let client = McpClient::new("http://localhost:8899");
+        let build = |accounts: &[(&str, Pubkey)]| {
+            client.build_instruction(&program_id, "swap", accounts, &[], &schema).unwrap()
+        };
+        let flags = |ix: Instruction| -> Vec<_> {
+            ix.accounts.iter().map(|m| (m.pubkey, m.is_signer, m.is_writable)).collect()
+        };
+
+        let ix = build(&[("user", user), ("referrer", referrer)]);
+        assert_eq!(flags(ix), vec![(user, true, false), (referrer, true, true)]);
+        let ix = build(&[("user", user)]);
+        assert_eq!(flags(ix), vec![(user, true, false), (program_id, false, false)]);
+    }
+
+    #[test]
+    fn test_required_params_verbose_skips_remaining_accounts() {
+        let json = r#"{
+            "v": "2024-11-05",
+            "name": "multisig",
+            "tools": [
+                {
+                    "name": "approve",
+                    "discriminator": "0b12680968ae3b21",
+                    "parameters": {
+                        "proposal": {"type": "pubkey", "writable": true},
+                        "signers": {"type": "pubkey[]", "signer": true}
+                    }
+                }
+            ]
+        }"#;
+        let schema: ParsedSchema = serde_json::from_str(json).unwrap();
+        let tool = &schema.tools[0];
+
+        assert_eq!(tool.required_params(), vec!["proposal"]);
+        let core_tool = tool.to_tool().unwrap();
+        let remaining = core_tool.remaining_accounts.unwrap();
+        assert_eq!(remaining.name, "signers");
+        assert!(remaining.is_signer);
+    }
+
     #[test]
     fn test_mixed_format_compatibility() {
         // Test that both name/n and description/i work
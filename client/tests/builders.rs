@@ -0,0 +1,35 @@
+//! Typed builders generated by `#[mcp_program]` agree with the
+//! schema-driven `McpClient::build_instruction`.
+//!
+//! Key, string and byte array arguments are covered by
+//! `sdk/tests/client_args.rs`, whose program also runs the dispatcher.
+
+use mcpsol_client::{McpClient, ParsedSchema};
+use minimal_counter::minimal_counter::{instruction, MCP_SCHEMA_BYTES};
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn test_builders_match_schema() {
+    let schema: ParsedSchema = serde_json::from_slice(MCP_SCHEMA_BYTES).unwrap();
+    let client = McpClient::new("http://localhost:8899");
+    let program_id = Pubkey::new_unique();
+    let (counter, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    // increment declares its accounts as a string, decrement derives them
+    let built = [
+        ("increment", instruction::increment(&program_id, &[counter, authority], 7)),
+        ("decrement", instruction::decrement(&program_id, &[counter, authority], 7)),
+    ];
+    for (name, ix) in built {
+        let expected = client
+            .build_instruction(
+                &program_id,
+                name,
+                &[("counter", counter), ("authority", authority)],
+                &[("amount", "7")],
+                &schema,
+            )
+            .unwrap();
+        assert_eq!(ix, expected, "{name}");
+    }
+}
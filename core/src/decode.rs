@@ -0,0 +1,435 @@
+//! Instruction data decoding
+//!
+//! The inverse of instruction building: given a schema and raw instruction
+//! bytes, find the tool by its discriminator and read every argument back
+//! by its [`ArgType`]. Used for transaction inspection, audit logs and
+//! debugging agent behaviour.
+//!
+//! Layout follows what clients encode and the dispatcher reads:
+//! discriminator (8 bytes, or the schema's shorter `discriminator_len`),
+//! then arguments in declaration order using Borsh
+//! encoding (little-endian integers, 4-byte length prefix for strings and
+//! bytes).
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use core::fmt;
+
+use crate::{ArgType, McpAccountMeta, McpSchema, McpTool};
+
+/// A decoded argument value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Bool(bool),
+    /// Raw 32-byte public key
+    Pubkey([u8; 32]),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+/// An argument read back from instruction data, paired with its declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedArg<'a> {
+    /// Argument name from the schema
+    pub name: &'a str,
+    /// Declared argument type
+    pub arg_type: ArgType,
+    /// Decoded value
+    pub value: ArgValue,
+}
+
+/// An instruction account paired with its declared name and role.
+#[derive(Debug, Clone)]
+pub struct DecodedAccount<'a> {
+    /// Account declaration from the schema
+    pub meta: &'a McpAccountMeta,
+    /// Public key passed in the instruction
+    pub key: [u8; 32],
+}
+
+/// A fully decoded instruction.
+#[derive(Debug, Clone)]
+pub struct DecodedInstruction<'a> {
+    /// The tool whose discriminator matched
+    pub tool: &'a McpTool,
+    /// Arguments in declaration order
+    pub args: Vec<DecodedArg<'a>>,
+    /// Declared accounts in order
+    pub accounts: Vec<DecodedAccount<'a>>,
+    /// Accounts passed beyond the declared list
+    pub remaining_accounts: Vec<[u8; 32]>,
+}
+
+impl<'a> DecodedInstruction<'a> {
+    /// Look up a decoded argument by name.
+    pub fn arg(&self, name: &str) -> Option<&ArgValue> {
+        self.args.iter().find(|a| a.name == name).map(|a| &a.value)
+    }
+
+    /// Look up an account key by its declared name.
+    pub fn account(&self, name: &str) -> Option<&[u8; 32]> {
+        self.accounts.iter().find(|a| a.meta.name == name).map(|a| &a.key)
+    }
+}
+
+/// Errors produced while decoding instruction data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Instruction data is shorter than the schema's discriminator
+    MissingDiscriminator { len: usize },
+    /// No tool in the schema has this discriminator
+    UnknownDiscriminator(Vec<u8>),
+    /// Instruction data ended before an argument was fully read
+    MissingBytes { arg: String, needed: usize, available: usize },
+    /// Bytes left over after all declared arguments were read
+    TrailingBytes { count: usize },
+    /// A bool argument held a byte other than 0 or 1
+    InvalidBool { arg: String, value: u8 },
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 { arg: String },
+    /// Fewer accounts were passed than the tool declares
+    MissingAccounts { expected: usize, found: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::MissingDiscriminator { len } => {
+                write!(f, "instruction data too short for discriminator: {} bytes", len)
+            }
+            DecodeError::UnknownDiscriminator(disc) => {
+                f.write_str("unknown discriminator: ")?;
+                disc.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+            }
+            DecodeError::MissingBytes { arg, needed, available } => write!(
+                f,
+                "missing bytes for argument '{}': needed {}, {} available",
+                arg, needed, available
+            ),
+            DecodeError::TrailingBytes { count } => {
+                write!(f, "{} unexpected trailing bytes after arguments", count)
+            }
+            DecodeError::InvalidBool { arg, value } => {
+                write!(f, "invalid bool for argument '{}': {}", arg, value)
+            }
+            DecodeError::InvalidUtf8 { arg } => {
+                write!(f, "invalid UTF-8 in string argument '{}'", arg)
+            }
+            DecodeError::MissingAccounts { expected, found } => {
+                write!(f, "expected {} accounts, found {}", expected, found)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/// Decode raw instruction data and account keys against a schema.
+///
+/// Matches the discriminator to a tool, decodes every argument by its
+/// [`ArgType`] and pairs each account key with its declared name. Accounts
+/// beyond the declared list are returned in `remaining_accounts`; leftover
+/// or missing data bytes are errors.
+///
+/// # Example
+///
+/// ```
+/// use mcpsol_core::{decode_instruction, ArgType, ArgValue, McpSchemaBuilder, McpToolBuilder};
+///
+/// let schema = McpSchemaBuilder::new("counter")
+///     .add_tool(
+///         McpToolBuilder::new("increment")
+///             .writable("counter")
+///             .signer("authority")
+///             .arg("amount", ArgType::U64)
+///             .build()
+///     )
+///     .build();
+///
+/// let mut data = schema.tools[0].discriminator.to_vec();
+/// data.extend_from_slice(&100u64.to_le_bytes());
+///
+/// let decoded = decode_instruction(&schema, &data, &[[1u8; 32], [2u8; 32]]).unwrap();
+/// assert_eq!(decoded.tool.name, "increment");
+/// assert_eq!(decoded.arg("amount"), Some(&ArgValue::U64(100)));
+/// assert_eq!(decoded.account("authority"), Some(&[2u8; 32]));
+/// ```
+pub fn decode_instruction<'a>(
+    schema: &'a McpSchema,
+    data: &[u8],
+    accounts: &[[u8; 32]],
+) -> Result<DecodedInstruction<'a>, DecodeError> {
+    let discriminator = data
+        .get(..schema.discriminator_len)
+        .ok_or(DecodeError::MissingDiscriminator { len: data.len() })?;
+
+    let tool = schema
+        .tool_by_discriminator(data)
+        .ok_or_else(|| DecodeError::UnknownDiscriminator(discriminator.to_vec()))?;
+
+    let args = decode_args(tool, &data[tool.discriminator_len..])?;
+
+    if accounts.len() < tool.accounts.len() {
+        return Err(DecodeError::MissingAccounts {
+            expected: tool.accounts.len(),
+            found: accounts.len(),
+        });
+    }
+
+    let (declared, remaining) = accounts.split_at(tool.accounts.len());
+    let accounts = tool
+        .accounts
+        .iter()
+        .zip(declared)
+        .map(|(meta, key)| DecodedAccount { meta, key: *key })
+        .collect();
+
+    Ok(DecodedInstruction {
+        tool,
+        args,
+        accounts,
+        remaining_accounts: remaining.to_vec(),
+    })
+}
+
+/// Decode a tool's arguments from instruction data following the discriminator.
+///
+/// Every declared argument must be present and no bytes may be left over.
+pub fn decode_args<'a>(tool: &'a McpTool, data: &[u8]) -> Result<Vec<DecodedArg<'a>>, DecodeError> {
+    let mut reader = Reader { data, offset: 0 };
+    let mut args = Vec::with_capacity(tool.args.len());
+
+    for arg in &tool.args {
+        let value = reader.read_value(&arg.name, arg.arg_type)?;
+        args.push(DecodedArg {
+            name: &arg.name,
+            arg_type: arg.arg_type,
+            value,
+        });
+    }
+
+    let trailing = data.len() - reader.offset;
+    if trailing > 0 {
+        return Err(DecodeError::TrailingBytes { count: trailing });
+    }
+
+    Ok(args)
+}
+
+/// Sequential little-endian reader over argument bytes
+struct Reader<'d> {
+    data: &'d [u8],
+    offset: usize,
+}
+
+impl<'d> Reader<'d> {
+    fn take(&mut self, arg: &str, len: usize) -> Result<&'d [u8], DecodeError> {
+        let available = self.data.len() - self.offset;
+        if available < len {
+            return Err(DecodeError::MissingBytes {
+                arg: arg.into(),
+                needed: len,
+                available,
+            });
+        }
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self, arg: &str) -> Result<[u8; N], DecodeError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(arg, N)?);
+        Ok(out)
+    }
+
+    /// Borsh length-prefixed byte sequence (u32 length)
+    fn take_prefixed(&mut self, arg: &str) -> Result<&'d [u8], DecodeError> {
+        let len = u32::from_le_bytes(self.take_array(arg)?) as usize;
+        self.take(arg, len)
+    }
+
+    fn read_value(&mut self, arg: &str, arg_type: ArgType) -> Result<ArgValue, DecodeError> {
+        Ok(match arg_type {
+            ArgType::U8 => ArgValue::U8(u8::from_le_bytes(self.take_array(arg)?)),
+            ArgType::U16 => ArgValue::U16(u16::from_le_bytes(self.take_array(arg)?)),
+            ArgType::U32 => ArgValue::U32(u32::from_le_bytes(self.take_array(arg)?)),
+            ArgType::U64 => ArgValue::U64(u64::from_le_bytes(self.take_array(arg)?)),
+            ArgType::U128 => ArgValue::U128(u128::from_le_bytes(self.take_array(arg)?)),
+            ArgType::I8 => ArgValue::I8(i8::from_le_bytes(self.take_array(arg)?)),
+            ArgType::I16 => ArgValue::I16(i16::from_le_bytes(self.take_array(arg)?)),
+            ArgType::I32 => ArgValue::I32(i32::from_le_bytes(self.take_array(arg)?)),
+            ArgType::I64 => ArgValue::I64(i64::from_le_bytes(self.take_array(arg)?)),
+            ArgType::I128 => ArgValue::I128(i128::from_le_bytes(self.take_array(arg)?)),
+            ArgType::Bool => match self.take_array::<1>(arg)?[0] {
+                0 => ArgValue::Bool(false),
+                1 => ArgValue::Bool(true),
+                value => {
+                    return Err(DecodeError::InvalidBool {
+                        arg: arg.into(),
+                        value,
+                    })
+                }
+            },
+            ArgType::Pubkey => ArgValue::Pubkey(self.take_array(arg)?),
+            ArgType::String => {
+                let bytes = self.take_prefixed(arg)?;
+                let s = core::str::from_utf8(bytes)
+                    .map_err(|_| DecodeError::InvalidUtf8 { arg: arg.into() })?;
+                ArgValue::String(s.into())
+            }
+            ArgType::Bytes => ArgValue::Bytes(self.take_prefixed(arg)?.to_vec()),
+            ArgType::FixedBytes(len) => ArgValue::Bytes(self.take(arg, len)?.to_vec()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{McpSchemaBuilder, McpToolBuilder};
+
+    fn schema() -> McpSchema {
+        McpSchemaBuilder::new("test")
+            .add_tool(
+                McpToolBuilder::new("transfer")
+                    .signer_writable("from")
+                    .writable("to")
+                    .arg("amount", ArgType::U64)
+                    .arg("memo", ArgType::String)
+                    .arg("urgent", ArgType::Bool)
+                    .build()
+            )
+            .add_tool(
+                McpToolBuilder::new("configure")
+                    .arg("fee", ArgType::I16)
+                    .arg("admin", ArgType::Pubkey)
+                    .arg("blob", ArgType::Bytes)
+                    .arg("seed", ArgType::FixedBytes(4))
+                    .arg("cap", ArgType::U128)
+                    .build()
+            )
+            .build()
+    }
+
+    fn transfer_data(memo: &str) -> Vec<u8> {
+        let mut data = crate::instruction_discriminator("transfer").to_vec();
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(&(memo.len() as u32).to_le_bytes());
+        data.extend_from_slice(memo.as_bytes());
+        data.push(1);
+        data
+    }
+
+    #[test]
+    fn test_decode_transfer() {
+        let schema = schema();
+        let data = transfer_data("rent");
+        let decoded = decode_instruction(&schema, &data, &[[1; 32], [2; 32], [3; 32]]).unwrap();
+
+        assert_eq!(decoded.tool.name, "transfer");
+        assert_eq!(decoded.arg("amount"), Some(&ArgValue::U64(500)));
+        assert_eq!(decoded.arg("memo"), Some(&ArgValue::String("rent".into())));
+        assert_eq!(decoded.arg("urgent"), Some(&ArgValue::Bool(true)));
+
+        assert_eq!(decoded.accounts.len(), 2);
+        assert_eq!(decoded.accounts[0].meta.name, "from");
+        assert!(decoded.accounts[0].meta.is_signer);
+        assert_eq!(decoded.account("to"), Some(&[2; 32]));
+        assert_eq!(decoded.remaining_accounts, vec![[3; 32]]);
+    }
+
+    #[test]
+    fn test_decode_short_discriminators() {
+        let schema = McpSchemaBuilder::new("test")
+            .discriminator_len(1)
+            .unwrap()
+            .add_tool(McpToolBuilder::new("transfer").arg("amount", ArgType::U64).build())
+            .build();
+        let mut data = crate::instruction_discriminator("transfer")[..1].to_vec();
+        data.extend_from_slice(&500u64.to_le_bytes());
+
+        let decoded = decode_instruction(&schema, &data, &[]).unwrap();
+        assert_eq!(decoded.tool.name, "transfer");
+        assert_eq!(decoded.arg("amount"), Some(&ArgValue::U64(500)));
+
+        let unknown = decode_instruction(&schema, &[!data[0]], &[]).unwrap_err();
+        assert_eq!(unknown, DecodeError::UnknownDiscriminator(vec![!data[0]]));
+        assert_eq!(unknown.to_string(), format!("unknown discriminator: {:02x}", !data[0]));
+        assert_eq!(
+            decode_instruction(&schema, &[], &[]).unwrap_err(),
+            DecodeError::MissingDiscriminator { len: 0 }
+        );
+    }
+
+    #[test]
+    fn test_decode_all_widths() {
+        let schema = schema();
+        let mut data = crate::instruction_discriminator("configure").to_vec();
+        data.extend_from_slice(&(-7i16).to_le_bytes());
+        data.extend_from_slice(&[9u8; 32]);
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        data.extend_from_slice(&u128::MAX.to_le_bytes());
+
+        let decoded = decode_instruction(&schema, &data, &[]).unwrap();
+        assert_eq!(decoded.arg("fee"), Some(&ArgValue::I16(-7)));
+        assert_eq!(decoded.arg("admin"), Some(&ArgValue::Pubkey([9; 32])));
+        assert_eq!(decoded.arg("blob"), Some(&ArgValue::Bytes(vec![0xaa, 0xbb, 0xcc])));
+        assert_eq!(decoded.arg("seed"), Some(&ArgValue::Bytes(vec![1, 2, 3, 4])));
+        assert_eq!(decoded.arg("cap"), Some(&ArgValue::U128(u128::MAX)));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let schema = schema();
+        let accounts = [[0; 32]; 2];
+
+        assert_eq!(
+            decode_instruction(&schema, &[1, 2, 3], &accounts).unwrap_err(),
+            DecodeError::MissingDiscriminator { len: 3 }
+        );
+        assert!(matches!(
+            decode_instruction(&schema, &[0; 8], &accounts).unwrap_err(),
+            DecodeError::UnknownDiscriminator(_)
+        ));
+
+        let data = transfer_data("x");
+        assert_eq!(
+            decode_instruction(&schema, &data[..12], &accounts).unwrap_err(),
+            DecodeError::MissingBytes { arg: "amount".into(), needed: 8, available: 4 }
+        );
+
+        let mut extra = data.clone();
+        extra.extend_from_slice(&[0, 0]);
+        assert_eq!(
+            decode_instruction(&schema, &extra, &accounts).unwrap_err(),
+            DecodeError::TrailingBytes { count: 2 }
+        );
+
+        assert_eq!(
+            decode_instruction(&schema, &data, &accounts[..1]).unwrap_err(),
+            DecodeError::MissingAccounts { expected: 2, found: 1 }
+        );
+
+        let mut bad_bool = data;
+        *bad_bool.last_mut().unwrap() = 2;
+        assert_eq!(
+            decode_instruction(&schema, &bad_bool, &accounts).unwrap_err(),
+            DecodeError::InvalidBool { arg: "urgent".into(), value: 2 }
+        );
+    }
+}
@@ -7,9 +7,15 @@
 #[cfg(not(feature = "std"))]
 use alloc::{string::String, vec::Vec, format};
 
-use crate::{McpSchema, McpTool, PROTOCOL_VERSION};
+use crate::{McpAccountMeta, McpError, McpSchema, McpTool, PROTOCOL_VERSION};
 use crate::discriminator::discriminator_to_hex;
 
+/// Schema type of a variadic trailing account list
+pub const REMAINING_ACCOUNTS_TYPE: &str = "pubkey[]";
+
+/// Schema type of an account that may be omitted by passing the program id
+pub const OPTIONAL_ACCOUNT_TYPE: &str = "pubkey?";
+
 // ============================================================================
 // Paginated Verbose Schema (for AI agents with full descriptions)
 // ============================================================================
@@ -33,7 +39,17 @@ pub fn generate_paginated_schema(schema: &McpSchema, cursor: u8) -> String {
     json.push_str(PROTOCOL_VERSION);
     json.push_str("\",\"name\":\"");
     escape_json_into(&schema.name, &mut json);
-    json.push_str("\",\"tools\":[");
+    json.push('"');
+
+    if let Some(ref desc) = schema.description {
+        json.push_str(",\"description\":\"");
+        escape_json_into(desc, &mut json);
+        json.push('"');
+    }
+
+    push_discriminator_len(schema.discriminator_len, "discriminatorLength", &mut json);
+
+    json.push_str(",\"tools\":[");
 
     // Get the tool at cursor index
     if let Some(tool) = schema.tools.get(cursor_idx) {
@@ -42,6 +58,13 @@ pub fn generate_paginated_schema(schema: &McpSchema, cursor: u8) -> String {
 
     json.push(']');
 
+    // Errors are listed once, on the first page
+    if cursor_idx == 0 && !schema.errors.is_empty() {
+        json.push_str(",\"errors\":[");
+        push_errors(&schema.errors, ["code", "name", "message"], &mut json);
+        json.push(']');
+    }
+
     // Add nextCursor if more tools exist
     if cursor_idx + 1 < schema.tools.len() {
         json.push_str(",\"nextCursor\":\"");
@@ -87,39 +110,28 @@ fn generate_verbose_tool(tool: &McpTool, json: &mut String) {
 
     // Discriminator (for Solana instruction routing)
     json.push_str(",\"discriminator\":\"");
-    let hex = discriminator_to_hex(&tool.discriminator);
-    json.push_str(core::str::from_utf8(&hex).unwrap_or("0000000000000000"));
+    push_tag_hex(&tool.discriminator, tool.discriminator_len, json);
     json.push('"');
 
     // Parameters object with full descriptions
-    if !tool.accounts.is_empty() || !tool.args.is_empty() {
+    if !tool.accounts.is_empty() || tool.remaining_accounts.is_some() || !tool.args.is_empty() {
         json.push_str(",\"parameters\":{");
 
         let mut first = true;
 
         // Accounts
-        for acc in &tool.accounts {
+        if !tool.accounts.is_empty() {
+            first = false;
+            json.push_str(&generate_verbose_accounts(&tool.accounts));
+        }
+
+        // Variadic trailing accounts
+        if let Some(ref acc) = tool.remaining_accounts {
             if !first {
                 json.push(',');
             }
             first = false;
-
-            json.push('"');
-            escape_json_into(&acc.name, json);
-            json.push_str("\":{\"type\":\"pubkey\"");
-
-            if acc.is_signer {
-                json.push_str(",\"signer\":true");
-            }
-            if acc.is_writable {
-                json.push_str(",\"writable\":true");
-            }
-            if let Some(ref desc) = acc.description {
-                json.push_str(",\"description\":\"");
-                escape_json_into(desc, json);
-                json.push('"');
-            }
-            json.push('}');
+            push_verbose_account(acc, REMAINING_ACCOUNTS_TYPE, json);
         }
 
         // Args
@@ -132,7 +144,7 @@ fn generate_verbose_tool(tool: &McpTool, json: &mut String) {
             json.push('"');
             escape_json_into(&arg.name, json);
             json.push_str("\":{\"type\":\"");
-            json.push_str(arg.arg_type.compact_name());
+            arg.arg_type.push_type_name(json);
             json.push('"');
 
             if let Some(ref desc) = arg.description {
@@ -146,6 +158,45 @@ fn generate_verbose_tool(tool: &McpTool, json: &mut String) {
         json.push('}');
     }
 
+    push_returns(tool, "returns", json);
+    push_aliases(tool, "deprecatedAliases", json);
+    json.push('}');
+}
+
+/// Generate the verbose parameter entries for a list of accounts.
+///
+/// Returns the comma-separated `"name":{"type":"pubkey",...}` entries as
+/// they appear inside a paginated tool's `parameters` object.
+pub fn generate_verbose_accounts(accounts: &[McpAccountMeta]) -> String {
+    let mut json = String::new();
+    for (i, acc) in accounts.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        push_verbose_account(acc, acc.schema_type(), &mut json);
+    }
+    json
+}
+
+/// Write one verbose account entry with the given type
+fn push_verbose_account(acc: &McpAccountMeta, ty: &str, json: &mut String) {
+    json.push('"');
+    escape_json_into(&acc.name, json);
+    json.push_str("\":{\"type\":\"");
+    json.push_str(ty);
+    json.push('"');
+
+    if acc.is_signer {
+        json.push_str(",\"signer\":true");
+    }
+    if acc.is_writable {
+        json.push_str(",\"writable\":true");
+    }
+    if let Some(ref desc) = acc.description {
+        json.push_str(",\"description\":\"");
+        escape_json_into(desc, json);
+        json.push('"');
+    }
     json.push('}');
 }
 
@@ -164,13 +215,17 @@ pub fn generate_paginated_schema_bytes(schema: &McpSchema, cursor: u8) -> Vec<u8
 /// ```json
 /// {"v":"2024-11-05","name":"program","tools":[...]}
 /// ```
+///
+/// Custom errors, when declared, follow as `"e":[{"c":6000,"n":"..","m":".."}]`.
 pub fn generate_compact_schema(schema: &McpSchema) -> String {
     let mut json = String::with_capacity(800);
     json.push_str("{\"v\":\"");
     json.push_str(PROTOCOL_VERSION);
     json.push_str("\",\"name\":\"");
     escape_json_into(&schema.name, &mut json);
-    json.push_str("\",\"tools\":[");
+    json.push('"');
+    push_discriminator_len(schema.discriminator_len, "dl", &mut json);
+    json.push_str(",\"tools\":[");
 
     for (i, tool) in schema.tools.iter().enumerate() {
         if i > 0 {
@@ -179,7 +234,60 @@ pub fn generate_compact_schema(schema: &McpSchema) -> String {
         generate_tool_json(tool, &mut json);
     }
 
-    json.push_str("]}");
+    json.push(']');
+
+    if !schema.errors.is_empty() {
+        json.push_str(",\"e\":[");
+        push_errors(&schema.errors, ["c", "n", "m"], &mut json);
+        json.push(']');
+    }
+
+    json.push('}');
+    json
+}
+
+/// Generate the compact error entries, as they appear in the `"e"` array.
+///
+/// Entries are comma-separated `{"c":6000,"n":"..","m":".."}` objects.
+pub fn generate_compact_errors(errors: &[McpError]) -> String {
+    let mut json = String::new();
+    push_errors(errors, ["c", "n", "m"], &mut json);
+    json
+}
+
+/// Write error objects with the given code, name and message keys
+fn push_errors(errors: &[McpError], keys: [&str; 3], json: &mut String) {
+    let [code_key, name_key, message_key] = keys;
+    for (i, err) in errors.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str("{\"");
+        json.push_str(code_key);
+        json.push_str("\":");
+        json.push_str(&format!("{}", err.code));
+        json.push_str(",\"");
+        json.push_str(name_key);
+        json.push_str("\":\"");
+        escape_json_into(&err.name, json);
+        json.push('"');
+        if let Some(ref message) = err.message {
+            json.push_str(",\"");
+            json.push_str(message_key);
+            json.push_str("\":\"");
+            escape_json_into(message, json);
+            json.push('"');
+        }
+        json.push('}');
+    }
+}
+
+/// Generate the compact JSON object for a single tool.
+///
+/// This is the entry [`generate_compact_schema`] writes into its `tools` array.
+pub fn generate_compact_tool(tool: &McpTool) -> String {
+    let mut json = String::with_capacity(estimate_single_tool_size(Some(tool)));
+    generate_tool_json(tool, &mut json);
     json
 }
 
@@ -199,12 +307,14 @@ fn generate_tool_json(tool: &McpTool, json: &mut String) {
     json.push_str(",\"d\":\"");
 
     // Discriminator as hex
-    let hex = discriminator_to_hex(&tool.discriminator);
-    json.push_str(core::str::from_utf8(&hex).unwrap_or("0000000000000000"));
+    push_tag_hex(&tool.discriminator, tool.discriminator_len, json);
 
     // Only include p and r if there are properties
-    if tool.accounts.is_empty() && tool.args.is_empty() {
-        json.push_str("\"}");
+    if tool.accounts.is_empty() && tool.remaining_accounts.is_none() && tool.args.is_empty() {
+        json.push('"');
+        push_returns(tool, "o", json);
+        push_aliases(tool, "a", json);
+        json.push('}');
         return;
     }
 
@@ -224,7 +334,9 @@ fn generate_tool_json(tool: &McpTool, json: &mut String) {
         json.push('"');
         escape_json_into(&acc.name, json);
         json.push_str(acc.suffix());
-        json.push_str("\":\"pubkey\"");
+        json.push_str("\":\"");
+        json.push_str(acc.schema_type());
+        json.push('"');
 
         // Build key for required array
         let mut key = String::new();
@@ -233,6 +345,21 @@ fn generate_tool_json(tool: &McpTool, json: &mut String) {
         required.push(key);
     }
 
+    // Variadic trailing accounts: typed "pubkey[]" and never required
+    if let Some(ref acc) = tool.remaining_accounts {
+        if !first {
+            json.push(',');
+        }
+        first = false;
+
+        json.push('"');
+        escape_json_into(&acc.name, json);
+        json.push_str(acc.suffix());
+        json.push_str("\":\"");
+        json.push_str(REMAINING_ACCOUNTS_TYPE);
+        json.push('"');
+    }
+
     // Add args
     for arg in &tool.args {
         if !first {
@@ -243,7 +370,7 @@ fn generate_tool_json(tool: &McpTool, json: &mut String) {
         json.push('"');
         escape_json_into(&arg.name, json);
         json.push_str("\":\"");
-        json.push_str(arg.arg_type.compact_name());
+        arg.arg_type.push_type_name(json);
         json.push('"');
 
         let mut key = String::new();
@@ -263,7 +390,61 @@ fn generate_tool_json(tool: &McpTool, json: &mut String) {
         json.push('"');
     }
 
-    json.push_str("]}");
+    json.push(']');
+    push_returns(tool, "o", json);
+    push_aliases(tool, "a", json);
+    json.push('}');
+}
+
+/// Write the return data layout of a tool as `,"key":<layout>`, or nothing
+/// when it returns nothing.
+fn push_returns(tool: &McpTool, key: &str, json: &mut String) {
+    if let Some(ref layout) = tool.returns {
+        json.push_str(",\"");
+        json.push_str(key);
+        json.push_str("\":");
+        json.push_str(layout);
+    }
+}
+
+/// Write the deprecated names of a tool as `,"key":{"old_name":"<hex>"}`,
+/// or nothing when it has none.
+fn push_aliases(tool: &McpTool, key: &str, json: &mut String) {
+    if tool.aliases.is_empty() {
+        return;
+    }
+    json.push_str(",\"");
+    json.push_str(key);
+    json.push_str("\":{");
+    for (i, alias) in tool.aliases.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push('"');
+        escape_json_into(alias, json);
+        json.push_str("\":\"");
+        push_tag_hex(&crate::instruction_discriminator(alias), tool.discriminator_len, json);
+        json.push('"');
+    }
+    json.push('}');
+}
+
+/// Write the first `len` bytes of a discriminator as hex
+fn push_tag_hex(discriminator: &[u8; 8], len: usize, json: &mut String) {
+    let hex = discriminator_to_hex(discriminator);
+    json.push_str(core::str::from_utf8(&hex[..2 * len]).unwrap_or("0000000000000000"));
+}
+
+/// Write `,"key":len` for short discriminators; full ones are the default
+/// and not advertised.
+fn push_discriminator_len(len: usize, key: &str, json: &mut String) {
+    if len == crate::DISCRIMINATOR_LEN {
+        return;
+    }
+    json.push_str(",\"");
+    json.push_str(key);
+    json.push_str("\":");
+    json.push_str(&format!("{}", len));
 }
 
 /// Escape JSON special characters into a string buffer
@@ -294,6 +475,11 @@ pub fn estimate_schema_size(schema: &McpSchema) -> usize {
         size += estimate_single_tool_size(Some(tool));
     }
 
+    // Errors: {"c":6000,"n":"...","m":"..."}
+    for err in &schema.errors {
+        size += err.name.len() + err.message.as_ref().map_or(0, |m| m.len() + 7) + 20;
+    }
+
     size
 }
 
@@ -317,7 +503,11 @@ pub fn estimate_single_tool_size(tool: Option<&McpTool>) -> usize {
 
     // Accounts: "name_suffix":"pubkey"
     for acc in &tool.accounts {
-        size += acc.name.len() + 15; // name + suffix + "pubkey" + quotes + colon
+        size += acc.name.len() + 16; // name + suffix + "pubkey?" + quotes + colon
+    }
+
+    if let Some(ref acc) = tool.remaining_accounts {
+        size += acc.name.len() + 17; // name + suffix + "pubkey[]" + quotes + colon
     }
 
     // Args: "name":"type"
@@ -325,6 +515,16 @@ pub fn estimate_single_tool_size(tool: Option<&McpTool>) -> usize {
         size += arg.name.len() + 10; // name + type + quotes + colon
     }
 
+    // Return layout: ,"o":<layout>
+    if let Some(ref layout) = tool.returns {
+        size += layout.len() + 5;
+    }
+
+    // Aliases: "name":"<hex>"
+    for alias in &tool.aliases {
+        size += alias.len() + 24; // name + hex + quotes + colon
+    }
+
     size
 }
 
@@ -455,6 +655,164 @@ mod tests {
         assert!(json.contains(r#""arg\"name":"u64""#));
     }
 
+    #[test]
+    fn test_remaining_accounts() {
+        let schema = McpSchemaBuilder::new("multisig")
+            .add_tool(
+                McpToolBuilder::new("approve")
+                    .writable("proposal")
+                    .remaining_accounts_desc("signers", "Owners approving", true, false)
+                    .arg("index", ArgType::U8)
+                    .build()
+            )
+            .build();
+
+        // Typed as a list after the fixed accounts, and not required
+        let json = generate_compact_schema(&schema);
+        assert!(json.contains(
+            r#""p":{"proposal_w":"pubkey","signers_s":"pubkey[]","index":"u8"},"r":["proposal_w","index"]"#
+        ));
+
+        let page = generate_paginated_schema(&schema, 0);
+        assert!(page.contains(
+            r#""signers":{"type":"pubkey[]","signer":true,"description":"Owners approving"}"#
+        ));
+    }
+
+    #[test]
+    fn test_optional_accounts() {
+        let schema = McpSchemaBuilder::new("market")
+            .add_tool(
+                McpToolBuilder::new("swap")
+                    .signer("user")
+                    .optional_account("referrer", false, true)
+                    .build()
+            )
+            .build();
+
+        // Typed "pubkey?" and still required, to keep its position
+        let json = generate_compact_schema(&schema);
+        assert!(json.contains(
+            r#""p":{"user_s":"pubkey","referrer_w":"pubkey?"},"r":["user_s","referrer_w"]"#
+        ));
+
+        let page = generate_paginated_schema(&schema, 0);
+        assert!(page.contains(r#""referrer":{"type":"pubkey?","writable":true}"#));
+    }
+
+    #[test]
+    fn test_aliases() {
+        use crate::instruction_discriminator;
+
+        let schema = McpSchemaBuilder::new("counter")
+            .add_tool(McpToolBuilder::new("increment").arg("amount", ArgType::U64).alias("add").build())
+            .add_tool(McpToolBuilder::new("reset").alias("clear").alias("zero").build())
+            .build();
+        let hex = |name: &str| {
+            let hex = discriminator_to_hex(&instruction_discriminator(name));
+            String::from_utf8(hex.to_vec()).unwrap()
+        };
+
+        // Old names map to their own discriminators inside the new tool
+        let json = generate_compact_schema(&schema);
+        assert!(json.contains(&format!(r#""r":["amount"],"a":{{"add":"{}"}}}}"#, hex("add"))));
+        assert!(json.contains(&format!(
+            r#""d":"{}","a":{{"clear":"{}","zero":"{}"}}}}"#,
+            hex("reset"),
+            hex("clear"),
+            hex("zero"),
+        )));
+        assert!(json.len() <= estimate_schema_size(&schema));
+
+        let page = generate_paginated_schema(&schema, 1);
+        assert!(page.contains(&format!(r#""deprecatedAliases":{{"clear":"{}","#, hex("clear"))));
+    }
+
+    #[test]
+    fn test_return_layouts() {
+        let schema = McpSchemaBuilder::new("vault")
+            .add_tool(McpToolBuilder::new("balance").writable("vault").returns(ArgType::U64).build())
+            .add_tool(
+                McpToolBuilder::new("info")
+                    .returns_layout(r#"{"balance":"u64","owner":"pubkey"}"#)
+                    .alias("get_info")
+                    .build(),
+            )
+            .add_tool(McpToolBuilder::new("close").build())
+            .build();
+
+        let json = generate_compact_schema(&schema);
+        assert!(json.contains(r#""r":["vault_w"],"o":"u64"}"#));
+        assert!(json.contains(r#","o":{"balance":"u64","owner":"pubkey"},"a":{"get_info":"#));
+        assert_eq!(json.matches(r#""o":"#).count(), 2);
+        assert!(json.len() <= estimate_schema_size(&schema));
+
+        let page = generate_paginated_schema(&schema, 0);
+        assert!(page.contains(r#""parameters":{"vault":{"type":"pubkey","writable":true}},"returns":"u64"}"#));
+        assert!(!generate_paginated_schema(&schema, 2).contains("returns"));
+    }
+
+    #[test]
+    fn test_short_discriminators() {
+        use crate::instruction_discriminator;
+
+        let schema = McpSchemaBuilder::new("counter")
+            .discriminator_len(2)
+            .unwrap()
+            .add_tool(McpToolBuilder::new("increment").alias("add").build())
+            .add_tool(McpToolBuilder::new("list_tools").build())
+            .build();
+        let hex = |name: &str, len: usize| {
+            let hex = discriminator_to_hex(&instruction_discriminator(name));
+            String::from_utf8(hex[..2 * len].to_vec()).unwrap()
+        };
+
+        // Tools carry their tag, list_tools its full discriminator
+        let json = generate_compact_schema(&schema);
+        assert!(json.starts_with(r#"{"v":"2024-11-05","name":"counter","dl":2,"tools":["#));
+        assert!(json.contains(&format!(
+            r#"{{"n":"increment","d":"{}","a":{{"add":"{}"}}}}"#,
+            hex("increment", 2),
+            hex("add", 2),
+        )));
+        assert!(json.contains(&format!(r#"{{"n":"list_tools","d":"{}"}}"#, hex("list_tools", 8))));
+
+        let page = generate_paginated_schema(&schema, 0);
+        assert!(page.contains(r#""name":"counter","discriminatorLength":2,"tools""#));
+        assert!(page.contains(&format!(r#""discriminator":"{}""#, hex("increment", 2))));
+
+        // Full discriminators are the default and not advertised
+        let full = McpSchemaBuilder::new("counter").build();
+        assert!(!generate_compact_schema(&full).contains("dl"));
+        assert!(!generate_paginated_schema(&full, 0).contains("discriminatorLength"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let schema = McpSchemaBuilder::new("vault")
+            .add_tool(McpToolBuilder::new("withdraw").build())
+            .add_tool(McpToolBuilder::new("deposit").build())
+            .error_desc(6000, "InsufficientFunds", "vault balance too low")
+            .error(6001, "Frozen")
+            .build();
+
+        let json = generate_compact_schema(&schema);
+        assert!(json.ends_with(
+            r#"],"e":[{"c":6000,"n":"InsufficientFunds","m":"vault balance too low"},{"c":6001,"n":"Frozen"}]}"#
+        ));
+        assert!(estimate_schema_size(&schema) >= json.len());
+
+        // Listed once, on the first verbose page
+        let first = generate_paginated_schema(&schema, 0);
+        assert!(first.contains(
+            r#""errors":[{"code":6000,"name":"InsufficientFunds","message":"vault balance too low"},{"code":6001,"name":"Frozen"}]"#
+        ));
+        assert!(!generate_paginated_schema(&schema, 1).contains("errors"));
+
+        assert_eq!(schema.error(6001).map(|e| e.name.as_str()), Some("Frozen"));
+        assert!(schema.error(6002).is_none());
+    }
+
     // ========================================================================
     // Paginated Schema Tests
     // ========================================================================
@@ -522,6 +880,21 @@ mod tests {
         assert!(!json.contains("nextCursor"));
     }
 
+    #[test]
+    fn test_paginated_schema_program_description() {
+        let schema = McpSchemaBuilder::new("counter")
+            .description("A \"simple\" counter")
+            .add_tool(McpToolBuilder::new("initialize").build())
+            .build();
+
+        let page = generate_paginated_schema(&schema, 0);
+        assert!(page.contains(r#""name":"counter","description":"A \"simple\" counter","tools""#));
+
+        // Compact format stays within budget by omitting it
+        let compact = generate_compact_schema(&schema);
+        assert!(!compact.contains("simple"));
+    }
+
     #[test]
     fn test_paginated_schema_out_of_bounds() {
         let schema = McpSchemaBuilder::new("counter")
@@ -11,29 +11,45 @@ extern crate alloc;
 #[cfg(not(feature = "std"))]
 use alloc::{string::String, vec::Vec, format};
 
+mod decode;
 mod discriminator;
 mod schema;
 mod json;
 
+pub use decode::*;
 pub use discriminator::*;
 pub use schema::*;
 pub use json::{
     // Compact schema (backwards compatible)
     generate_compact_schema,
+    generate_compact_tool,
+    generate_compact_errors,
     generate_schema_bytes,
     estimate_schema_size,
     estimate_single_tool_size,
     // Paginated verbose schema (full descriptions)
     generate_paginated_schema,
     generate_paginated_schema_bytes,
+    generate_verbose_accounts,
+    REMAINING_ACCOUNTS_TYPE,
+    OPTIONAL_ACCOUNT_TYPE,
 };
 
 /// MCP protocol version
 pub const PROTOCOL_VERSION: &str = "2024-11-05";
 
+/// Length of a full discriminator. Programs may tag instruction data with
+/// fewer of its bytes; see [`McpSchema::discriminator_len`].
+pub const DISCRIMINATOR_LEN: usize = 8;
+
 /// Universal list_tools discriminator
 /// sha256("global:list_tools")[0..8]
 pub const LIST_TOOLS_DISCRIMINATOR: [u8; 8] = [0x42, 0x19, 0x5e, 0x6a, 0x55, 0xfd, 0x41, 0xc0];
 
 /// Maximum size for return_data on Solana (1024 bytes)
 pub const MAX_RETURN_DATA_SIZE: usize = 1024;
+
+/// First `ProgramError::Custom` code of program-defined errors.
+///
+/// Codes below this are reserved for the SDK's own errors.
+pub const CUSTOM_ERROR_OFFSET: u32 = 6000;
@@ -13,6 +13,8 @@
 #[cfg(not(feature = "std"))]
 use alloc::{string::String, vec::Vec};
 
+use core::fmt;
+
 /// A complete MCP program schema for on-chain tool discovery.
 ///
 /// This is the compact schema format designed to fit within Solana's
@@ -38,8 +40,46 @@ use alloc::{string::String, vec::Vec};
 pub struct McpSchema {
     /// Program name
     pub name: String,
+    /// Program description (verbose pages only, omitted from compact schema)
+    pub description: Option<String>,
     /// Available tools (instructions)
     pub tools: Vec<McpTool>,
+    /// Custom program errors, by `ProgramError::Custom` code
+    pub errors: Vec<McpError>,
+    /// Leading discriminator bytes that tag instruction data: 1, 2, 4 or 8.
+    /// `list_tools` always answers to its full discriminator.
+    pub discriminator_len: usize,
+}
+
+impl McpSchema {
+    /// Find a tool by name, or by a deprecated alias of it
+    pub fn tool(&self, name: &str) -> Option<&McpTool> {
+        self.tools
+            .iter()
+            .find(|t| t.name == name)
+            .or_else(|| self.tools.iter().find(|t| t.aliases.iter().any(|a| a == name)))
+    }
+
+    /// Find the tool whose discriminator tags instruction data starting with `data`
+    pub fn tool_by_discriminator(&self, data: &[u8]) -> Option<&McpTool> {
+        self.tools.iter().find(|t| t.routes(data))
+    }
+
+    /// Find a custom error by its `ProgramError::Custom` code
+    pub fn error(&self, code: u32) -> Option<&McpError> {
+        self.errors.iter().find(|e| e.code == code)
+    }
+}
+
+/// A custom program error, as returned in `ProgramError::Custom(code)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpError {
+    /// Custom error code
+    pub code: u32,
+    /// Variant name, e.g. `InsufficientFunds`
+    pub name: String,
+    /// Human-readable message
+    pub message: Option<String>,
 }
 
 /// An MCP tool (instruction) definition.
@@ -54,10 +94,38 @@ pub struct McpTool {
     pub description: Option<String>,
     /// 8-byte instruction discriminator (SHA256 of "global:{name}")
     pub discriminator: [u8; 8],
+    /// Leading bytes of `discriminator` that tag instruction data, set for
+    /// every tool by [`McpSchemaBuilder::discriminator_len`]
+    pub discriminator_len: usize,
     /// Required accounts for this instruction
     pub accounts: Vec<McpAccountMeta>,
+    /// Variadic trailing accounts passed after `accounts`, zero or more
+    pub remaining_accounts: Option<McpAccountMeta>,
     /// Instruction arguments (serialized after discriminator)
     pub args: Vec<McpArg>,
+    /// Former names, deprecated in favour of `name`. Their discriminators
+    /// still route to this instruction.
+    pub aliases: Vec<String>,
+    /// Layout of the return data as JSON: a type name such as `"u64"`, or an
+    /// object of struct fields to their layouts in order. `None` when the
+    /// instruction returns nothing.
+    pub returns: Option<String>,
+}
+
+impl McpTool {
+    /// The bytes instruction data for this tool starts with
+    pub fn tag(&self) -> &[u8] {
+        &self.discriminator[..self.discriminator_len]
+    }
+
+    /// Whether instruction data starting with `data` is dispatched to this
+    /// tool, under its name or a deprecated alias
+    pub fn routes(&self, data: &[u8]) -> bool {
+        data.starts_with(self.tag())
+            || self.aliases.iter().any(|alias| {
+                data.starts_with(&crate::instruction_discriminator(alias)[..self.discriminator_len])
+            })
+    }
 }
 
 /// Account metadata for a tool.
@@ -74,6 +142,8 @@ pub struct McpAccountMeta {
     pub is_signer: bool,
     /// Whether this account's data is modified
     pub is_writable: bool,
+    /// Whether the program id may be passed instead to leave it out
+    pub is_optional: bool,
 }
 
 impl McpAccountMeta {
@@ -87,6 +157,15 @@ impl McpAccountMeta {
             (false, false) => "",
         }
     }
+
+    /// Get the schema type: `"pubkey"`, or `"pubkey?"` when optional
+    pub const fn schema_type(&self) -> &'static str {
+        if self.is_optional {
+            crate::json::OPTIONAL_ACCOUNT_TYPE
+        } else {
+            "pubkey"
+        }
+    }
 }
 
 /// Argument definition for a tool.
@@ -137,10 +216,15 @@ pub enum ArgType {
     String,
     /// Variable-length bytes (Borsh-encoded: 4-byte length prefix)
     Bytes,
+    /// Fixed-length bytes, written as-is without a length prefix
+    FixedBytes(usize),
 }
 
 impl ArgType {
     /// Get the compact type name for schema
+    ///
+    /// [`ArgType::FixedBytes`] gives the family name `"[u8;N]"`; use
+    /// [`ArgType::push_type_name`] to write its length.
     pub const fn compact_name(&self) -> &'static str {
         match self {
             ArgType::U8 => "u8",
@@ -157,9 +241,45 @@ impl ArgType {
             ArgType::Pubkey => "pubkey",
             ArgType::String => "str",
             ArgType::Bytes => "bytes",
+            ArgType::FixedBytes(_) => "[u8;N]",
         }
     }
 
+    /// Append the schema type name, e.g. `u64` or `[u8;32]`, to `out`
+    pub fn push_type_name(&self, out: &mut String) {
+        match self {
+            ArgType::FixedBytes(len) => {
+                use core::fmt::Write;
+                let _ = write!(out, "[u8;{}]", len);
+            }
+            ty => out.push_str(ty.compact_name()),
+        }
+    }
+
+    /// Parse from a compact schema type name.
+    ///
+    /// Accepts the legacy `"int"` name as `u64`, which is how older
+    /// macro-generated schemas advertised integers.
+    pub fn from_compact_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => ArgType::U8,
+            "u16" => ArgType::U16,
+            "u32" => ArgType::U32,
+            "u64" | "int" => ArgType::U64,
+            "u128" => ArgType::U128,
+            "i8" => ArgType::I8,
+            "i16" => ArgType::I16,
+            "i32" => ArgType::I32,
+            "i64" => ArgType::I64,
+            "i128" => ArgType::I128,
+            "bool" => ArgType::Bool,
+            "pubkey" => ArgType::Pubkey,
+            "str" => ArgType::String,
+            "bytes" => ArgType::Bytes,
+            t => ArgType::FixedBytes(array_len(t)?),
+        })
+    }
+
     /// Parse from Rust type string
     pub fn from_rust_type(ty: &str) -> Self {
         match ty {
@@ -175,12 +295,25 @@ impl ArgType {
             "i128" => ArgType::I128,
             "bool" => ArgType::Bool,
             t if t.contains("Pubkey") => ArgType::Pubkey,
-            t if t.starts_with("Vec<u8>") || t.starts_with("[u8;") => ArgType::Bytes,
-            _ => ArgType::String,
+            t if t.starts_with("Vec<u8>") => ArgType::Bytes,
+            t => match array_len(t) {
+                Some(len) => ArgType::FixedBytes(len),
+                None => ArgType::String,
+            },
         }
     }
 }
 
+/// Length of a `[u8;N]` byte array type, ignoring whitespace
+fn array_len(ty: &str) -> Option<usize> {
+    let inner = ty.strip_prefix('[')?.strip_suffix(']')?;
+    let (elem, len) = inner.split_once(';')?;
+    if elem.trim() != "u8" {
+        return None;
+    }
+    len.trim().parse().ok()
+}
+
 /// Builder for creating MCP schemas programmatically.
 ///
 /// # Example
@@ -196,30 +329,94 @@ impl ArgType {
 #[must_use = "builders do nothing until .build() is called"]
 pub struct McpSchemaBuilder {
     name: String,
+    description: Option<String>,
     tools: Vec<McpTool>,
+    errors: Vec<McpError>,
+    discriminator_len: Option<usize>,
 }
 
 impl McpSchemaBuilder {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
+            description: None,
             tools: Vec::new(),
+            errors: Vec::new(),
+            discriminator_len: None,
         }
     }
 
+    /// Tag instruction data with the first `len` discriminator bytes (1, 2,
+    /// 4 or 8) instead of all 8. `list_tools` keeps its full discriminator.
+    pub fn discriminator_len(mut self, len: usize) -> Result<Self, InvalidDiscriminatorLen> {
+        if !matches!(len, 1 | 2 | 4 | 8) {
+            return Err(InvalidDiscriminatorLen(len));
+        }
+        self.discriminator_len = Some(len);
+        Ok(self)
+    }
+
+    /// Add a program description, shown on verbose schema pages
+    pub fn description(mut self, desc: impl Into<String>) -> Self {
+        self.description = Some(desc.into());
+        self
+    }
+
     pub fn add_tool(mut self, tool: McpTool) -> Self {
         self.tools.push(tool);
         self
     }
 
-    pub fn build(self) -> McpSchema {
+    /// Add a custom error without a message
+    pub fn error(mut self, code: u32, name: impl Into<String>) -> Self {
+        self.errors.push(McpError {
+            code,
+            name: name.into(),
+            message: None,
+        });
+        self
+    }
+
+    /// Add a custom error with a message for clients to display
+    pub fn error_desc(mut self, code: u32, name: impl Into<String>, message: impl Into<String>) -> Self {
+        self.errors.push(McpError {
+            code,
+            name: name.into(),
+            message: Some(message.into()),
+        });
+        self
+    }
+
+    pub fn build(mut self) -> McpSchema {
+        let discriminator_len = self.discriminator_len.unwrap_or(crate::DISCRIMINATOR_LEN);
+        for tool in &mut self.tools {
+            if tool.discriminator != crate::LIST_TOOLS_DISCRIMINATOR {
+                tool.discriminator_len = discriminator_len;
+            }
+        }
         McpSchema {
             name: self.name,
+            description: self.description,
             tools: self.tools,
+            errors: self.errors,
+            discriminator_len,
         }
     }
 }
 
+/// A discriminator length other than 1, 2, 4 or 8 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidDiscriminatorLen(pub usize);
+
+impl fmt::Display for InvalidDiscriminatorLen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid discriminator length {}: expected 1, 2, 4 or 8", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidDiscriminatorLen {}
+
 /// Builder for creating MCP tools (instructions).
 ///
 /// # Example
@@ -240,7 +437,10 @@ pub struct McpToolBuilder {
     name: String,
     description: Option<String>,
     accounts: Vec<McpAccountMeta>,
+    remaining_accounts: Option<McpAccountMeta>,
     args: Vec<McpArg>,
+    aliases: Vec<String>,
+    returns: Option<String>,
 }
 
 impl McpToolBuilder {
@@ -249,7 +449,10 @@ impl McpToolBuilder {
             name: name.into(),
             description: None,
             accounts: Vec::new(),
+            remaining_accounts: None,
             args: Vec::new(),
+            aliases: Vec::new(),
+            returns: None,
         }
     }
 
@@ -265,6 +468,7 @@ impl McpToolBuilder {
             description: None,
             is_signer,
             is_writable,
+            is_optional: false,
         });
         self
     }
@@ -282,6 +486,7 @@ impl McpToolBuilder {
             description: Some(desc.into()),
             is_signer,
             is_writable,
+            is_optional: false,
         });
         self
     }
@@ -313,6 +518,76 @@ impl McpToolBuilder {
         self.account_with_desc(name, desc, true, true)
     }
 
+    /// Add an account that may be left out by passing the program id in
+    /// its place.
+    ///
+    /// Rendered as `"name":"pubkey?"` and still listed as required, since
+    /// it keeps its position.
+    pub fn optional_account(
+        mut self,
+        name: impl Into<String>,
+        is_signer: bool,
+        is_writable: bool,
+    ) -> Self {
+        self.accounts.push(McpAccountMeta {
+            name: name.into(),
+            description: None,
+            is_signer,
+            is_writable,
+            is_optional: true,
+        });
+        self
+    }
+
+    /// Add an optional account with a description
+    pub fn optional_account_desc(
+        self,
+        name: impl Into<String>,
+        desc: impl Into<String>,
+        is_signer: bool,
+        is_writable: bool,
+    ) -> Self {
+        let mut builder = self.optional_account(name, is_signer, is_writable);
+        if let Some(acc) = builder.accounts.last_mut() {
+            acc.description = Some(desc.into());
+        }
+        builder
+    }
+
+    /// Declare a variadic list of accounts passed after the fixed ones.
+    ///
+    /// Rendered as `"name":"pubkey[]"` and left out of the required list.
+    pub fn remaining_accounts(
+        mut self,
+        name: impl Into<String>,
+        is_signer: bool,
+        is_writable: bool,
+    ) -> Self {
+        self.remaining_accounts = Some(McpAccountMeta {
+            name: name.into(),
+            description: None,
+            is_signer,
+            is_writable,
+            is_optional: false,
+        });
+        self
+    }
+
+    /// Declare a described variadic account list
+    pub fn remaining_accounts_desc(
+        self,
+        name: impl Into<String>,
+        desc: impl Into<String>,
+        is_signer: bool,
+        is_writable: bool,
+    ) -> Self {
+        let mut builder = self.remaining_accounts(name, is_signer, is_writable);
+        if let Some(ref mut acc) = builder.remaining_accounts {
+            acc.description = Some(desc.into());
+        }
+        builder
+    }
+
     pub fn arg(mut self, name: impl Into<String>, arg_type: ArgType) -> Self {
         self.args.push(McpArg {
             name: name.into(),
@@ -332,14 +607,40 @@ impl McpToolBuilder {
         self
     }
 
+    /// Keep routing a former name of this tool, marked deprecated in the
+    /// schema.
+    pub fn alias(mut self, name: impl Into<String>) -> Self {
+        self.aliases.push(name.into());
+        self
+    }
+
+    /// Declare that the instruction sets return data holding one value
+    pub fn returns(self, ty: ArgType) -> Self {
+        let mut layout = String::from("\"");
+        ty.push_type_name(&mut layout);
+        layout.push('"');
+        self.returns_layout(layout)
+    }
+
+    /// Declare the return data layout as JSON, e.g.
+    /// `{"balance":"u64","bump":"u8"}` for a struct
+    pub fn returns_layout(mut self, layout: impl Into<String>) -> Self {
+        self.returns = Some(layout.into());
+        self
+    }
+
     pub fn build(self) -> McpTool {
         use crate::instruction_discriminator;
         McpTool {
             discriminator: instruction_discriminator(&self.name),
+            discriminator_len: crate::DISCRIMINATOR_LEN,
             name: self.name,
             description: self.description,
             accounts: self.accounts,
+            remaining_accounts: self.remaining_accounts,
             args: self.args,
+            aliases: self.aliases,
+            returns: self.returns,
         }
     }
 }
@@ -437,4 +738,35 @@ mod tests {
         assert_eq!(schema.tools[0].accounts.len(), 2);
         assert_eq!(schema.tools[0].args.len(), 1);
     }
+
+    #[test]
+    fn test_discriminator_len() {
+        let schema = McpSchemaBuilder::new("test_program")
+            .discriminator_len(2)
+            .unwrap()
+            .add_tool(McpToolBuilder::new("transfer").build())
+            .add_tool(McpToolBuilder::new("list_tools").build())
+            .build();
+        assert_eq!(schema.tools[0].tag().len(), 2);
+        // list_tools is recognised by its discriminator
+        assert_eq!(schema.tools[1].tag(), crate::LIST_TOOLS_DISCRIMINATOR);
+
+        for len in [0, 3, 9] {
+            let err = McpSchemaBuilder::new("test_program").discriminator_len(len).unwrap_err();
+            assert_eq!(err, InvalidDiscriminatorLen(len));
+        }
+    }
+
+    #[test]
+    fn test_fixed_bytes_names() {
+        assert_eq!(ArgType::from_rust_type("[u8;32]"), ArgType::FixedBytes(32));
+        assert_eq!(ArgType::from_rust_type("[u8; 4]"), ArgType::FixedBytes(4));
+        assert_eq!(ArgType::from_rust_type("Vec<u8>"), ArgType::Bytes);
+        assert_eq!(ArgType::FixedBytes(32).compact_name(), "[u8;N]");
+        let mut name = String::new();
+        ArgType::FixedBytes(32).push_type_name(&mut name);
+        assert_eq!(name, "[u8;32]");
+        assert_eq!(ArgType::from_compact_name("[u8;32]"), Some(ArgType::FixedBytes(32)));
+        assert_eq!(ArgType::from_compact_name("[u16;2]"), None);
+    }
 }
@@ -220,6 +220,81 @@ fn benchmark_no_context_path() {
     println!("  (Skips Context::new and try_accounts)");
 }
 
+// ============================================================================
+// Dispatch Strategies
+// ============================================================================
+
+/// Discriminators of `count` tools plus list_tools, read as the dispatcher's u64
+fn route_keys(count: usize) -> Vec<u64> {
+    std::iter::once("list_tools".to_string())
+        .chain((0..count).map(|i| format!("tool_{}", i)))
+        .map(|name| u64::from_le_bytes(mcpsol_core::instruction_discriminator(&name)))
+        .collect()
+}
+
+/// First discriminator byte whose value differs across all keys
+fn distinct_byte(keys: &[u64]) -> Option<u32> {
+    (0..8).find(|&byte| {
+        let mut seen = [false; 256];
+        keys.iter()
+            .all(|key| !std::mem::replace(&mut seen[(key >> (8 * byte)) as u8 as usize], true))
+    })
+}
+
+/// CU per dispatch for each strategy `#[mcp_program]` can generate, cycling
+/// through every route as a mix of instructions would.
+/// Timing depends on the host, so this runs with `--ignored` and only reports
+#[test]
+#[ignore]
+fn benchmark_dispatch_strategies() {
+    println!("\n--- Dispatch Strategy Benchmark ---");
+
+    for count in [4, 16, 64] {
+        let keys = route_keys(count);
+        println!("  {} routes:", keys.len());
+
+        // Match: a compare chain, the worst case of a lowered `match`
+        let mut next = 0;
+        let chain = bench_overhead("dispatch_match", 100_000, || {
+            let key = std::hint::black_box(keys[next % keys.len()]);
+            next += 1;
+            std::hint::black_box(keys.iter().position(|k| *k == key));
+        });
+        chain.print();
+
+        // Binary search over the sorted discriminators
+        let mut sorted = keys.clone();
+        sorted.sort_unstable();
+        let mut next = 0;
+        let search = bench_overhead("dispatch_binary_search", 100_000, || {
+            let key = std::hint::black_box(keys[next % keys.len()]);
+            next += 1;
+            std::hint::black_box(sorted.binary_search(&key).ok());
+        });
+        search.print();
+
+        // Byte hash: slot table on one byte, then a single full compare
+        match distinct_byte(&keys) {
+            Some(byte) => {
+                let mut slots = [0u8; 256];
+                for (idx, key) in keys.iter().enumerate() {
+                    slots[(key >> (8 * byte)) as u8 as usize] = idx as u8 + 1;
+                }
+                let mut next = 0;
+                let hash = bench_overhead("dispatch_byte_hash", 100_000, || {
+                    let key = std::hint::black_box(keys[next % keys.len()]);
+                    next += 1;
+                    let slot = slots[(key >> (8 * byte)) as u8 as usize];
+                    let hit = slot != 0 && keys[slot as usize - 1] == key;
+                    std::hint::black_box(hit.then_some(slot));
+                });
+                hash.print();
+            }
+            None => println!("  No distinct byte: the macro falls back to binary search"),
+        }
+    }
+}
+
 // ============================================================================
 // T034: Comprehensive Benchmark Assertions
 // ============================================================================
@@ -36,12 +36,16 @@ pub struct Counter {
 }
 
 /// Accounts for initialize instruction
+///
+/// The counter is created by the derive: funded rent-exempt by the
+/// authority, assigned to this program and stamped with its discriminator.
 #[derive(Accounts)]
 pub struct Initialize<'info> {
-    #[account(mut)]
-    pub counter: &'info AccountInfo,
-    #[account(signer)]
+    #[account(init, payer = authority, space = Counter::SPACE)]
+    pub counter: Account<'info, Counter>,
+    #[account(mut, signer)]
     pub authority: Signer<'info>,
+    #[account(address = [0u8; 32])]
     pub system_program: &'info AccountInfo,
 }
 
@@ -65,8 +69,8 @@ fn build_schema() -> McpSchema {
         .add_tool(
             CoreToolBuilder::new("initialize")
                 .description("Create a new counter account with initial value of 0")
-                .writable_desc("counter", "The counter account to initialize")
-                .signer_desc("authority", "The authority who will control this counter")
+                .signer_writable_desc("counter", "New keypair for the counter account")
+                .signer_writable_desc("authority", "Pays for the counter and will control it")
                 .account_with_desc("system_program", "System program", false, false)
                 .build()
         )
@@ -156,37 +160,14 @@ fn parse_u64(data: &[u8]) -> core::result::Result<u64, pinocchio::program_error:
 }
 
 fn process_initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> pinocchio::ProgramResult {
-    let counter_account = accounts.first()
-        .ok_or(pinocchio::program_error::ProgramError::NotEnoughAccountKeys)?;
-    let authority = accounts.get(1)
-        .ok_or(pinocchio::program_error::ProgramError::NotEnoughAccountKeys)?;
-
-    // SECURITY: Verify counter is owned by this program
-    // Safety: owner() returns valid pointer to account owner
-    if unsafe { counter_account.owner() } != program_id {
-        pinocchio_log::log!("Invalid counter owner");
-        return Err(pinocchio::program_error::ProgramError::IncorrectProgramId);
-    }
-
-    // SECURITY: Verify counter is writable
-    if !counter_account.is_writable() {
-        return Err(pinocchio::program_error::ProgramError::InvalidAccountData);
-    }
-
-    if !authority.is_signer() {
-        return Err(pinocchio::program_error::ProgramError::MissingRequiredSignature);
-    }
-
-    let mut data = counter_account.try_borrow_mut_data()?;
+    // Creates the counter with its discriminator written
+    let ctx = ContextBuilder::new(program_id, accounts).build::<Initialize>()?;
 
-    // Write discriminator
-    data[..8].copy_from_slice(&Counter::DISCRIMINATOR);
+    let mut data = ctx.accounts.counter.info.try_borrow_mut_data()?;
     // Write count = 0
     data[8..16].copy_from_slice(&0i64.to_le_bytes());
     // Write authority
-    data[16..48].copy_from_slice(authority.key().as_ref());
-    // Zero bump and padding
-    data[48..56].fill(0);
+    data[16..48].copy_from_slice(ctx.accounts.authority.key().as_ref());
 
     pinocchio_log::log!("Counter initialized!");
     Ok(())
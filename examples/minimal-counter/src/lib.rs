@@ -1,7 +1,7 @@
 //! Minimal Counter - The simplest possible MCP-enabled Solana program
 //!
 //! This example shows the ideal developer experience:
-//! - ~50 lines of actual code (vs 200+ in the verbose example)
+//! - ~30 lines of actual code (vs 200+ in the verbose example)
 //! - Zero boilerplate for discriminators, dispatching, or schema
 //!
 //! Compare with examples/counter which has the same functionality
@@ -21,8 +21,10 @@ pub struct Counter {
 /// Accounts for modifying the counter
 #[derive(Accounts)]
 pub struct Modify<'info> {
-    #[account(mut)]
-    pub counter: &'info AccountInfo,
+    /// Counter to modify
+    #[account(mut, has_one = authority)]
+    pub counter: AccountLoader<'info, Counter>,
+    /// Authority stored in the counter
     #[account(signer)]
     pub authority: Signer<'info>,
 }
@@ -38,65 +40,38 @@ pub struct Modify<'info> {
 pub mod minimal_counter {
     use super::*;
 
+    #[mcp_error]
+    pub enum CounterError {
+        /// Count would overflow
+        Overflow,
+    }
+
     #[mcp_instruction(
         name = "increment",
         description = "Increase counter value",
-        accounts = "counter:mut, authority:signer"
+        accounts = "counter:mut, authority:signer",
+        aliases = ["add"]
     )]
-    pub fn increment<'info>(ctx: Context<'info, Modify<'info>>, amount: u64) -> Result<()> {
-        let counter = ctx.accounts.counter;
-        let authority = ctx.accounts.authority.key();
-        let mut data = counter.try_borrow_mut_data()?;
-
-        // Verify discriminator
-        if data[..8] != Counter::DISCRIMINATOR {
-            return Err(McpSolError::InvalidAccount.into());
-        }
-
-        // Verify authority matches stored authority
-        // Layout: [0..8] discriminator, [8..16] count, [16..48] authority
-        if data[16..48] != *authority.as_ref() {
-            return Err(McpSolError::ConstraintViolation.into());
-        }
-
-        // Update count - safe: slice [8..16] is 8 bytes after discriminator check
-        let current_bytes: [u8; 8] = data[8..16]
-            .try_into()
-            .map_err(|_| McpSolError::InvalidAccount)?;
-        let current = i64::from_le_bytes(current_bytes);
-        let new_count = current.saturating_add(amount as i64);
-        data[8..16].copy_from_slice(&new_count.to_le_bytes());
-
+    pub fn increment<'info>(ctx: &Context<'info, Modify<'info>>, amount: u64) -> Result<()> {
+        // Owner, discriminator and authority were checked by Modify
+        let mut counter = ctx.accounts.counter.load_mut()?;
+        counter.count = counter
+            .count
+            .checked_add(amount as i64)
+            .ok_or(CounterError::Overflow)?;
         Ok(())
     }
 
     #[mcp_instruction(
         name = "decrement",
-        description = "Decrease counter value",
-        accounts = "counter:mut, authority:signer"
+        description = "Decrease counter value"
     )]
-    pub fn decrement<'info>(ctx: Context<'info, Modify<'info>>, amount: u64) -> Result<()> {
-        let counter = ctx.accounts.counter;
-        let authority = ctx.accounts.authority.key();
-        let mut data = counter.try_borrow_mut_data()?;
-
-        if data[..8] != Counter::DISCRIMINATOR {
-            return Err(McpSolError::InvalidAccount.into());
-        }
-
-        // Verify authority matches stored authority
-        if data[16..48] != *authority.as_ref() {
-            return Err(McpSolError::ConstraintViolation.into());
-        }
-
-        // Update count - safe: slice [8..16] is 8 bytes after discriminator check
-        let current_bytes: [u8; 8] = data[8..16]
-            .try_into()
-            .map_err(|_| McpSolError::InvalidAccount)?;
-        let current = i64::from_le_bytes(current_bytes);
-        let new_count = current.saturating_sub(amount as i64);
-        data[8..16].copy_from_slice(&new_count.to_le_bytes());
-
+    pub fn decrement<'info>(
+        ctx: &Context<'info, Modify<'info>>,
+        #[mcp_arg(description = "Amount to subtract")] amount: u64,
+    ) -> Result<()> {
+        let mut counter = ctx.accounts.counter.load_mut()?;
+        counter.count = counter.count.saturating_sub(amount as i64);
         Ok(())
     }
 }
@@ -121,6 +96,160 @@ mod tests {
         assert!(schema.len() <= 1024, "Schema too large for return_data");
     }
 
+    #[test]
+    fn test_schema_pages_generated() {
+        let pages = minimal_counter::MCP_SCHEMA_PAGES;
+        // increment, decrement, list_tools
+        assert_eq!(pages.len(), 3);
+
+        for (cursor, page) in pages.iter().enumerate() {
+            let page_str = std::str::from_utf8(page).unwrap();
+            assert!(page.len() <= 1024, "Page {} too large for return_data", cursor);
+            assert!(page_str.contains("\"description\":\"Minimal MCP counter example\""));
+            if cursor + 1 < pages.len() {
+                assert!(page_str.contains(&format!("\"nextCursor\":\"{}\"", cursor + 1)));
+            } else {
+                assert!(!page_str.contains("nextCursor"));
+            }
+        }
+
+        let first = std::str::from_utf8(pages[0]).unwrap();
+        assert!(first.contains("\"description\":\"Increase counter value\""));
+    }
+
+    #[test]
+    fn test_accounts_inferred_from_derive() {
+        // decrement omits the accounts string; Modify supplies the list
+        let schema = std::str::from_utf8(minimal_counter::MCP_SCHEMA_BYTES).unwrap();
+        assert!(schema.contains(
+            r#""n":"decrement","i":"Decrease counter value","d":"6ae3a83bf81b9665","p":{"counter_w":"pubkey","authority_s":"pubkey","amount":"u64"},"r":["counter_w","authority_s","amount"]"#
+        ));
+
+        let page = std::str::from_utf8(minimal_counter::MCP_SCHEMA_PAGES[1]).unwrap();
+        assert!(page.contains(r#""counter":{"type":"pubkey","writable":true,"#));
+        assert!(page.contains(r#""authority":{"type":"pubkey","signer":true,"#));
+    }
+
+    #[test]
+    fn test_doc_comments_reach_verbose_schema() {
+        // Field docs on Modify describe the accounts of both tools
+        let page = std::str::from_utf8(minimal_counter::MCP_SCHEMA_PAGES[0]).unwrap();
+        assert!(page.contains(r#""description":"Counter to modify""#));
+        assert!(page.contains(r#""description":"Authority stored in the counter""#));
+
+        let page = std::str::from_utf8(minimal_counter::MCP_SCHEMA_PAGES[1]).unwrap();
+        assert!(page.contains(r#""amount":{"type":"u64","description":"Amount to subtract"}"#));
+
+        // Compact schema stays free of account and argument descriptions
+        let schema = std::str::from_utf8(minimal_counter::MCP_SCHEMA_BYTES).unwrap();
+        assert!(!schema.contains("Amount to subtract"));
+    }
+
+    #[test]
+    fn test_errors_published() {
+        let schema = std::str::from_utf8(minimal_counter::MCP_SCHEMA_BYTES).unwrap();
+        assert!(schema.ends_with(r#","e":[{"c":6000,"n":"Overflow","m":"Count would overflow"}]}"#));
+
+        let err = ProgramError::from(minimal_counter::CounterError::Overflow);
+        assert_eq!(err, ProgramError::Custom(6000));
+    }
+
+    #[test]
+    fn test_dispatch_in_process() {
+        let program_id = [1u8; 32];
+        let process = minimal_counter::process_instruction;
+
+        assert_eq!(process(&program_id, &[], &minimal_counter::LIST_TOOLS_DISCRIMINATOR), Ok(()));
+        assert_eq!(process(&program_id, &[], &[0; 4]), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(process(&program_id, &[], &[0; 8]), Err(ProgramError::InvalidInstructionData));
+
+        // Modify needs its two accounts before the handler runs
+        let mut data = minimal_counter::increment::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&5u64.to_le_bytes());
+        assert_eq!(process(&program_id, &[], &data), Err(McpSolError::MissingAccount.into()));
+    }
+
+    #[test]
+    fn test_alias_routes_to_handler() {
+        let add = mcpsol::core::instruction_discriminator("add");
+        let hex: String = add.iter().map(|b| format!("{:02x}", b)).collect();
+        let schema = std::str::from_utf8(minimal_counter::MCP_SCHEMA_BYTES).unwrap();
+        assert!(schema.contains(&format!(r#""a":{{"add":"{}"}}"#, hex)));
+
+        // The old discriminator reaches increment, which then wants accounts
+        let mut data = add.to_vec();
+        data.extend_from_slice(&5u64.to_le_bytes());
+        let result = minimal_counter::process_instruction(&[1; 32], &[], &data);
+        assert_eq!(result, Err(McpSolError::MissingAccount.into()));
+    }
+
+    /// Account infos with `keys` and no data, serialized the way the loader
+    /// does. The returned buffer backs them and must outlive them.
+    fn account_infos(keys: &[Pubkey]) -> (Vec<u64>, Vec<AccountInfo>) {
+        use core::mem::MaybeUninit;
+        use mcpsol::pinocchio::account_info::MAX_PERMITTED_DATA_INCREASE;
+
+        let mut bytes = (keys.len() as u64).to_le_bytes().to_vec();
+        for key in keys {
+            bytes.extend_from_slice(&[u8::MAX, 0, 0, 0, 0, 0, 0, 0]); // not a duplicate, no flags
+            bytes.extend_from_slice(key);
+            bytes.extend_from_slice(&[0; 32]); // owner
+            bytes.extend_from_slice(&[0; 16]); // lamports, data length
+            bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            bytes.extend_from_slice(&[0; 8]); // rent epoch
+        }
+        bytes.extend_from_slice(&[0; 8]); // instruction data length
+        bytes.extend_from_slice(&[0; 32]); // program id
+
+        let mut input = vec![0u64; bytes.len().div_ceil(8)];
+        bytemuck::cast_slice_mut::<u64, u8>(&mut input)[..bytes.len()].copy_from_slice(&bytes);
+
+        let mut slots = [const { MaybeUninit::<AccountInfo>::uninit() }; 2];
+        // SAFETY: the buffer follows the loader's serialization format
+        let (_, count, _) = unsafe {
+            mcpsol::pinocchio::entrypoint::deserialize::<2>(input.as_mut_ptr().cast(), &mut slots)
+        };
+        // SAFETY: deserialize initialized the first `count` slots
+        let infos = slots[..count]
+            .iter()
+            .map(|slot| unsafe { slot.assume_init_ref() }.clone())
+            .collect();
+        (input, infos)
+    }
+
+    #[test]
+    fn test_cpi_encodes_instruction() {
+        let program_id = [1u8; 32];
+        let (_input, infos) = account_infos(&[[2; 32], [3; 32]]);
+        let accounts = [&infos[0], &infos[1]];
+
+        minimal_counter::cpi::increment(&program_id, &accounts, 5).unwrap();
+        let sent = mcpsol::cpi::take_invocation().unwrap();
+        assert_eq!(sent.program_id, program_id);
+        // Flags come from Modify: counter is writable, authority signs
+        assert_eq!(sent.accounts, [([2; 32], true, false), ([3; 32], false, true)]);
+        let mut data = minimal_counter::increment::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&5u64.to_le_bytes());
+        assert_eq!(sent.data, data);
+
+        minimal_counter::cpi::decrement_signed(&program_id, &accounts, 7, &[]).unwrap();
+        let sent = mcpsol::cpi::take_invocation().unwrap();
+        assert_eq!(sent.accounts, [([2; 32], true, false), ([3; 32], false, true)]);
+        let mut data = minimal_counter::decrement::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&7u64.to_le_bytes());
+        assert_eq!(sent.data, data);
+    }
+
+    #[test]
+    fn test_cpi_signatures() {
+        use mcpsol::pinocchio::instruction::Signer;
+
+        // Accounts come from Modify, arguments from the handler
+        let _: fn(&Pubkey, &[&AccountInfo; 2], u64) -> Result<()> = minimal_counter::cpi::increment;
+        let _: fn(&Pubkey, &[&AccountInfo; 2], u64, &[Signer]) -> Result<()> =
+            minimal_counter::cpi::decrement_signed;
+    }
+
     #[test]
     fn test_discriminators() {
         // Verify discriminator was generated
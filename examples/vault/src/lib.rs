@@ -41,6 +41,20 @@ pub struct Vault {
 
 pub const VAULT_DISCRIMINATOR: [u8; 8] = [0x3b, 0x7a, 0x3e, 0x2c, 0x8f, 0x1d, 0x4a, 0x5b];
 
+/// `get_info` return data
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct VaultInfo {
+    pub balance: u64,
+    pub bump: u8,
+    pub auth_bump: u8,
+    pub _padding: [u8; 6],
+}
+
+/// Field layout of [`VaultInfo`], published in the schema for clients
+const VAULT_INFO_LAYOUT: &str =
+    r#"{"balance":"u64","bump":"u8","auth_bump":"u8","_padding":"[u8;6]"}"#;
+
 // Instruction discriminators
 const INITIALIZE: [u8; 8] = [0xaf, 0xaf, 0x6d, 0x1f, 0x0d, 0x98, 0x9b, 0xed];
 const DEPOSIT: [u8; 8] = [0xf2, 0x23, 0xc6, 0x89, 0x52, 0xe1, 0xf2, 0xb6];
@@ -91,6 +105,7 @@ fn build_schema() -> McpSchema {
             McpToolBuilder::new("get_info")
                 .description("Get vault balance and metadata via return_data")
                 .account_with_desc("vault", "Vault to query", false, false)
+                .returns_layout(VAULT_INFO_LAYOUT)
                 .build()
         )
         .build()
@@ -324,11 +339,13 @@ fn process_get_info(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRes
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let info = format!(
-        "{{\"balance\":{},\"bump\":{},\"auth_bump\":{}}}",
-        v.balance, v.bump, v.auth_bump
-    );
-    pinocchio::program::set_return_data(info.as_bytes());
+    let info = VaultInfo {
+        balance: v.balance,
+        bump: v.bump,
+        auth_bump: v.auth_bump,
+        _padding: [0; 6],
+    };
+    pinocchio::program::set_return_data(bytemuck::bytes_of(&info));
     Ok(())
 }
 
@@ -373,6 +390,15 @@ mod tests {
     #[test]
     fn test_vault_size() {
         assert_eq!(core::mem::size_of::<Vault>(), 88);
+        assert_eq!(core::mem::size_of::<VaultInfo>(), 16);
+    }
+
+    #[test]
+    fn test_schema_has_return_layout() {
+        let cached = CachedSchemaPages::from_schema(&build_schema());
+        let json = String::from_utf8(cached.get_page(4).to_vec()).unwrap();
+
+        assert!(json.contains(&format!("\"returns\":{}", VAULT_INFO_LAYOUT)));
     }
 
     #[test]
@@ -211,9 +211,10 @@ fn idl_type_to_arg_type(ty: &IdlType) -> ArgType {
                 ArgType::String
             }
         }
-        IdlType::Array { array: (inner, _) } => {
+        IdlType::Array { array: (inner, len) } => {
+            // Borsh writes fixed arrays without a length prefix
             if matches!(inner.as_ref(), IdlType::Primitive(s) if s == "u8") {
-                ArgType::Bytes
+                ArgType::FixedBytes(*len)
             } else {
                 ArgType::String
             }
@@ -224,8 +225,8 @@ fn idl_type_to_arg_type(ty: &IdlType) -> ArgType {
     }
 }
 
-/// Flatten nested account structures
-fn flatten_accounts(items: &[IdlAccountItem], prefix: &str) -> Vec<(String, bool, bool)> {
+/// Flatten nested account structures into (name, signer, writable, optional)
+fn flatten_accounts(items: &[IdlAccountItem], prefix: &str) -> Vec<(String, bool, bool, bool)> {
     let mut result = Vec::new();
 
     for item in items {
@@ -236,7 +237,7 @@ fn flatten_accounts(items: &[IdlAccountItem], prefix: &str) -> Vec<(String, bool
                 } else {
                     format!("{}_{}", prefix, acc.name)
                 };
-                result.push((name, acc.is_signer, acc.is_mut));
+                result.push((name, acc.is_signer, acc.is_mut, acc.is_optional));
             }
             IdlAccountItem::Composite(comp) => {
                 let new_prefix = if prefix.is_empty() {
@@ -275,8 +276,12 @@ pub fn idl_to_mcp(idl: &AnchorIdl) -> McpSchema {
 
         // Add accounts
         let accounts = flatten_accounts(&ix.accounts, "");
-        for (name, is_signer, is_writable) in accounts {
-            tool_builder = tool_builder.account(&name, is_signer, is_writable);
+        for (name, is_signer, is_writable, is_optional) in accounts {
+            tool_builder = if is_optional {
+                tool_builder.optional_account(&name, is_signer, is_writable)
+            } else {
+                tool_builder.account(&name, is_signer, is_writable)
+            };
         }
 
         // Add args
@@ -327,7 +332,8 @@ mod tests {
                 "docs": ["Increment the counter by amount"],
                 "accounts": [
                     {"name": "counter", "isMut": true, "isSigner": false},
-                    {"name": "authority", "isMut": false, "isSigner": true}
+                    {"name": "authority", "isMut": false, "isSigner": true},
+                    {"name": "referrer", "isMut": true, "isSigner": false, "isOptional": true}
                 ],
                 "args": [
                     {"name": "amount", "type": "u64"}
@@ -373,7 +379,8 @@ mod tests {
 
         // Check increment
         assert_eq!(schema.tools[2].name, "increment");
-        assert_eq!(schema.tools[2].accounts.len(), 2);
+        assert_eq!(schema.tools[2].accounts.len(), 3);
+        assert!(schema.tools[2].accounts[2].is_optional);
         assert_eq!(schema.tools[2].args.len(), 1);
         assert_eq!(schema.tools[2].args[0].name, "amount");
     }
@@ -390,6 +397,7 @@ mod tests {
         assert!(json.contains("\"n\":\"increment\""));
         assert!(json.contains("\"i\":\"Initialize a new counter account\""));
         assert!(json.contains("\"amount\":\"u64\""));
+        assert!(json.contains("\"referrer_w\":\"pubkey?\""));
     }
 
     #[test]
@@ -405,7 +413,8 @@ mod tests {
                     {"name": "key", "type": "pubkey"},
                     {"name": "data", "type": {"vec": "u8"}},
                     {"name": "name", "type": "string"},
-                    {"name": "big", "type": "u128"}
+                    {"name": "big", "type": "u128"},
+                    {"name": "seed", "type": {"array": ["u8", 32]}}
                 ]
             }]
         }"#;
@@ -413,12 +422,13 @@ mod tests {
         let schema = parse_idl_to_mcp(idl_json).unwrap();
         let test_types = &schema.tools[1];
 
-        assert_eq!(test_types.args.len(), 6);
+        assert_eq!(test_types.args.len(), 7);
         assert_eq!(test_types.args[0].arg_type, ArgType::U64);
         assert_eq!(test_types.args[1].arg_type, ArgType::Bool);
         assert_eq!(test_types.args[2].arg_type, ArgType::Pubkey);
         assert_eq!(test_types.args[3].arg_type, ArgType::Bytes); // Vec<u8> -> Bytes
         assert_eq!(test_types.args[4].arg_type, ArgType::String);
         assert_eq!(test_types.args[5].arg_type, ArgType::U128);
+        assert_eq!(test_types.args[6].arg_type, ArgType::FixedBytes(32));
     }
 }
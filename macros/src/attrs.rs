@@ -0,0 +1,718 @@
+//! Attribute parsing for the mcpsol macros.
+//!
+//! Every attribute goes through syn's nested meta parser, so unknown keys,
+//! duplicate keys and malformed values are reported at the offending token.
+
+use mcpsol_core::ArgType;
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::meta::ParseNestedMeta;
+use syn::parse::Parser;
+use syn::{Attribute, Expr, ExprArray, Ident, Lit, LitStr, Meta, Path};
+
+use crate::mcp_gen::SchemaOverflow;
+use crate::program::{AccountMeta, DispatchStrategy};
+
+/// Arguments of `#[mcp_program(...)]`
+#[derive(Default)]
+pub struct ProgramAttrs {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub schema_overflow: Option<SchemaOverflow>,
+    pub hooks: DispatchHooks,
+    /// Dispatch strategy, chosen from the instruction count when unset
+    pub dispatch: Option<DispatchStrategy>,
+    /// Discriminator bytes that tag instruction data: 1, 2, 4 or 8 (default)
+    pub discriminator_len: Option<usize>,
+}
+
+/// Functions the generated dispatcher calls around instructions
+#[derive(Default)]
+pub struct DispatchHooks {
+    /// Handles instruction data no tool matches, e.g. legacy discriminators
+    pub fallback: Option<Path>,
+    /// Runs before each tool with its name and accounts
+    pub before: Option<Path>,
+    /// Runs after each tool succeeds, with its name and accounts
+    pub after: Option<Path>,
+}
+
+impl ProgramAttrs {
+    const KEYS: &'static [&'static str] = &[
+        "name",
+        "description",
+        "schema_overflow",
+        "fallback",
+        "before",
+        "after",
+        "dispatch",
+        "discriminator_len",
+    ];
+
+    pub fn parse(tokens: TokenStream) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        syn::meta::parser(|meta| attrs.parse_meta(&meta)).parse2(tokens)?;
+        Ok(attrs)
+    }
+
+    fn parse_meta(&mut self, meta: &ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            set_once(&meta.path, &mut self.name, string_value(meta)?)
+        } else if meta.path.is_ident("description") {
+            set_once(&meta.path, &mut self.description, string_value(meta)?)
+        } else if meta.path.is_ident("schema_overflow") {
+            let lit: LitStr = meta.value()?.parse()?;
+            let policy = SchemaOverflow::parse(&lit.value()).ok_or_else(|| {
+                syn::Error::new(
+                    lit.span(),
+                    "invalid schema_overflow: expected \"error\", \"paginate\" or \"strip\"",
+                )
+            })?;
+            set_once(&meta.path, &mut self.schema_overflow, policy)
+        } else if meta.path.is_ident("fallback") {
+            set_once(&meta.path, &mut self.hooks.fallback, meta.value()?.parse()?)
+        } else if meta.path.is_ident("before") {
+            set_once(&meta.path, &mut self.hooks.before, meta.value()?.parse()?)
+        } else if meta.path.is_ident("after") {
+            set_once(&meta.path, &mut self.hooks.after, meta.value()?.parse()?)
+        } else if meta.path.is_ident("dispatch") {
+            let lit: LitStr = meta.value()?.parse()?;
+            let strategy = DispatchStrategy::parse(&lit.value()).ok_or_else(|| {
+                syn::Error::new(
+                    lit.span(),
+                    "invalid dispatch: expected \"match\", \"binary_search\" or \"byte_hash\"",
+                )
+            })?;
+            set_once(&meta.path, &mut self.dispatch, strategy)
+        } else if meta.path.is_ident("discriminator_len") {
+            let lit: syn::LitInt = meta.value()?.parse()?;
+            let len: usize = lit.base10_parse()?;
+            if ![1, 2, 4, 8].contains(&len) {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "invalid discriminator_len: expected 1, 2, 4 or 8",
+                ));
+            }
+            set_once(&meta.path, &mut self.discriminator_len, len)
+        } else {
+            Err(unknown_key("mcp_program", &meta.path, Self::KEYS))
+        }
+    }
+}
+
+/// Arguments of `#[mcp_instruction(...)]`
+#[derive(Default)]
+pub struct InstructionAttrs {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub accounts: Option<Vec<AccountMeta>>,
+    /// Span of the `accounts` string, for mismatch diagnostics
+    pub accounts_span: Option<Span>,
+    /// Variadic trailing accounts, e.g. `remaining_accounts = "signers:signer"`
+    pub remaining_accounts: Option<AccountMeta>,
+    pub context: Option<bool>,
+    /// Former tool names still routed to this instruction
+    pub aliases: Option<Vec<String>>,
+    /// Skip the account checks generated for no-Context handlers
+    pub unchecked: bool,
+}
+
+impl InstructionAttrs {
+    const KEYS: &'static [&'static str] =
+        &["name", "description", "accounts", "remaining_accounts", "context", "aliases", "unchecked"];
+
+    pub fn parse(tokens: TokenStream) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        syn::meta::parser(|meta| attrs.parse_meta(&meta)).parse2(tokens)?;
+        Ok(attrs)
+    }
+
+    /// Parse from the attribute as written on a function inside `#[mcp_program]`.
+    pub fn from_attr(attr: &Attribute) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        if !matches!(attr.meta, Meta::Path(_)) {
+            attr.parse_nested_meta(|meta| attrs.parse_meta(&meta))?;
+        }
+        Ok(attrs)
+    }
+
+    fn parse_meta(&mut self, meta: &ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            set_once(&meta.path, &mut self.name, string_value(meta)?)
+        } else if meta.path.is_ident("description") {
+            set_once(&meta.path, &mut self.description, string_value(meta)?)
+        } else if meta.path.is_ident("accounts") {
+            let lit: LitStr = meta.value()?.parse()?;
+            set_once(&meta.path, &mut self.accounts, parse_accounts(&lit)?)?;
+            self.accounts_span = Some(lit.span());
+            Ok(())
+        } else if meta.path.is_ident("remaining_accounts") {
+            let lit: LitStr = meta.value()?.parse()?;
+            let mut accounts = parse_accounts(&lit)?;
+            if accounts.len() != 1 {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "remaining_accounts takes a single entry, e.g. \"signers:signer\"",
+                ));
+            }
+            set_once(&meta.path, &mut self.remaining_accounts, accounts.remove(0))
+        } else if meta.path.is_ident("context") {
+            set_once(&meta.path, &mut self.context, bool_value(meta)?)
+        } else if meta.path.is_ident("aliases") {
+            set_once(&meta.path, &mut self.aliases, alias_list(meta)?)
+        } else if meta.path.is_ident("unchecked") {
+            set_flag(meta, &mut self.unchecked)
+        } else {
+            Err(unknown_key("mcp_instruction", &meta.path, Self::KEYS))
+        }
+    }
+}
+
+/// Arguments of `#[mcp_account(...)]`
+#[derive(Default)]
+pub struct McpAccountAttrs {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+impl McpAccountAttrs {
+    const KEYS: &'static [&'static str] = &["name", "description"];
+
+    /// Collect every `#[mcp_account]` attribute on the item.
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("mcp_account")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    set_once(&meta.path, &mut parsed.name, string_value(&meta)?)
+                } else if meta.path.is_ident("description") {
+                    set_once(&meta.path, &mut parsed.description, string_value(&meta)?)
+                } else {
+                    Err(unknown_key("mcp_account", &meta.path, Self::KEYS))
+                }
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Field arguments of `#[account(...)]` in `#[derive(Accounts)]`
+#[derive(Default)]
+pub struct AccountFieldAttrs {
+    pub signer: bool,
+    pub is_mut: bool,
+    /// `owner = <expr>`, the program that must own the account
+    pub owner: Option<Expr>,
+    /// `address = <expr>`, the exact key the account must have
+    pub address: Option<Expr>,
+    /// `has_one = field`, keys stored in the account data
+    pub has_one: Vec<Ident>,
+    /// `seeds = [...]`, PDA seeds checked together with `bump`
+    pub seeds: Option<ExprArray>,
+    /// `bump` (search for the canonical bump) or `bump = <expr>`
+    pub bump: Option<Option<Expr>>,
+    /// `constraint = <expr>`, arbitrary boolean checks
+    pub constraints: Vec<Expr>,
+    /// `init`, create the account before loading it
+    pub init: bool,
+    /// `payer = field`, funds `init` and `realloc`
+    pub payer: Option<Ident>,
+    /// `space = <expr>`, data length of an `init` account
+    pub space: Option<Expr>,
+    /// `realloc = <expr>`, new data length of the account
+    pub realloc: Option<Expr>,
+    /// `close = field`, receives the lamports once the handler succeeds
+    pub close: Option<Ident>,
+    /// `nested`, the field is another `Accounts` struct
+    pub nested: bool,
+}
+
+impl AccountFieldAttrs {
+    const KEYS: &'static [&'static str] = &[
+        "signer",
+        "mut",
+        "owner",
+        "address",
+        "has_one",
+        "seeds",
+        "bump",
+        "constraint",
+        "init",
+        "payer",
+        "space",
+        "realloc",
+        "close",
+        "nested",
+    ];
+
+    /// Collect every `#[account]` attribute on a field.
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        let mut bump_path = None;
+        let mut other_key = None;
+        for attr in attrs.iter().filter(|a| a.path().is_ident("account")) {
+            attr.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("nested") && other_key.is_none() {
+                    other_key = Some(meta.path.clone());
+                }
+                if meta.path.is_ident("nested") {
+                    set_flag(&meta, &mut parsed.nested)
+                } else if meta.path.is_ident("signer") {
+                    set_flag(&meta, &mut parsed.signer)
+                } else if meta.path.is_ident("mut") {
+                    set_flag(&meta, &mut parsed.is_mut)
+                } else if meta.path.is_ident("owner") {
+                    set_once(&meta.path, &mut parsed.owner, meta.value()?.parse()?)
+                } else if meta.path.is_ident("address") {
+                    set_once(&meta.path, &mut parsed.address, meta.value()?.parse()?)
+                } else if meta.path.is_ident("has_one") {
+                    parsed.has_one.push(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("seeds") {
+                    set_once(&meta.path, &mut parsed.seeds, meta.value()?.parse()?)
+                } else if meta.path.is_ident("bump") {
+                    let bump = if meta.input.peek(syn::Token![=]) {
+                        Some(meta.value()?.parse()?)
+                    } else {
+                        None
+                    };
+                    bump_path = Some(meta.path.clone());
+                    set_once(&meta.path, &mut parsed.bump, bump)
+                } else if meta.path.is_ident("constraint") {
+                    parsed.constraints.push(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("init") {
+                    set_flag(&meta, &mut parsed.init)
+                } else if meta.path.is_ident("payer") {
+                    set_once(&meta.path, &mut parsed.payer, meta.value()?.parse()?)
+                } else if meta.path.is_ident("space") {
+                    set_once(&meta.path, &mut parsed.space, meta.value()?.parse()?)
+                } else if meta.path.is_ident("realloc") {
+                    set_once(&meta.path, &mut parsed.realloc, meta.value()?.parse()?)
+                } else if meta.path.is_ident("close") {
+                    set_once(&meta.path, &mut parsed.close, meta.value()?.parse()?)
+                } else {
+                    Err(unknown_key("account", &meta.path, Self::KEYS))
+                }
+            })?;
+        }
+
+        if let Some(key) = other_key.filter(|_| parsed.nested) {
+            return Err(syn::Error::new_spanned(
+                key,
+                "nested `Accounts` fields take no other `#[account(...)]` keys",
+            ));
+        }
+        match (&parsed.seeds, bump_path) {
+            (Some(seeds), None) => {
+                return Err(syn::Error::new_spanned(
+                    seeds,
+                    "`seeds` requires `bump` or `bump = <expr>`",
+                ))
+            }
+            (None, Some(bump)) => {
+                return Err(syn::Error::new_spanned(
+                    bump,
+                    "`bump` requires `seeds = [...]`",
+                ))
+            }
+            _ => {}
+        }
+        parsed.check_lifecycle(attrs)?;
+        Ok(parsed)
+    }
+
+    /// `init`, `realloc` and `close` each need their companion keys.
+    fn check_lifecycle(&self, attrs: &[Attribute]) -> syn::Result<()> {
+        let error = |message: &str| {
+            let span = attrs.iter().find(|a| a.path().is_ident("account"));
+            Err(syn::Error::new_spanned(span, message))
+        };
+        if self.init {
+            if self.payer.is_none() || self.space.is_none() {
+                return error("`init` requires `payer = <field>` and `space = <expr>`");
+            }
+            if self.realloc.is_some() {
+                return error("`init` and `realloc` cannot be combined");
+            }
+        } else if self.space.is_some() {
+            return error("`space` requires `init`");
+        }
+        if self.realloc.is_some() && self.payer.is_none() {
+            return error("`realloc` requires `payer = <field>`");
+        }
+        if self.payer.is_some() && !self.init && self.realloc.is_none() {
+            return error("`payer` requires `init` or `realloc`");
+        }
+        if (self.realloc.is_some() || self.close.is_some()) && !self.is_mut {
+            return error("`realloc` and `close` require `mut`");
+        }
+        Ok(())
+    }
+}
+
+/// Parameter arguments of `#[mcp_arg(...)]` on an instruction handler
+#[derive(Default)]
+pub struct ArgAttrs {
+    pub description: Option<String>,
+    /// Schema type a user type is encoded as
+    pub schema_type: Option<ArgType>,
+}
+
+impl ArgAttrs {
+    const KEYS: &'static [&'static str] = &["description", "schema_type"];
+
+    /// Collect every `#[mcp_arg]` attribute on a parameter.
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("mcp_arg")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("description") {
+                    set_once(&meta.path, &mut parsed.description, string_value(&meta)?)
+                } else if meta.path.is_ident("schema_type") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    let arg_type = ArgType::from_compact_name(&lit.value()).ok_or_else(|| {
+                        syn::Error::new_spanned(&lit, r#"unknown schema type, e.g. "u64" or "[u8;16]""#)
+                    })?;
+                    set_once(&meta.path, &mut parsed.schema_type, arg_type)
+                } else {
+                    Err(unknown_key("mcp_arg", &meta.path, Self::KEYS))
+                }
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// First paragraph of the `///` doc comments, joined into one line.
+pub fn doc_summary(attrs: &[Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("doc")) {
+        let Meta::NameValue(nv) = &attr.meta else {
+            continue;
+        };
+        let syn::Expr::Lit(syn::ExprLit { lit: Lit::Str(lit), .. }) = &nv.value else {
+            continue;
+        };
+        let line = lit.value().trim().to_string();
+        if line.is_empty() {
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        lines.push(line);
+    }
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
+
+/// Parse an accounts list such as `"counter:mut, authority:signer"`.
+///
+/// Entries are comma-separated. Flags follow the first `:` and may be
+/// joined with `:`, `+` or whitespace, e.g. `"payer:signer+mut"`. An
+/// `optional` account may be omitted by passing the program id.
+pub fn parse_accounts(lit: &LitStr) -> syn::Result<Vec<AccountMeta>> {
+    let value = lit.value();
+    let mut accounts: Vec<AccountMeta> = Vec::new();
+
+    for part in value.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (name, flags) = match part.split_once(':') {
+            Some((name, flags)) => (name.trim(), flags),
+            None => (part, ""),
+        };
+
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(syn::Error::new(
+                lit.span(),
+                format!("invalid account name `{}` in accounts list", name),
+            ));
+        }
+        if accounts.iter().any(|acc| acc.name == name) {
+            return Err(syn::Error::new(
+                lit.span(),
+                format!("account `{}` is listed more than once", name),
+            ));
+        }
+
+        let mut meta = AccountMeta {
+            name: name.to_string(),
+            is_signer: false,
+            is_writable: false,
+            is_optional: false,
+            description: String::new(),
+        };
+        for flag in flags
+            .split(|c: char| c == ':' || c == '+' || c.is_whitespace())
+            .filter(|f| !f.is_empty())
+        {
+            let slot = match flag {
+                "signer" => &mut meta.is_signer,
+                "mut" => &mut meta.is_writable,
+                "optional" => &mut meta.is_optional,
+                _ => {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        format!(
+                            "unknown flag `{}` in `{}`: expected `signer`, `mut` or `optional`",
+                            flag, part
+                        ),
+                    ));
+                }
+            };
+            if *slot {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!("duplicate flag `{}` in `{}`", flag, part),
+                ));
+            }
+            *slot = true;
+        }
+        accounts.push(meta);
+    }
+
+    Ok(accounts)
+}
+
+fn string_value(meta: &ParseNestedMeta) -> syn::Result<String> {
+    let lit: LitStr = meta.value()?.parse()?;
+    Ok(lit.value())
+}
+
+/// Parse `aliases = ["old_name", ...]`, rejecting repeats.
+fn alias_list(meta: &ParseNestedMeta) -> syn::Result<Vec<String>> {
+    let array: syn::ExprArray = meta.value()?.parse()?;
+    let mut aliases: Vec<String> = Vec::new();
+    for elem in &array.elems {
+        let syn::Expr::Lit(syn::ExprLit { lit: Lit::Str(lit), .. }) = elem else {
+            return Err(syn::Error::new_spanned(elem, "aliases must be string literals"));
+        };
+        let alias = lit.value();
+        if aliases.contains(&alias) {
+            return Err(syn::Error::new_spanned(lit, format!("duplicate alias `{}`", alias)));
+        }
+        aliases.push(alias);
+    }
+    Ok(aliases)
+}
+
+fn bool_value(meta: &ParseNestedMeta) -> syn::Result<bool> {
+    let lit: Lit = meta.value()?.parse()?;
+    match &lit {
+        Lit::Bool(b) => Ok(b.value),
+        // Accept the quoted form older code generated
+        Lit::Str(s) if s.value() == "true" => Ok(true),
+        Lit::Str(s) if s.value() == "false" => Ok(false),
+        _ => Err(syn::Error::new_spanned(lit, "expected `true` or `false`")),
+    }
+}
+
+fn set_once<T>(path: &Path, slot: &mut Option<T>, value: T) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new_spanned(
+            path,
+            format!("duplicate `{}` key", path_name(path)),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn set_flag(meta: &ParseNestedMeta, slot: &mut bool) -> syn::Result<()> {
+    if !meta.input.is_empty() && !meta.input.peek(syn::Token![,]) {
+        return Err(syn::Error::new_spanned(
+            &meta.path,
+            format!("`{}` is a flag and takes no value", path_name(&meta.path)),
+        ));
+    }
+    if *slot {
+        return Err(syn::Error::new_spanned(
+            &meta.path,
+            format!("duplicate `{}` flag", path_name(&meta.path)),
+        ));
+    }
+    *slot = true;
+    Ok(())
+}
+
+fn unknown_key(attr: &str, path: &Path, expected: &[&str]) -> syn::Error {
+    let expected: Vec<String> = expected.iter().map(|k| format!("`{}`", k)).collect();
+    syn::Error::new_spanned(
+        path,
+        format!(
+            "unknown `{}` key `{}`: expected one of {}",
+            attr,
+            path_name(path),
+            expected.join(", ")
+        ),
+    )
+}
+
+fn path_name(path: &Path) -> String {
+    path.to_token_stream().to_string().replace(' ', "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn test_instruction_attrs_spacing() {
+        // The tokenizer normalizes spacing, so every form parses the same
+        let variants = [
+            quote!(name = "increment" , description = "Increase counter"),
+            quote!(name= "increment", description= "Increase counter"),
+            quote!(name="increment",description="Increase counter"),
+        ];
+        for tokens in variants {
+            let attrs = InstructionAttrs::parse(tokens).unwrap();
+            assert_eq!(attrs.name.as_deref(), Some("increment"));
+            assert_eq!(attrs.description.as_deref(), Some("Increase counter"));
+        }
+    }
+
+    #[test]
+    fn test_escaped_quotes_and_similar_keys() {
+        let attrs = InstructionAttrs::parse(quote!(
+            description = "Say \"hi\"",
+            accounts = "payer:signer+mut, vault:mut, clock",
+            context = false
+        ))
+        .unwrap();
+        assert_eq!(attrs.description.as_deref(), Some("Say \"hi\""));
+        assert_eq!(attrs.name, None);
+        assert_eq!(attrs.context, Some(false));
+        assert!(!attrs.unchecked);
+
+        let accounts = attrs.accounts.unwrap();
+        assert_eq!(accounts.len(), 3);
+        assert!(accounts[0].is_signer && accounts[0].is_writable);
+        assert!(!accounts[1].is_signer && accounts[1].is_writable);
+        assert!(!accounts[2].is_signer && !accounts[2].is_writable);
+
+        let attrs = InstructionAttrs::parse(quote!(accounts = "payer:signer", unchecked)).unwrap();
+        assert!(attrs.unchecked);
+
+        let err = InstructionAttrs::parse(quote!(rename = "x")).err().unwrap();
+        assert!(err.to_string().contains("unknown `mcp_instruction` key `rename`"));
+    }
+
+    #[test]
+    fn test_rejects_bad_attrs() {
+        let dup = ProgramAttrs::parse(quote!(name = "a", name = "b")).err().unwrap();
+        assert!(dup.to_string().contains("duplicate `name` key"));
+
+        let policy = ProgramAttrs::parse(quote!(schema_overflow = "truncate")).err().unwrap();
+        assert!(policy.to_string().contains("invalid schema_overflow"));
+        let dispatch = ProgramAttrs::parse(quote!(dispatch = "jump_table")).err().unwrap();
+        assert!(dispatch.to_string().contains("invalid dispatch"));
+        let len = ProgramAttrs::parse(quote!(discriminator_len = 3)).err().unwrap();
+        assert!(len.to_string().contains("expected 1, 2, 4 or 8"));
+        let len = ProgramAttrs::parse(quote!(discriminator_len = 2)).unwrap();
+        assert_eq!(len.discriminator_len, Some(2));
+
+        let flag = InstructionAttrs::parse(quote!(accounts = "authority:cosigner"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            flag.to_string(),
+            "unknown flag `cosigner` in `authority:cosigner`: \
+             expected `signer`, `mut` or `optional`"
+        );
+        let flag = InstructionAttrs::parse(quote!(accounts = "payer:signer, vault:mut+signer+mut"))
+            .err()
+            .unwrap();
+        assert_eq!(flag.to_string(), "duplicate flag `mut` in `vault:mut+signer+mut`");
+        let name = InstructionAttrs::parse(quote!(accounts = "vault:mut, vault"))
+            .err()
+            .unwrap();
+        assert_eq!(name.to_string(), "account `vault` is listed more than once");
+        let optional = InstructionAttrs::parse(quote!(accounts = "fee:mut+optional")).unwrap();
+        let fee = &optional.accounts.unwrap()[0];
+        assert!(fee.is_writable && fee.is_optional && !fee.is_signer);
+
+        let item: syn::ItemStruct = syn::parse_quote! {
+            struct Pda {
+                #[account(seeds = [b"vault"])]
+                vault: u8,
+                #[account(bump)]
+                other: u8,
+                #[account(init, payer = authority)]
+                counter: u8,
+                #[account(space = 8)]
+                sized: u8,
+                #[account(realloc = 64)]
+                grown: u8,
+                #[account(close = authority)]
+                closed: u8,
+            }
+        };
+        let errors: Vec<String> = item
+            .fields
+            .iter()
+            .map(|field| AccountFieldAttrs::from_attrs(&field.attrs).err().unwrap().to_string())
+            .collect();
+        assert!(errors[0].contains("`seeds` requires `bump`"));
+        assert!(errors[1].contains("`bump` requires `seeds"));
+        assert!(errors[2].contains("`init` requires `payer = <field>` and `space = <expr>`"));
+        assert!(errors[3].contains("`space` requires `init`"));
+        assert!(errors[4].contains("`realloc` requires `payer"));
+        assert!(errors[5].contains("require `mut`"));
+
+        let many = InstructionAttrs::parse(quote!(remaining_accounts = "a:signer, b:signer"))
+            .err()
+            .unwrap();
+        assert!(many.to_string().contains("single entry"));
+
+        let aliases = InstructionAttrs::parse(quote!(aliases = ["add", "plus"])).unwrap();
+        assert_eq!(aliases.aliases.unwrap(), ["add", "plus"]);
+        let repeated = InstructionAttrs::parse(quote!(aliases = ["add", "add"])).err().unwrap();
+        assert!(repeated.to_string().contains("duplicate alias `add`"));
+        let ident = InstructionAttrs::parse(quote!(aliases = [add])).err().unwrap();
+        assert!(ident.to_string().contains("string literals"));
+
+        let item: syn::ItemStruct = syn::parse_quote! {
+            struct Modify {
+                #[account(mut, immutable)]
+                counter: u8,
+            }
+        };
+        let field = item.fields.iter().next().unwrap();
+        let err = AccountFieldAttrs::from_attrs(&field.attrs).err().unwrap();
+        assert!(err.to_string().contains("unknown `account` key `immutable`"));
+
+        let item: syn::ItemStruct = syn::parse_quote! {
+            struct Pay {
+                #[account(nested)]
+                transfer: u8,
+                #[account(mut, nested)]
+                fee: u8,
+            }
+        };
+        let mut fields = item.fields.iter();
+        assert!(AccountFieldAttrs::from_attrs(&fields.next().unwrap().attrs).unwrap().nested);
+        let err = AccountFieldAttrs::from_attrs(&fields.next().unwrap().attrs).err().unwrap();
+        assert!(err.to_string().contains("nested `Accounts` fields take no other"));
+    }
+
+    #[test]
+    fn test_doc_summary_and_arg_attrs() {
+        let func: syn::ItemFn = syn::parse_quote! {
+            /// Add to the counter.
+            /// Fails on overflow.
+            ///
+            /// Details that stay out of the schema.
+            fn increment(#[mcp_arg(description = "Amount to add")] amount: u64) {}
+        };
+        assert_eq!(
+            doc_summary(&func.attrs).as_deref(),
+            Some("Add to the counter. Fails on overflow.")
+        );
+
+        let syn::FnArg::Typed(arg) = &func.sig.inputs[0] else {
+            panic!("expected a typed argument");
+        };
+        let attrs = ArgAttrs::from_attrs(&arg.attrs).unwrap();
+        assert_eq!(attrs.description.as_deref(), Some("Amount to add"));
+    }
+}
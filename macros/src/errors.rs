@@ -0,0 +1,166 @@
+//! `#[mcp_error]` expansion for program-defined error enums.
+//!
+//! Variants get `ProgramError::Custom` codes starting at
+//! `CUSTOM_ERROR_OFFSET`, so they never collide with `McpSolError`. The
+//! same variant list feeds the schema when the enum is declared inside an
+//! `#[mcp_program]` module.
+
+use mcpsol_core::{McpError, CUSTOM_ERROR_OFFSET};
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{Expr, ExprLit, Fields, Ident, ItemEnum, Lit};
+
+use crate::attrs::doc_summary;
+
+/// One variant of an `#[mcp_error]` enum
+pub struct ErrorVariant {
+    pub ident: Ident,
+    pub code: u32,
+    /// First paragraph of the variant's doc comment
+    pub message: String,
+}
+
+impl ErrorVariant {
+    /// Schema entry for this variant
+    pub fn to_schema(&self) -> McpError {
+        McpError {
+            code: self.code,
+            name: self.ident.to_string(),
+            message: (!self.message.is_empty()).then(|| self.message.clone()),
+        }
+    }
+}
+
+/// Assign codes to the variants of an `#[mcp_error]` enum.
+///
+/// Variants count up from `CUSTOM_ERROR_OFFSET`; an explicit `= n` sets
+/// the offset-relative code, and later variants continue from it.
+pub fn error_variants(item: &ItemEnum) -> syn::Result<Vec<ErrorVariant>> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.generics,
+            "`#[mcp_error]` enums cannot be generic",
+        ));
+    }
+
+    let mut variants: Vec<ErrorVariant> = Vec::new();
+    let mut next = 0u32;
+    for variant in &item.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                &variant.fields,
+                "`#[mcp_error]` variants cannot carry data",
+            ));
+        }
+        if let Some((_, ref discriminant)) = variant.discriminant {
+            next = match discriminant {
+                Expr::Lit(ExprLit { lit: Lit::Int(lit), .. }) => lit.base10_parse()?,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        discriminant,
+                        "`#[mcp_error]` discriminants must be integer literals",
+                    ))
+                }
+            };
+        }
+        let code = CUSTOM_ERROR_OFFSET.checked_add(next).ok_or_else(|| {
+            syn::Error::new_spanned(&variant.ident, "error code overflows u32")
+        })?;
+        if let Some(prev) = variants.iter().find(|v| v.code == code) {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                format!("error code {} is already used by `{}`", code, prev.ident),
+            ));
+        }
+        variants.push(ErrorVariant {
+            ident: variant.ident.clone(),
+            code,
+            message: doc_summary(&variant.attrs).unwrap_or_default(),
+        });
+        next = next.saturating_add(1);
+    }
+    Ok(variants)
+}
+
+/// Generate the code, name and message accessors and the conversion into
+/// `ProgramError`, which logs the error before returning its code.
+pub fn expand(item: &ItemEnum) -> syn::Result<TokenStream> {
+    let variants = error_variants(item)?;
+    let name = &item.ident;
+
+    let idents: Vec<_> = variants.iter().map(|v| &v.ident).collect();
+    let codes = variants.iter().map(|v| v.code);
+    let names = variants.iter().map(|v| v.ident.to_string());
+    let messages = variants.iter().map(|v| &v.message);
+    let logs = variants.iter().map(|v| {
+        let line = if v.message.is_empty() {
+            format!("Error {}: {}", v.code, v.ident)
+        } else {
+            format!("Error {}: {}: {}", v.code, v.ident, v.message)
+        };
+        Literal::byte_string(line.as_bytes())
+    });
+
+    Ok(quote! {
+        #item
+
+        impl #name {
+            /// `ProgramError::Custom` code of this error
+            pub const fn code(&self) -> u32 {
+                match self {
+                    #(Self::#idents => #codes,)*
+                }
+            }
+
+            /// Variant name, as published in the schema
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    #(Self::#idents => #names,)*
+                }
+            }
+
+            /// Message from the variant's doc comment
+            pub const fn message(&self) -> &'static str {
+                match self {
+                    #(Self::#idents => #messages,)*
+                }
+            }
+        }
+
+        impl From<#name> for mcpsol::prelude::ProgramError {
+            fn from(e: #name) -> Self {
+                mcpsol::pinocchio_log::logger::log_message(match e {
+                    #(#name::#idents => #logs,)*
+                });
+                mcpsol::prelude::ProgramError::Custom(e.code())
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_of(item: &ItemEnum) -> String {
+        error_variants(item).err().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_error_variants_rejected() {
+        let duplicate = error_of(&syn::parse_quote! {
+            enum E { A = 1, B = 0, C }
+        });
+        assert!(duplicate.contains("error code 6001 is already used by `A`"));
+
+        let data = error_of(&syn::parse_quote! {
+            enum E { A(u8) }
+        });
+        assert!(data.contains("cannot carry data"));
+
+        let expr = error_of(&syn::parse_quote! {
+            enum E { A = 1 + 1 }
+        });
+        assert!(expr.contains("integer literals"));
+    }
+}
@@ -4,20 +4,70 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput, Fields, ItemFn, ItemMod, Type};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, ItemEnum, ItemFn, ItemMod, Type};
 
+mod attrs;
 mod discriminator;
+mod errors;
 mod mcp_gen;
 mod program;
+mod returns;
 
+use attrs::{AccountFieldAttrs, InstructionAttrs, McpAccountAttrs, ProgramAttrs};
 use discriminator::{account_discriminator, instruction_discriminator};
 
 /// Marks a module as an MCP-enabled Solana program.
 ///
 /// Generates:
-/// - Program entrypoint
-/// - MCP schema generation
-/// - Instruction dispatcher
+/// - Program entrypoint, left out when the program crate is built with its
+///   `no-entrypoint` feature
+/// - MCP schema generation (compact `MCP_SCHEMA_BYTES` plus verbose
+///   `MCP_SCHEMA_PAGES` with all descriptions)
+/// - Instruction dispatcher, `process_instruction` in the module, where
+///   `list_tools` serves the compact schema or, given a cursor byte, the
+///   matching verbose page
+/// - A `cpi` module, compiled under the program crate's `cpi` feature, with
+///   `name(program_id, accounts, args..)` and `name_signed(.., signers)` per
+///   instruction for calls from other programs
+/// - An `instruction` module, compiled under the `client` feature, of
+///   off-chain builders returning solana-sdk `Instruction`s
+///
+/// `#[mcp_error]` enums declared inside the module are published in the
+/// schema as `"e"` entries, so clients can name a failing custom error code.
+///
+/// Dispatch hooks name functions visible inside the module:
+/// - `before = f`: `fn(tool: &str, accounts: &[AccountInfo]) -> Result<()>`,
+///   run before every tool; an error aborts the instruction
+/// - `after = f`: same signature, run once the tool has succeeded
+/// - `fallback = f`: `fn(&Pubkey, &[AccountInfo], data: &[u8]) -> Result<()>`,
+///   given any instruction data no tool matches
+///
+/// The built-in `list_tools` is never hooked.
+///
+/// Tool names and aliases must be unique, must not hash to the same
+/// discriminator, and must not clash with `list_tools`; each case is a
+/// compile error naming both functions.
+///
+/// `dispatch` sets how the dispatcher finds a tool. By default programs with
+/// up to 8 routed discriminators (tools, aliases and `list_tools`) use
+/// `"match"`; larger ones use `"byte_hash"` when one discriminator byte
+/// differs across all routes, and `"binary_search"` otherwise:
+/// - `"match"`: a `match` on the u64 discriminator
+/// - `"binary_search"`: a binary search over the sorted discriminators
+/// - `"byte_hash"`: a 256-entry table on that byte, then one full compare;
+///   a compile error when no byte qualifies
+///
+/// `discriminator_len = N` (1, 2, 4 or 8, default 8) shortens every tool's
+/// tag to the first N bytes of its discriminator, saving 8 - N bytes per
+/// instruction. Tags that collide at that length are a compile error, and
+/// `list_tools` keeps its full 8 bytes. The schema advertises the length so
+/// clients build matching instruction data.
+///
+/// Every schema payload is checked against the 1024-byte return_data limit
+/// at compile time. `schema_overflow` chooses what happens when one is over:
+/// - `"error"` (default): fail the build with a per-tool byte breakdown
+/// - `"paginate"`: without a cursor, `list_tools` serves the first verbose page
+/// - `"strip"`: drop descriptions from the payloads that do not fit
 ///
 /// # Example
 ///
@@ -38,7 +88,10 @@ use discriminator::{account_discriminator, instruction_discriminator};
 #[proc_macro_attribute]
 pub fn mcp_program(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemMod);
-    let attrs = parse_program_attrs(attr);
+    let attrs = match ProgramAttrs::parse(attr.into()) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
     let mod_name = &input.ident;
     let mod_vis = &input.vis;
@@ -49,23 +102,50 @@ pub fn mcp_program(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     let expanded = if let Some((_brace, items)) = mod_content {
         // Extract instruction metadata from the module
-        let instructions = program::extract_instructions(items);
+        let discriminator_len = attrs.discriminator_len.unwrap_or(8);
+        let instructions = match program::extract_instructions(items, discriminator_len) {
+            Ok(instructions) => instructions,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        let program_errors = match program::extract_errors(items) {
+            Ok(program_errors) => program_errors,
+            Err(err) => return err.to_compile_error().into(),
+        };
 
-        // Generate MCP schema JSON
-        let schema_json = mcp_gen::generate_schema_json(
+        // Generate the compact schema and verbose pages, checked against
+        // the return_data limit
+        let payloads = match mcp_gen::generate_schema_payloads(
             &program_name,
             &program_desc,
             &instructions,
-        );
+            &program_errors,
+            attrs.schema_overflow.unwrap_or_default(),
+            discriminator_len,
+        ) {
+            Ok(payloads) => payloads,
+            Err(msg) => return syn::Error::new(mod_name.span(), msg).to_compile_error().into(),
+        };
 
-        // Generate the list_tools function and schema constant
-        let list_tools = program::generate_list_tools(&schema_json);
+        // Generate the list_tools function and schema constants
+        let list_tools = program::generate_list_tools(&payloads, &instructions);
 
         // Generate the instruction dispatcher
-        let dispatcher = program::generate_dispatcher(mod_name, &instructions);
+        let dispatcher = match program::generate_dispatcher(
+            &instructions,
+            &attrs.hooks,
+            attrs.dispatch,
+            discriminator_len,
+        ) {
+            Ok(dispatcher) => dispatcher,
+            Err(msg) => return syn::Error::new(mod_name.span(), msg).to_compile_error().into(),
+        };
+
+        // Generate the feature-gated CPI helpers
+        let cpi = program::generate_cpi(&instructions);
+        let client = program::generate_client(&instructions);
 
         // Generate the entrypoint
-        let entrypoint = program::generate_entrypoint();
+        let entrypoint = program::generate_entrypoint(mod_name);
 
         quote! {
             #mod_vis mod #mod_name {
@@ -78,10 +158,16 @@ pub fn mcp_program(attr: TokenStream, item: TokenStream) -> TokenStream {
 
                 // Auto-generated MCP schema and list_tools instruction
                 #list_tools
+
+                // Auto-generated dispatcher
+                #dispatcher
+
+                #cpi
+
+                #client
             }
 
-            // Auto-generated dispatcher and entrypoint (outside module)
-            #dispatcher
+            // Entrypoint (outside module), unless built with `no-entrypoint`
             #entrypoint
         }
     } else {
@@ -99,6 +185,32 @@ pub fn mcp_program(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// - `name`: Tool name (defaults to function name)
 /// - `description`: Human-readable description for AI agents
+/// - `accounts`: Account list, e.g. `"counter:mut, payer:signer+mut"`. Optional
+///   for `Context` handlers, which take it from `#[derive(Accounts)]`; when
+///   both are given they must agree
+/// - `context`: Force (`true`) or skip (`false`) building a `Context`
+/// - `aliases`: Former names, e.g. `["add"]`, whose discriminators still
+///   dispatch here; the schema lists them as deprecated under this tool
+/// - `unchecked`: Skip the account checks of a handler without a `Context`
+///
+/// Before a handler without a `Context` runs, the dispatcher checks that at
+/// least as many accounts as `accounts` declares were passed, and that each
+/// `signer` and `mut` account is one. Add `unchecked` to leave that to the
+/// handler.
+///
+/// A `Context` handler takes it by value, or as `&Context<..>` when it only
+/// reads from it; the dispatcher passes whichever the signature asks for.
+///
+/// Without `description`, the first paragraph of the doc comment is used.
+/// Arguments are described with `#[mcp_arg(description = "...")]` on the
+/// parameter. An argument of a user type names the schema type it is encoded
+/// as with `#[mcp_arg(schema_type = "...")]`.
+///
+/// A handler may return `Result<T>` for any `T: McpReturn` instead of
+/// `Result<()>`. Once it succeeds, the dispatcher writes the value to return
+/// data, and the schema publishes its layout.
+///
+/// Unknown or repeated keys and unknown account flags are compile errors.
 ///
 /// # Example
 ///
@@ -113,18 +225,34 @@ pub fn mcp_program(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro_attribute]
 pub fn mcp_instruction(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(item as ItemFn);
-    let attrs = parse_instruction_attrs(attr);
+    let mut input = parse_macro_input!(item as ItemFn);
+    let attrs = match InstructionAttrs::parse(attr.into()) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    // Validate and strip #[mcp_arg] so the function compiles as plain Rust
+    for input_arg in &mut input.sig.inputs {
+        if let syn::FnArg::Typed(pat_type) = input_arg {
+            if let Err(err) = attrs::ArgAttrs::from_attrs(&pat_type.attrs) {
+                return err.to_compile_error().into();
+            }
+            pat_type.attrs.retain(|a| !a.path().is_ident("mcp_arg"));
+        }
+    }
 
     let fn_name = &input.sig.ident;
 
     let tool_name = attrs.name.unwrap_or_else(|| fn_name.to_string());
-    let tool_desc = attrs.description.unwrap_or_default();
+    let tool_desc = attrs
+        .description
+        .or_else(|| attrs::doc_summary(&input.attrs))
+        .unwrap_or_default();
 
     // Generate SHA256-based discriminator for the instruction
     let discriminator = instruction_discriminator(&tool_name);
 
-    // Keep the original function intact, just add metadata module
+    // Keep the original function intact, just add metadata module.
     let expanded = quote! {
         #input
 
@@ -139,6 +267,46 @@ pub fn mcp_instruction(attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// Defines program errors with codes clients can look up in the schema.
+///
+/// Variants must be fieldless and are numbered from `CUSTOM_ERROR_OFFSET`
+/// (6000), above the codes `McpSolError` uses; `Variant = n` sets the code
+/// to `6000 + n`. The first paragraph of each variant's doc comment is its
+/// message. Converting into `ProgramError` logs the code, name and message.
+///
+/// Declare the enum inside the `#[mcp_program]` module to publish its
+/// variants in the schema.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[mcp_error]
+/// pub enum VaultError {
+///     /// Vault balance too low
+///     InsufficientFunds,
+///     /// Vault is frozen
+///     Frozen,
+/// }
+///
+/// return Err(VaultError::InsufficientFunds.into()); // Custom(6000)
+/// ```
+#[proc_macro_attribute]
+pub fn mcp_error(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new_spanned(
+            proc_macro2::TokenStream::from(attr),
+            "`#[mcp_error]` takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let input = parse_macro_input!(item as ItemEnum);
+    match errors::expand(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 /// Derive macro for MCP account types (resources).
 ///
 /// Generates:
@@ -172,7 +340,15 @@ pub fn derive_mcp_account(input: TokenStream) -> TokenStream {
     let name = &input.ident;
 
     // Parse attributes
-    let (resource_name, resource_desc) = parse_mcp_account_attrs(&input);
+    let attrs = match McpAccountAttrs::from_attrs(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let resource_name = attrs.name.unwrap_or_else(|| name.to_string());
+    let resource_desc = attrs
+        .description
+        .or_else(|| attrs::doc_summary(&input.attrs))
+        .unwrap_or_default();
 
     // Generate SHA256-based discriminator for the account
     let discriminator = account_discriminator(&name.to_string());
@@ -247,6 +423,33 @@ pub fn derive_mcp_account(input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// Derive `McpReturn` for a `Pod` struct, so instructions can return it.
+///
+/// The struct is returned as its bytes, and its fields, in order, are the
+/// layout published in the schema. Fields must be integers, `bool`,
+/// `Pubkey` or fixed-size arrays of them.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// #[derive(McpReturn, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+/// #[repr(C)]
+/// pub struct VaultInfo {
+///     pub balance: u64,
+///     pub owner: Pubkey,
+/// }
+///
+/// // Schema: "o":{"balance":"u64","owner":"pubkey"}
+/// ```
+#[proc_macro_derive(McpReturn)]
+pub fn derive_mcp_return(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match returns::expand(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 /// Generate JSON schema from struct fields for MCP resource definition
 fn generate_account_schema(input: &DeriveInput) -> String {
     let fields = match &input.data {
@@ -280,6 +483,82 @@ fn generate_account_schema(input: &DeriveInput) -> String {
     )
 }
 
+/// Whether a field type is `Account<..>` or `AccountLoader<..>`, which
+/// default to an owner check
+fn is_account_type(ty: &Type) -> bool {
+    type_name_is(ty, "Account") || is_loader_type(ty)
+}
+
+/// Whether a field type is the zero-copy `AccountLoader<..>`
+fn is_loader_type(ty: &Type) -> bool {
+    type_name_is(ty, "AccountLoader")
+}
+
+fn type_name_is(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name),
+        _ => false,
+    }
+}
+
+/// The `T` of an `Account<'info, T>` or `AccountLoader<'info, T>` field type
+fn account_data_type(ty: &Type) -> Option<&Type> {
+    if !is_account_type(ty) {
+        return None;
+    }
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+/// The `T` of an `Option<T>` field type
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+/// A field type with its lifetimes replaced by `'static`, to name it
+/// outside the `'info` impl
+fn with_static_lifetimes(ty: &Type) -> Type {
+    let mut ty = ty.clone();
+    if let Type::Path(path) = &mut ty {
+        for segment in &mut path.path.segments {
+            if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                for arg in &mut args.args {
+                    if let syn::GenericArgument::Lifetime(lifetime) = arg {
+                        *lifetime = syn::Lifetime::new("'static", lifetime.span());
+                    }
+                }
+            }
+        }
+    }
+    ty
+}
+
 /// Map Rust type to JSON schema type
 fn type_to_json_schema(ty: &Type) -> String {
     let type_str = quote!(#ty).to_string().replace(' ', "");
@@ -301,24 +580,69 @@ fn type_to_json_schema(ty: &Type) -> String {
 
 /// Derive macro for account context structs.
 ///
-/// Parses field attributes and generates `Accounts` trait implementation.
+/// Parses field attributes and generates `Accounts` trait implementation,
+/// plus `AccountsMeta` so `#[mcp_program]` can build tool account lists.
 ///
 /// # Field Attributes
 ///
 /// - `#[account(signer)]` - Verify the account is a signer
 /// - `#[account(mut)]` - Verify the account is writable
-/// - `#[account(owner = <program>)]` - Verify account owner
+/// - `#[account(owner = <expr>)]` - Verify the owning program (`InvalidOwner`).
+///   `Account<T>` and `AccountLoader<T>` fields default to `owner = program_id`.
+/// - `#[account(address = <expr>)]` - Verify the account key (`AddressMismatch`).
+///   Required on `Program<'info>` fields.
+/// - `#[account(has_one = field)]` - Verify `self.data.field` is the key of
+///   `field` (`HasOneMismatch`)
+/// - `#[account(seeds = [...], bump)]` - Verify the PDA, searching for the
+///   canonical bump or using `bump = <expr>` (`SeedsMismatch`). The seeds are
+///   appended to the account description as `seeds=[...]`.
+/// - `#[account(constraint = <expr>)]` - Verify an expression (`ConstraintViolation`)
+/// - `#[account(init, payer = field, space = <expr>)]` - Create an
+///   `Account<T>` or `AccountLoader<T>` through the system program, funded
+///   rent-exempt by `payer`, owned by this program and starting with
+///   `T::DISCRIMINATOR`. With `seeds` the PDA signs for itself; otherwise the
+///   account must sign.
+/// - `#[account(mut, realloc = <expr>, payer = field)]` - Resize the data,
+///   with `payer` covering or receiving the rent difference
+/// - `#[account(mut, close = field)]` - After the handler succeeds, move
+///   all lamports to `field` and return the account to the system program
+/// - `#[account(nested)]` - The field is another `Accounts` struct (below)
+///
+/// `has_one`, `seeds`, `constraint`, `init` and `realloc` run after the
+/// other fields are loaded and may refer to them by name. Anything else is a
+/// compile error.
+///
+/// # Nested and Optional Accounts
+///
+/// A field marked `#[account(nested)]` is another `Accounts` struct and
+/// takes that struct's `LEN` accounts from its position, e.g. a reusable
+/// `TokenTransfer<'info>`. Its accounts appear in the schema as
+/// `<field>_<name>`. Nested fields take no other `#[account(...)]` keys.
+///
+/// An `Option<_>` field is `None` when the program id is passed in its
+/// place; otherwise it is checked like any other account. It is typed
+/// `"pubkey?"` in the schema and cannot be `init`, `realloc` or `close`.
 ///
 /// # Example
 ///
 /// ```rust,ignore
 /// #[derive(Accounts)]
-/// pub struct Initialize<'info> {
-///     #[account(mut)]
-///     pub counter: Account<'info, Counter>,
+/// pub struct Withdraw<'info> {
+///     #[account(mut, has_one = authority, seeds = [b"vault", authority.key().as_ref()], bump)]
+///     pub vault: Account<'info, Vault>,
 ///     #[account(signer)]
 ///     pub authority: Signer<'info>,
-///     pub system_program: Program<'info>,
+///     #[account(address = SYSTEM_PROGRAM_ID)]
+///     pub system_program: &'info AccountInfo,
+/// }
+///
+/// #[derive(Accounts)]
+/// pub struct Pay<'info> {
+///     // Schema: "transfer_from_w", "transfer_to_w", ...
+///     #[account(nested)]
+///     pub transfer: TokenTransfer<'info>,
+///     #[account(mut)]
+///     pub referrer: Option<&'info AccountInfo>,
 /// }
 /// ```
 #[proc_macro_derive(Accounts, attributes(account))]
@@ -344,187 +668,457 @@ pub fn derive_accounts(input: TokenStream) -> TokenStream {
     };
 
     // Generate field extraction code
-    let field_count = fields.len();
     let mut field_extractions = Vec::new();
+    let mut deferred_extractions = Vec::new();
+    let mut field_checks = Vec::new();
+    let mut exit_actions = Vec::new();
     let mut field_names = Vec::new();
+    let mut account_metas = Vec::new();
+    let mut schema_fields = Vec::new();
+    let all_names: Vec<String> = fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref().map(ToString::to_string))
+        .collect();
+
+    // Parse #[account(...)] attributes
+    let all_attrs = match fields
+        .iter()
+        .map(|field| AccountFieldAttrs::from_attrs(&field.attrs))
+        .collect::<syn::Result<Vec<_>>>()
+    {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    // Index of each field's first account; a nested struct takes its `LEN`
+    let mut offsets = Vec::new();
+    let mut own_count = 0usize;
+    let mut nested_lens = Vec::new();
+    for (field, field_attrs) in fields.iter().zip(&all_attrs) {
+        offsets.push(quote! { #own_count #(+ #nested_lens)* });
+        let ty = &field.ty;
+        if field_attrs.nested {
+            nested_lens.push(quote! { <#ty as mcpsol::context::Accounts<'info>>::LEN });
+        } else {
+            own_count += 1;
+        }
+    }
+    let accounts_len = quote! { #own_count #(+ #nested_lens)* };
 
-    for (idx, field) in fields.iter().enumerate() {
+    for ((field, offset), field_attrs) in fields.iter().zip(&offsets).zip(&all_attrs) {
         // Safe: We verified above that fields are named, which always have idents
         let Some(field_name) = field.ident.as_ref() else {
             continue; // Unreachable for named fields
         };
-        let field_ty = &field.ty;
         field_names.push(field_name);
 
-        // Parse #[account(...)] attributes
-        let mut is_signer = false;
-        let mut is_mut = false;
-
-        for attr in &field.attrs {
-            if attr.path().is_ident("account") {
-                let tokens = attr.meta.require_list().ok()
-                    .map(|list| list.tokens.to_string())
-                    .unwrap_or_default();
-
-                // Use word boundaries to avoid matching "cosigner" or "immutable"
-                // Split on common delimiters and check for exact matches
-                let parts: Vec<&str> = tokens.split([',', ' '])
-                    .map(|s| s.trim())
-                    .collect();
-                is_signer = parts.contains(&"signer");
-                is_mut = parts.contains(&"mut");
+        // A nested `Accounts` struct loads its own accounts from a sub-range
+        if field_attrs.nested {
+            if option_inner(&field.ty).is_some() {
+                return syn::Error::new_spanned(
+                    &field.ty,
+                    "nested `Accounts` fields cannot be optional; \
+                     make their accounts optional instead",
+                )
+                .to_compile_error()
+                .into();
+            }
+            let field_ty = &field.ty;
+            let range = quote! {
+                accounts
+                    .get(#offset..#offset + <#field_ty as mcpsol::context::Accounts<'info>>::LEN)
+                    .ok_or(mcpsol::error::McpSolError::MissingAccount)?
+            };
+            field_extractions.push(quote! {
+                let #field_name = <#field_ty as mcpsol::context::Accounts<'info>>::try_accounts(
+                    program_id,
+                    #range,
+                )?;
+            });
+            exit_actions.push(quote! {
+                <#field_ty as mcpsol::context::Accounts<'info>>::exit(program_id, #range)?;
+            });
+            let prefix = field_name.to_string();
+            let static_ty = with_static_lifetimes(field_ty);
+            schema_fields.push(quote! {
+                mcpsol::schema::AccountsField::Nested(
+                    #prefix,
+                    <#static_ty as mcpsol::context::AccountsMeta>::ACCOUNTS,
+                )
+            });
+            continue;
+        }
+
+        // `Option<T>` is `None` when the program id is passed in its place
+        let (field_ty, optional) = match option_inner(&field.ty) {
+            Some(inner) => (inner, true),
+            None => (&field.ty, false),
+        };
+        for target in field_attrs.payer.iter().chain(&field_attrs.close) {
+            if !all_names.iter().any(|n| target == n) {
+                return syn::Error::new_spanned(
+                    target,
+                    format!("`{}` is not a field of `{}`", target, name),
+                )
+                .to_compile_error()
+                .into();
             }
         }
+        let lifecycle_attrs =
+            field_attrs.init || field_attrs.realloc.is_some() || field_attrs.close.is_some();
+        if optional && lifecycle_attrs {
+            return syn::Error::new_spanned(
+                &field.ty,
+                "`init`, `realloc` and `close` do not apply to optional accounts",
+            )
+            .to_compile_error()
+            .into();
+        }
+        let init = field_attrs.init;
+        // A new keypair account signs its own creation; a PDA signs via seeds
+        let is_signer = field_attrs.signer || (init && field_attrs.seeds.is_none());
+        let is_mut = field_attrs.is_mut || init;
+
+        // PDA seeds are appended to the description so agents can derive the address
+        let mut description = attrs::doc_summary(&field.attrs).unwrap_or_default();
+        if let Some(ref seeds) = field_attrs.seeds {
+            let seeds: Vec<_> = seeds.elems.iter().cloned().collect();
+            description = mcp_gen::describe_seeds(&description, &seeds, &all_names);
+        }
+        let meta = program::AccountMeta {
+            name: field_name.to_string(),
+            is_signer,
+            is_writable: is_mut,
+            is_optional: optional,
+            description,
+        };
+        let def = program::account_meta_def(&meta);
+        schema_fields.push(quote! { mcpsol::schema::AccountsField::Account(#def) });
+        account_metas.push(meta);
+
+        // Checks on the raw AccountInfo, before it is wrapped
+        let mut info_checks = Vec::new();
+        if is_mut {
+            info_checks.push(quote! {
+                if !info.is_writable() {
+                    return Err(mcpsol::error::McpSolError::NotWritable.into());
+                }
+            });
+        }
+        if init && is_signer {
+            info_checks.push(quote! {
+                if !info.is_signer() {
+                    return Err(mcpsol::error::McpSolError::MissingSigner.into());
+                }
+            });
+        }
+        // Account<T> is owned by this program unless `owner` says otherwise;
+        // an `init` account is still owned by the system program here
+        let owner = match field_attrs.owner {
+            Some(ref owner) => Some(quote! { core::convert::AsRef::<[u8]>::as_ref(&(#owner)) }),
+            None if !is_signer && !init && is_account_type(field_ty) => Some(quote! { program_id }),
+            None => None,
+        };
+        if let Some(owner) = owner {
+            info_checks.push(quote! {
+                mcpsol::constraints::check_owner(info, #owner)?;
+            });
+        }
+        if let Some(ref address) = field_attrs.address {
+            info_checks.push(quote! {
+                mcpsol::constraints::check_address(
+                    info,
+                    core::convert::AsRef::<[u8]>::as_ref(&(#address)),
+                )?;
+            });
+        }
 
         // Check if this is a raw reference type (starts with &)
         let ty_str = quote!(#field_ty).to_string();
         let is_raw_ref = ty_str.starts_with("&");
 
-        // Generate extraction code based on field type and attributes
-        let extraction = if is_signer {
-            // Signer check - also verify writable if mut is specified
-            if is_mut {
-                quote! {
-                    let #field_name = {
-                        let info = accounts.get(#idx)
-                            .ok_or(mcpsol::error::McpSolError::MissingAccount)?;
-                        if !info.is_writable() {
-                            return Err(mcpsol::error::McpSolError::NotWritable.into());
-                        }
-                        mcpsol::account::Signer::try_from(info)?
-                    };
-                }
-            } else {
-                quote! {
-                    let #field_name = {
-                        let info = accounts.get(#idx)
-                            .ok_or(mcpsol::error::McpSolError::MissingAccount)?;
-                        mcpsol::account::Signer::try_from(info)?
+        // Creation and resizing happen before the account is wrapped, so they
+        // run after the other fields are loaded and can use them as payer/seeds
+        let mut lifecycle = quote! {};
+        if init {
+            let Some(data_ty) = account_data_type(field_ty) else {
+                return syn::Error::new_spanned(
+                    field_ty,
+                    "`init` requires an `Account<'info, T>` or `AccountLoader<'info, T>` field",
+                )
+                .to_compile_error()
+                .into();
+            };
+            let payer = &field_attrs.payer;
+            let space = &field_attrs.space;
+            let (seed_setup, signers) = match (&field_attrs.seeds, &field_attrs.bump) {
+                (Some(seeds), Some(bump)) => {
+                    let seed_count = seeds.elems.len();
+                    let seeds = seeds.elems.iter();
+                    let indices = 0..seed_count;
+                    let bump = match bump {
+                        Some(bump) => quote! { Some(#bump) },
+                        None => quote! { None },
                     };
+                    (
+                        quote! {
+                            let seeds: [&[u8]; #seed_count] = [#(#seeds),*];
+                            let bump = [mcpsol::constraints::check_seeds(info, &seeds, #bump, program_id)?];
+                            let signer_seeds = [
+                                #(mcpsol::pinocchio::instruction::Seed::from(seeds[#indices]),)*
+                                mcpsol::pinocchio::instruction::Seed::from(&bump),
+                            ];
+                        },
+                        quote! { &[mcpsol::pinocchio::instruction::Signer::from(&signer_seeds)] },
+                    )
                 }
-            }
+                _ => (quote! {}, quote! { &[] }),
+            };
+            lifecycle = quote! {
+                #seed_setup
+                mcpsol::lifecycle::init_account(
+                    info,
+                    #payer.as_account_info(),
+                    #space,
+                    program_id,
+                    &<#data_ty as mcpsol::account::AccountData>::DISCRIMINATOR,
+                    #signers,
+                )?;
+            };
+        } else if let Some(ref new_len) = field_attrs.realloc {
+            let payer = &field_attrs.payer;
+            lifecycle = quote! {
+                mcpsol::lifecycle::realloc_account(info, #payer.as_account_info(), #new_len)?;
+            };
+        }
+
+        let wrap = if is_signer && !init {
+            quote! { mcpsol::account::Signer::try_from(info)? }
         } else if is_raw_ref {
-            // Raw reference - just use the account directly
-            if is_mut {
-                quote! {
-                    let #field_name = {
-                        let info = accounts.get(#idx)
-                            .ok_or(mcpsol::error::McpSolError::MissingAccount)?;
-                        if !info.is_writable() {
-                            return Err(mcpsol::error::McpSolError::NotWritable.into());
-                        }
-                        info
-                    };
-                }
-            } else {
-                quote! {
-                    let #field_name = accounts.get(#idx)
-                        .ok_or(mcpsol::error::McpSolError::MissingAccount)?;
-                }
+            quote! { info }
+        } else if is_account_type(field_ty) {
+            // The owner was checked above, or just assigned by `init`
+            quote! { <#field_ty>::try_from_unchecked(info)? }
+        } else if type_name_is(field_ty, "Program") {
+            if field_attrs.address.is_none() {
+                return syn::Error::new_spanned(
+                    field_ty,
+                    "`Program` fields require `address = <program id>`",
+                )
+                .to_compile_error()
+                .into();
             }
-        } else if is_mut {
+            // The key was checked against `address` above
+            quote! { mcpsol::account::Program { info, program_id: info.key() } }
+        } else {
+            quote! { <#field_ty>::try_from(info)? }
+        };
+        let extraction = if optional {
             quote! {
                 let #field_name = {
-                    let info = accounts.get(#idx)
+                    let info = accounts.get(#offset)
                         .ok_or(mcpsol::error::McpSolError::MissingAccount)?;
-                    if !info.is_writable() {
-                        return Err(mcpsol::error::McpSolError::NotWritable.into());
+                    if info.key() == program_id {
+                        None
+                    } else {
+                        #(#info_checks)*
+                        Some(#wrap)
                     }
-                    <#field_ty>::try_from(info)?
                 };
             }
         } else {
             quote! {
                 let #field_name = {
-                    let info = accounts.get(#idx)
+                    let info = accounts.get(#offset)
                         .ok_or(mcpsol::error::McpSolError::MissingAccount)?;
-                    <#field_ty>::try_from(info)?
+                    #(#info_checks)*
+                    #lifecycle
+                    #wrap
                 };
             }
         };
+        if init || field_attrs.realloc.is_some() {
+            deferred_extractions.push(extraction);
+        } else {
+            field_extractions.push(extraction);
+        }
+
+        if let Some(ref destination) = field_attrs.close {
+            let dest = fields
+                .iter()
+                .zip(&offsets)
+                .zip(&all_attrs)
+                .find(|((f, _), _)| f.ident.as_ref().is_some_and(|ident| destination == ident));
+            let Some(((_, dest_offset), _)) = dest.filter(|(_, dest_attrs)| !dest_attrs.nested)
+            else {
+                return syn::Error::new_spanned(
+                    destination,
+                    "`close` must name an account field, not a nested `Accounts` field",
+                )
+                .to_compile_error()
+                .into();
+            };
+            exit_actions.push(quote! {
+                mcpsol::lifecycle::close_account(
+                    accounts.get(#offset).ok_or(mcpsol::error::McpSolError::MissingAccount)?,
+                    accounts.get(#dest_offset).ok_or(mcpsol::error::McpSolError::MissingAccount)?,
+                )?;
+            });
+        }
 
-        field_extractions.push(extraction);
+        // Checks that may reference other fields run once all are loaded
+        let mut own_checks = Vec::new();
+        for target in &field_attrs.has_one {
+            if !all_names.iter().any(|n| target == n) {
+                return syn::Error::new_spanned(
+                    target,
+                    format!("`has_one` target `{}` is not a field of `{}`", target, name),
+                )
+                .to_compile_error()
+                .into();
+            }
+            // Loaders borrow the data for the duration of the check
+            let data = if is_loader_type(field_ty) {
+                quote! { #field_name.load()? }
+            } else {
+                quote! { #field_name.data }
+            };
+            own_checks.push(quote! {
+                mcpsol::constraints::check_has_one(
+                    core::convert::AsRef::<[u8]>::as_ref(&#data.#target),
+                    #target.as_account_info(),
+                )?;
+            });
+        }
+        // `init` already derived the address while signing for it
+        if let (Some(seeds), Some(bump), false) = (&field_attrs.seeds, &field_attrs.bump, init) {
+            let seeds = seeds.elems.iter();
+            let bump = match bump {
+                Some(bump) => quote! { Some(#bump) },
+                None => quote! { None },
+            };
+            own_checks.push(quote! {
+                mcpsol::constraints::check_seeds(
+                    #field_name.as_account_info(),
+                    &[#(#seeds),*],
+                    #bump,
+                    program_id,
+                )?;
+            });
+        }
+        for constraint in &field_attrs.constraints {
+            own_checks.push(quote! {
+                mcpsol::constraints::check_constraint(#constraint)?;
+            });
+        }
+        // An absent optional account has nothing to check
+        if optional && !own_checks.is_empty() {
+            field_checks.push(quote! {
+                if let Some(#field_name) = &#field_name {
+                    #(#own_checks)*
+                }
+            });
+        } else {
+            field_checks.extend(own_checks);
+        }
     }
 
+    // Schema metadata for #[mcp_program] to build account lists from. With
+    // nested fields the accounts are only known once the consts evaluate.
+    let account_meta = if nested_lens.is_empty() {
+        let fragments = mcp_gen::account_fragments(&account_metas);
+        let meta_defs = account_metas.iter().map(program::account_meta_def);
+        let compact_properties = &fragments.compact_properties;
+        let compact_required = &fragments.compact_required;
+        let verbose = &fragments.verbose;
+        let verbose_stripped = &fragments.verbose_stripped;
+        quote! {
+            const ACCOUNTS: &'static [mcpsol::context::AccountMetaDef] = &[#(#meta_defs),*];
+            const COMPACT_PROPERTIES: &'static str = #compact_properties;
+            const COMPACT_REQUIRED: &'static str = #compact_required;
+            const VERBOSE_PROPERTIES: &'static str = #verbose;
+            const VERBOSE_PROPERTIES_STRIPPED: &'static str = #verbose_stripped;
+        }
+    } else {
+        let fragment = |kind: &str| {
+            let kind = syn::Ident::new(kind, proc_macro2::Span::call_site());
+            quote! {{
+                const DEFS: &[mcpsol::context::AccountMetaDef] =
+                    <#name<'static> as mcpsol::context::AccountsMeta>::ACCOUNTS;
+                const KIND: mcpsol::schema::AccountFragment =
+                    mcpsol::schema::AccountFragment::#kind;
+                const LEN: usize = mcpsol::schema::account_fragment_len(DEFS, KIND);
+                const BYTES: [u8; LEN] = mcpsol::schema::account_fragment::<LEN>(DEFS, KIND);
+                mcpsol::schema::utf8(&BYTES)
+            }}
+        };
+        let compact_properties = fragment("CompactProperties");
+        let compact_required = fragment("CompactRequired");
+        let verbose = fragment("VerboseProperties");
+        let verbose_stripped = fragment("VerbosePropertiesStripped");
+        quote! {
+            const ACCOUNTS: &'static [mcpsol::context::AccountMetaDef] = {
+                const FIELDS: &[mcpsol::schema::AccountsField] = &[#(#schema_fields),*];
+                const NAMES_LEN: usize = mcpsol::schema::nested_names_len(FIELDS);
+                const NAMES: [u8; NAMES_LEN] = mcpsol::schema::nested_names::<NAMES_LEN>(FIELDS);
+                const LEN: usize = mcpsol::schema::accounts_len(FIELDS);
+                const DEFS: [mcpsol::context::AccountMetaDef; LEN] =
+                    mcpsol::schema::flatten_accounts::<LEN>(FIELDS, &NAMES);
+                &DEFS
+            };
+            const COMPACT_PROPERTIES: &'static str = #compact_properties;
+            const COMPACT_REQUIRED: &'static str = #compact_required;
+            const VERBOSE_PROPERTIES: &'static str = #verbose;
+            const VERBOSE_PROPERTIES_STRIPPED: &'static str = #verbose_stripped;
+        }
+    };
+    let exit = if exit_actions.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            fn exit(
+                program_id: &mcpsol::prelude::Pubkey,
+                accounts: &'info [mcpsol::prelude::AccountInfo],
+            ) -> mcpsol::Result<()> {
+                let _ = program_id;
+                #(#exit_actions)*
+                Ok(())
+            }
+        }
+    };
+
     let expanded = quote! {
+        impl<'info> mcpsol::context::AccountsMeta for #name<'info> {
+            #account_meta
+        }
+
         impl<'info> mcpsol::context::Accounts<'info> for #name<'info> {
+            const LEN: usize = #accounts_len;
+
+            #[allow(unused_imports)]
             fn try_accounts(
-                _program_id: &mcpsol::prelude::Pubkey,
+                program_id: &mcpsol::prelude::Pubkey,
                 accounts: &'info [mcpsol::prelude::AccountInfo],
             ) -> mcpsol::Result<Self> {
-                if accounts.len() < #field_count {
+                use mcpsol::account::AsAccountInfo as _;
+                let _ = program_id;
+
+                if accounts.len() < #accounts_len {
                     return Err(mcpsol::error::McpSolError::MissingAccount.into());
                 }
 
                 #(#field_extractions)*
+                #(#deferred_extractions)*
+                #(#field_checks)*
 
                 Ok(Self {
                     #(#field_names),*
                 })
             }
+
+            #exit
         }
     };
 
     TokenStream::from(expanded)
 }
-
-// === Helper functions ===
-
-struct ProgramAttrs {
-    name: Option<String>,
-    description: Option<String>,
-}
-
-fn parse_program_attrs(attr: TokenStream) -> ProgramAttrs {
-    let attr_str = attr.to_string();
-    ProgramAttrs {
-        name: extract_attr_value(&attr_str, "name"),
-        description: extract_attr_value(&attr_str, "description"),
-    }
-}
-
-struct InstructionAttrs {
-    name: Option<String>,
-    description: Option<String>,
-}
-
-fn parse_instruction_attrs(attr: TokenStream) -> InstructionAttrs {
-    let attr_str = attr.to_string();
-    InstructionAttrs {
-        name: extract_attr_value(&attr_str, "name"),
-        description: extract_attr_value(&attr_str, "description"),
-    }
-}
-
-fn parse_mcp_account_attrs(input: &DeriveInput) -> (String, String) {
-    let mut name = input.ident.to_string();
-    let mut desc = String::new();
-
-    // Parse #[mcp_account(...)] attributes
-    for attr in &input.attrs {
-        if attr.path().is_ident("mcp_account") {
-            if let Ok(list) = attr.meta.require_list() {
-                let tokens = list.tokens.to_string();
-                if let Some(n) = extract_attr_value(&tokens, "name") {
-                    name = n;
-                }
-                if let Some(d) = extract_attr_value(&tokens, "description") {
-                    desc = d;
-                }
-            }
-        }
-    }
-
-    (name, desc)
-}
-
-fn extract_attr_value(attr_str: &str, key: &str) -> Option<String> {
-    let pattern = format!("{} = \"", key);
-    if let Some(start) = attr_str.find(&pattern) {
-        let value_start = start + pattern.len();
-        if let Some(end) = attr_str[value_start..].find('"') {
-            return Some(attr_str[value_start..value_start + end].to_string());
-        }
-    }
-    None
-}
@@ -2,102 +2,754 @@
 //!
 //! Generates JSON schema at compile time from instruction and account definitions.
 
-use crate::program::InstructionInfo;
+use mcpsol_core::{
+    generate_compact_errors, generate_compact_tool, generate_paginated_schema,
+    generate_paginated_schema_bytes, generate_verbose_accounts, McpAccountMeta,
+    McpError, McpSchema, McpSchemaBuilder, McpToolBuilder, DISCRIMINATOR_LEN,
+    OPTIONAL_ACCOUNT_TYPE, PROTOCOL_VERSION,
+};
 
-/// Generate MCP schema JSON string from extracted metadata
-/// Note: Solana return_data limit is 1024 bytes, so we keep schema compact
-pub fn generate_schema_json(
+use crate::errors::ErrorVariant;
+use crate::program::{AccountMeta, InstructionInfo};
+
+/// Description of the built-in list_tools tool on verbose pages
+const LIST_TOOLS_DESCRIPTION: &str = "List available MCP tools. Pass cursor byte to paginate.";
+
+/// Solana return_data limit; every schema payload must fit in it
+pub const RETURN_DATA_LIMIT: usize = 1024;
+
+/// What `#[mcp_program]` does when a schema payload exceeds return_data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchemaOverflow {
+    /// Fail the build with a per-tool byte breakdown
+    #[default]
+    Error,
+    /// Serve the first verbose page when no cursor is given
+    Paginate,
+    /// Drop descriptions until the payload fits
+    Strip,
+}
+
+impl SchemaOverflow {
+    /// Parse the `schema_overflow` attribute value.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "error" => Some(Self::Error),
+            "paginate" => Some(Self::Paginate),
+            "strip" => Some(Self::Strip),
+            _ => None,
+        }
+    }
+}
+
+/// Fix suggested when the compact schema is over budget
+const COMPACT_HINT: &str = "set `schema_overflow = \"paginate\"` to serve one tool per list_tools page, \
+     or `schema_overflow = \"strip\"` to drop tool descriptions";
+
+/// Fix suggested when a verbose page is over budget
+const PAGE_HINT: &str = "shorten their descriptions or set `schema_overflow = \"strip\"`";
+
+/// Start of every placeholder, which names and descriptions may not contain
+const RESERVED_PREFIX: &str = "__mcpsol_";
+
+/// Placeholder account standing in for a `#[derive(Accounts)]` list
+const PLACEHOLDER_PREFIX: &str = "__mcpsol_accounts_";
+
+/// Placeholder layout standing in for a `McpReturn::LAYOUT`
+const RETURNS_PLACEHOLDER_PREFIX: &str = "__mcpsol_returns_";
+
+/// Schema payloads served by the generated `list_tools`.
+pub struct SchemaPayloads {
+    /// Response without a cursor byte
+    pub compact: Payload,
+    /// Verbose pages, indexed by cursor
+    pub pages: Vec<Payload>,
+}
+
+/// A single schema payload.
+pub enum Payload {
+    /// Fully known at expansion time and already within budget
+    Fixed(Vec<u8>),
+    /// The first verbose page, served in place of the compact schema
+    FirstPage,
+    /// Contains account lists from `#[derive(Accounts)]` or return layouts
+    /// from `McpReturn`, so it is assembled and checked against the budget
+    /// in const context
+    Spliced {
+        /// What the payload is, for the overflow assertion
+        label: String,
+        parts: Vec<SchemaPart>,
+        fallback: Fallback,
+    },
+}
+
+/// What a spliced payload is replaced with when it is over budget.
+pub enum Fallback {
+    /// Fail the build, suggesting this fix
+    Error(&'static str),
+    /// Serve the first verbose page instead
+    FirstPage,
+    /// Serve this description-free variant instead
+    Stripped(Vec<SchemaPart>),
+}
+
+/// One piece of a spliced payload.
+pub enum SchemaPart {
+    Bytes(Vec<u8>),
+    /// `AccountsMeta` fragment of an accounts type
+    Accounts {
+        accounts_type: String,
+        fragment: AccountsFragment,
+        comma: bool,
+    },
+    /// `McpReturn::LAYOUT` of a return type
+    Returns(Box<syn::Type>),
+}
+
+/// Which `AccountsMeta` constant a splice reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountsFragment {
+    CompactProperties,
+    CompactRequired,
+    Verbose,
+    VerboseStripped,
+}
+
+impl AccountsFragment {
+    /// Name of the `AccountsMeta` associated constant
+    pub const fn const_name(self) -> &'static str {
+        match self {
+            Self::CompactProperties => "COMPACT_PROPERTIES",
+            Self::CompactRequired => "COMPACT_REQUIRED",
+            Self::Verbose => "VERBOSE_PROPERTIES",
+            Self::VerboseStripped => "VERBOSE_PROPERTIES_STRIPPED",
+        }
+    }
+}
+
+/// Pre-rendered schema fragments for an `Accounts` struct.
+pub struct AccountFragments {
+    pub compact_properties: String,
+    pub compact_required: String,
+    pub verbose: String,
+    pub verbose_stripped: String,
+}
+
+/// Append PDA seeds to an account description as `seeds=["vault", owner]`.
+///
+/// String literals are quoted, seeds built from another field of the
+/// Accounts struct are named after it, and anything else is kept as written.
+pub fn describe_seeds(description: &str, seeds: &[syn::Expr], fields: &[String]) -> String {
+    let seeds: Vec<String> = seeds.iter().map(|seed| describe_seed(seed, fields)).collect();
+    let seeds = format!("seeds=[{}]", seeds.join(", "));
+    match description.trim_end() {
+        "" => seeds,
+        desc if desc.ends_with('.') => format!("{} {}", desc, seeds),
+        desc => format!("{}. {}", desc, seeds),
+    }
+}
+
+fn describe_seed(seed: &syn::Expr, fields: &[String]) -> String {
+    use syn::{Expr, Lit};
+
+    match seed {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(s) => format!("\"{}\"", s.value()),
+            Lit::ByteStr(s) => format!("\"{}\"", String::from_utf8_lossy(&s.value())),
+            _ => quote::quote!(#seed).to_string().replace(' ', ""),
+        },
+        Expr::Reference(r) => describe_seed(&r.expr, fields),
+        Expr::MethodCall(call) => describe_seed(&call.receiver, fields),
+        Expr::Field(field) => describe_seed(&field.base, fields),
+        Expr::Path(path) => match path.path.get_ident() {
+            Some(ident) if fields.iter().any(|f| ident == f) => ident.to_string(),
+            _ => quote::quote!(#seed).to_string().replace(' ', ""),
+        },
+        _ => quote::quote!(#seed).to_string().replace(' ', ""),
+    }
+}
+
+/// Render the fragments `#[derive(Accounts)]` exposes through `AccountsMeta`.
+pub fn account_fragments(accounts: &[AccountMeta]) -> AccountFragments {
+    let keys: Vec<String> = accounts.iter().map(compact_account_key).collect();
+    let metas: Vec<McpAccountMeta> = accounts
+        .iter()
+        .map(|acc| McpAccountMeta {
+            name: acc.name.clone(),
+            description: (!acc.description.is_empty()).then(|| acc.description.clone()),
+            is_signer: acc.is_signer,
+            is_writable: acc.is_writable,
+            is_optional: acc.is_optional,
+        })
+        .collect();
+    let bare: Vec<McpAccountMeta> = metas
+        .iter()
+        .map(|meta| McpAccountMeta { description: None, ..meta.clone() })
+        .collect();
+
+    AccountFragments {
+        compact_properties: keys
+            .iter()
+            .zip(accounts)
+            .map(|(key, acc)| format!(r#""{}":"{}""#, key, account_type(acc)))
+            .collect::<Vec<_>>()
+            .join(","),
+        compact_required: keys
+            .iter()
+            .map(|key| format!(r#""{}""#, key))
+            .collect::<Vec<_>>()
+            .join(","),
+        verbose: generate_verbose_accounts(&metas),
+        verbose_stripped: generate_verbose_accounts(&bare),
+    }
+}
+
+/// Generate every schema payload and check it against the return_data limit.
+///
+/// Returns the compile error message when a payload cannot be made to fit
+/// under the chosen policy.
+pub fn generate_schema_payloads(
     program_name: &str,
-    _program_desc: &str,  // Omitted to save space
+    program_desc: &str,
     instructions: &[InstructionInfo],
-) -> String {
-    let mut tools = Vec::new();
+    errors: &[ErrorVariant],
+    policy: SchemaOverflow,
+    discriminator_len: usize,
+) -> Result<SchemaPayloads, String> {
+    check_reserved_text(program_name, program_desc, instructions, errors)?;
+    let errors: Vec<McpError> = errors.iter().map(ErrorVariant::to_schema).collect();
+    let bare_errors = strip_messages(&errors);
+    // Verbose pages take account entries (and their doc descriptions) from
+    // `#[derive(Accounts)]` whenever there is one; the compact schema carries
+    // no account descriptions, so it only needs them when the list is inferred
+    let verbose_prepared = with_account_placeholders(instructions, InstructionInfo::derives_accounts);
+    let prepared = with_account_placeholders(instructions, InstructionInfo::infers_accounts);
+    let verbose = build_verbose_schema(
+        program_name,
+        program_desc,
+        &verbose_prepared,
+        &errors,
+        discriminator_len,
+    )?;
+    if verbose.tools.len() > usize::from(u8::MAX) + 1 {
+        return Err(format!(
+            "program `{}` has {} tools, but the list_tools cursor is a single byte (max 256)",
+            program_name,
+            verbose.tools.len(),
+        ));
+    }
+
+    let pages = generate_budgeted_pages(&verbose, &verbose_prepared, policy)?;
 
-    for ix in instructions {
-        let tool = generate_tool_schema(ix);
-        tools.push(tool);
+    let tools = compact_tools(&prepared, true);
+    let compact = assemble_compact_schema(program_name, &tools, &errors, discriminator_len);
+
+    // Sizes of derived account lists and return layouts are only known in
+    // const context
+    if prepared.iter().any(|ix| ix.infers_accounts() || ix.splices_returns()) {
+        let fallback = match policy {
+            SchemaOverflow::Error => Fallback::Error(COMPACT_HINT),
+            SchemaOverflow::Paginate => Fallback::FirstPage,
+            SchemaOverflow::Strip => {
+                let stripped = assemble_compact_schema(
+                    program_name,
+                    &compact_tools(&prepared, false),
+                    &bare_errors,
+                    discriminator_len,
+                );
+                Fallback::Stripped(splice_schema(&stripped, &prepared, false))
+            }
+        };
+        let compact = Payload::Spliced {
+            label: format!("compact MCP schema for `{}`", program_name),
+            parts: splice_schema(&compact, &prepared, false),
+            fallback,
+        };
+        return Ok(SchemaPayloads { compact, pages });
     }
 
-    // Add list_tools as a built-in tool (compact format matching other tools)
-    tools.push(r#"{"n":"list_tools","d":"42195e6a55fd41c0"}"#.to_string());
+    if compact.len() <= RETURN_DATA_LIMIT {
+        return Ok(SchemaPayloads { compact: Payload::Fixed(compact.into_bytes()), pages });
+    }
 
-    // Compact format - omit description and resources to stay under 1024 bytes
-    format!(
-        r#"{{"v":"2024-11-05","name":"{}","tools":[{}]}}"#,
-        escape_json(program_name),
-        tools.join(","),
-    )
+    let compact = match policy {
+        SchemaOverflow::Error => {
+            return Err(compact_overflow_message(program_name, &tools, compact.len()));
+        }
+        // Page 0 passes its own budget check
+        SchemaOverflow::Paginate => return Ok(SchemaPayloads { compact: Payload::FirstPage, pages }),
+        SchemaOverflow::Strip => {
+            let stripped_tools = compact_tools(&prepared, false);
+            let stripped =
+                assemble_compact_schema(program_name, &stripped_tools, &bare_errors, discriminator_len);
+            if stripped.len() > RETURN_DATA_LIMIT {
+                return Err(compact_overflow_message(
+                    program_name,
+                    &stripped_tools,
+                    stripped.len(),
+                ));
+            }
+            stripped.into_bytes()
+        }
+    };
+
+    Ok(SchemaPayloads { compact: Payload::Fixed(compact), pages })
 }
 
-/// Generate a single tool's schema (compact format for 1024 byte limit)
-fn generate_tool_schema(ix: &InstructionInfo) -> String {
-    let mut properties = Vec::new();
-    let mut required = Vec::new();
-
-    // Add accounts as pubkey properties (compact: just type, no description)
-    for acc in &ix.accounts {
-        // Compact: mark signer/writable in property name suffix
-        let suffix = match (acc.is_signer, acc.is_writable) {
-            (true, true) => "_sw",
-            (true, false) => "_s",
-            (false, true) => "_w",
-            (false, false) => "",
-        };
-        let escaped_acc_name = escape_json(&acc.name);
-        let prop = format!(
-            r#""{}{}":"pubkey""#,
-            escaped_acc_name, suffix
-        );
-        properties.push(prop);
-        required.push(format!(r#""{}{}""#, escaped_acc_name, suffix));
+/// Reject text the schema carries that [`splice_schema`] could take for a
+/// placeholder.
+fn check_reserved_text(
+    program_name: &str,
+    program_desc: &str,
+    instructions: &[InstructionInfo],
+    errors: &[ErrorVariant],
+) -> Result<(), String> {
+    let tools = instructions.iter().flat_map(|ix| {
+        let args = ix.args.iter().flat_map(|arg| [&arg.name, &arg.description]);
+        let accounts = ix
+            .accounts
+            .iter()
+            .chain(&ix.remaining_accounts)
+            .flat_map(|acc| [&acc.name, &acc.description]);
+        [&ix.tool_name, &ix.tool_desc].into_iter().chain(args).chain(accounts).chain(&ix.aliases)
+    });
+    let errors = errors.iter().map(|variant| &variant.message);
+    let reserved = [program_name, program_desc]
+        .into_iter()
+        .chain(tools.chain(errors).map(String::as_str))
+        .find(|text| text.contains(RESERVED_PREFIX));
+    match reserved {
+        Some(text) => Err(format!(
+            "`{}` contains `{}`, which is reserved for schema placeholders",
+            text, RESERVED_PREFIX,
+        )),
+        None => Ok(()),
     }
+}
+
+/// Replace the account lists selected by `splice` with a placeholder
+/// account that [`splice_schema`] can find in the rendered JSON.
+fn with_account_placeholders(
+    instructions: &[InstructionInfo],
+    splice: fn(&InstructionInfo) -> bool,
+) -> Vec<InstructionInfo> {
+    instructions
+        .iter()
+        .enumerate()
+        .map(|(i, ix)| {
+            let mut ix = ix.clone();
+            if splice(&ix) {
+                ix.accounts = vec![AccountMeta {
+                    name: format!("{}{}__", PLACEHOLDER_PREFIX, i),
+                    is_signer: false,
+                    is_writable: false,
+                    is_optional: false,
+                    description: String::new(),
+                }];
+            }
+            ix
+        })
+        .collect()
+}
+
+/// Return layout of an instruction, or a placeholder [`splice_schema`]
+/// replaces with its `McpReturn::LAYOUT`.
+fn return_layout(index: usize, ix: &InstructionInfo) -> Option<String> {
+    let ret = ix.returns.as_ref()?;
+    Some(ret.layout.clone().unwrap_or_else(|| {
+        format!("\"{}{}__\"", RETURNS_PLACEHOLDER_PREFIX, index)
+    }))
+}
 
-    // Add instruction arguments (compact types)
-    for arg in &ix.args {
-        // Map to compact type names
-        let compact_type = if arg.json_type.contains("integer") {
-            "int"
-        } else if arg.json_type.contains("boolean") {
-            "bool"
+/// Split rendered JSON at placeholder accounts and return layouts into
+/// spliceable parts.
+fn splice_schema(json: &str, instructions: &[InstructionInfo], stripped: bool) -> Vec<SchemaPart> {
+    let prefix = format!("\"{}", PLACEHOLDER_PREFIX);
+    let mut parts = Vec::new();
+    let mut rest = json;
+
+    while let Some(start) = rest.find(&prefix) {
+        let after = &rest[start + prefix.len()..];
+        let Some(end) = after.find("__\"") else {
+            break;
+        };
+        let Some(ix) = after[..end].parse::<usize>().ok().and_then(|i| instructions.get(i)) else {
+            break;
+        };
+
+        let mut tail = &after[end + 3..];
+        let fragment = if let Some(t) = tail.strip_prefix(r#":"pubkey""#) {
+            tail = t;
+            AccountsFragment::CompactProperties
+        } else if let Some(t) = tail.strip_prefix(r#":{"type":"pubkey"}"#) {
+            tail = t;
+            if stripped {
+                AccountsFragment::VerboseStripped
+            } else {
+                AccountsFragment::Verbose
+            }
         } else {
-            "str"
+            AccountsFragment::CompactRequired
+        };
+        let comma = match tail.strip_prefix(',') {
+            Some(t) => {
+                tail = t;
+                true
+            }
+            None => false,
         };
-        let escaped_arg_name = escape_json(&arg.name);
-        let prop = format!(r#""{}":"{}""#, escaped_arg_name, compact_type);
-        properties.push(prop);
-        required.push(format!(r#""{}""#, escaped_arg_name));
+
+        splice_returns(&rest[..start], instructions, &mut parts);
+        parts.push(SchemaPart::Accounts {
+            accounts_type: ix.accounts_type.clone().unwrap_or_default(),
+            fragment,
+            comma,
+        });
+        rest = tail;
     }
 
-    // Discriminator as hex (essential for calling)
-    let disc_hex: String = ix.discriminator.iter()
-        .map(|b| format!("{:02x}", b))
-        .collect();
+    splice_returns(rest, instructions, &mut parts);
+    parts
+}
+
+/// Push `json` as parts, split at placeholder return layouts.
+fn splice_returns(json: &str, instructions: &[InstructionInfo], parts: &mut Vec<SchemaPart>) {
+    let prefix = format!("\"{}", RETURNS_PLACEHOLDER_PREFIX);
+    let mut rest = json;
+
+    while let Some(start) = rest.find(&prefix) {
+        let after = &rest[start + prefix.len()..];
+        let Some(end) = after.find("__\"") else {
+            break;
+        };
+        let Some(ret) = after[..end]
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| instructions.get(i))
+            .and_then(|ix| ix.returns.as_ref())
+        else {
+            break;
+        };
 
-    // Add description if present (compact: "i" = info)
-    let desc_part = if !ix.tool_desc.is_empty() {
-        format!(r#","i":"{}""#, escape_json(&ix.tool_desc))
+        parts.push(SchemaPart::Bytes(rest.as_bytes()[..start].to_vec()));
+        parts.push(SchemaPart::Returns(Box::new(ret.ty.clone())));
+        rest = &after[end + 3..];
+    }
+
+    parts.push(SchemaPart::Bytes(rest.as_bytes().to_vec()));
+}
+
+/// Serialize verbose pages, stripping or rejecting the ones over budget.
+fn generate_budgeted_pages(
+    schema: &McpSchema,
+    instructions: &[InstructionInfo],
+    policy: SchemaOverflow,
+) -> Result<Vec<Payload>, String> {
+    let stripped = strip_descriptions(schema);
+    let mut pages = Vec::new();
+    let mut errors = Vec::new();
+
+    for (cursor, page) in generate_schema_pages(schema).into_iter().enumerate() {
+        let tool = &schema.tools[cursor].name;
+
+        if instructions
+            .get(cursor)
+            .is_some_and(|ix| ix.derives_accounts() || ix.splices_returns())
+        {
+            let page = String::from_utf8_lossy(&page);
+            let fallback = if policy == SchemaOverflow::Strip {
+                let bare = generate_paginated_schema(&stripped, cursor as u8);
+                Fallback::Stripped(splice_schema(&bare, instructions, true))
+            } else {
+                Fallback::Error(PAGE_HINT)
+            };
+            pages.push(Payload::Spliced {
+                label: format!("verbose list_tools page {} (`{}`)", cursor, tool),
+                parts: splice_schema(&page, instructions, false),
+                fallback,
+            });
+            continue;
+        }
+
+        if page.len() <= RETURN_DATA_LIMIT {
+            pages.push(Payload::Fixed(page));
+            continue;
+        }
+        if policy == SchemaOverflow::Strip {
+            let bare = generate_paginated_schema_bytes(&stripped, cursor as u8);
+            if bare.len() <= RETURN_DATA_LIMIT {
+                pages.push(Payload::Fixed(bare));
+                continue;
+            }
+            errors.push(format!(
+                "  page {} (`{}`): {} bytes even without descriptions",
+                cursor,
+                tool,
+                bare.len(),
+            ));
+        } else {
+            errors.push(format!(
+                "  page {} (`{}`): {} bytes, {} over",
+                cursor,
+                tool,
+                page.len(),
+                page.len() - RETURN_DATA_LIMIT,
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        return Ok(pages);
+    }
+
+    let hint = if policy == SchemaOverflow::Strip {
+        "reduce the accounts or arguments of these tools"
     } else {
+        PAGE_HINT
+    };
+    Err(format!(
+        "verbose list_tools pages for `{}` exceed the {}-byte return_data limit:\n{}\n{}",
+        schema.name,
+        RETURN_DATA_LIMIT,
+        errors.join("\n"),
+        hint,
+    ))
+}
+
+/// Copy of the schema with every description removed.
+fn strip_descriptions(schema: &McpSchema) -> McpSchema {
+    let mut stripped = schema.clone();
+    stripped.description = None;
+    stripped.errors = strip_messages(&schema.errors);
+    for tool in &mut stripped.tools {
+        tool.description = None;
+        for acc in tool.accounts.iter_mut().chain(tool.remaining_accounts.as_mut()) {
+            acc.description = None;
+        }
+        for arg in &mut tool.args {
+            arg.description = None;
+        }
+    }
+    stripped
+}
+
+/// Copy of the errors without their messages.
+fn strip_messages(errors: &[McpError]) -> Vec<McpError> {
+    errors
+        .iter()
+        .map(|e| McpError { message: None, ..e.clone() })
+        .collect()
+}
+
+/// Compact entries as (tool name, JSON), with `list_tools` last.
+///
+/// Tools are rendered by mcpsol-core so macro-built schemas match
+/// builder-built ones byte for byte.
+fn compact_tools(instructions: &[InstructionInfo], with_desc: bool) -> Vec<(String, String)> {
+    instructions
+        .iter()
+        .enumerate()
+        .map(|(i, ix)| {
+            let mut tool = McpToolBuilder::new(&ix.tool_name);
+            if with_desc && !ix.tool_desc.is_empty() {
+                tool = tool.description(&ix.tool_desc);
+            }
+            for acc in &ix.accounts {
+                tool = if acc.is_optional {
+                    tool.optional_account(&acc.name, acc.is_signer, acc.is_writable)
+                } else {
+                    tool.account(&acc.name, acc.is_signer, acc.is_writable)
+                };
+            }
+            if let Some(ref acc) = ix.remaining_accounts {
+                tool = tool.remaining_accounts(&acc.name, acc.is_signer, acc.is_writable);
+            }
+            for arg in &ix.args {
+                tool = tool.arg(&arg.name, arg.arg_type);
+            }
+            for alias in &ix.aliases {
+                tool = tool.alias(alias);
+            }
+            if let Some(layout) = return_layout(i, ix) {
+                tool = tool.returns_layout(layout);
+            }
+            let mut tool = tool.build();
+            tool.discriminator_len = ix.discriminator_len;
+            (ix.tool_name.clone(), generate_compact_tool(&tool))
+        })
+        .chain(std::iter::once((
+            "list_tools".to_string(),
+            generate_compact_tool(&McpToolBuilder::new("list_tools").build()),
+        )))
+        .collect()
+}
+
+/// Wrap compact tool entries and errors in the schema envelope, which
+/// advertises short discriminators as `"dl"`.
+fn assemble_compact_schema(
+    program_name: &str,
+    tools: &[(String, String)],
+    errors: &[McpError],
+    discriminator_len: usize,
+) -> String {
+    let entries: Vec<&str> = tools.iter().map(|(_, json)| json.as_str()).collect();
+    let errors = if errors.is_empty() {
         String::new()
+    } else {
+        format!(r#","e":[{}]"#, generate_compact_errors(errors))
     };
 
-    // Compact format: n=name, i=info (optional), d=discriminator, p=props, r=required
-    let escaped_name = escape_json(&ix.tool_name);
-    if properties.is_empty() {
-        format!(
-            r#"{{"n":"{}"{},"d":"{}"}}"#,
-            escaped_name,
-            desc_part,
-            disc_hex,
-        )
+    let discriminator_len = if discriminator_len == DISCRIMINATOR_LEN {
+        String::new()
     } else {
-        format!(
-            r#"{{"n":"{}"{},"d":"{}","p":{{{}}},"r":[{}]}}"#,
-            escaped_name,
-            desc_part,
-            disc_hex,
-            properties.join(","),
-            required.join(","),
+        format!(r#","dl":{}"#, discriminator_len)
+    };
+
+    // Compact format - omit description and resources to stay under 1024 bytes
+    format!(
+        r#"{{"v":"{}","name":"{}"{},"tools":[{}]{}}}"#,
+        PROTOCOL_VERSION,
+        escape_json(program_name),
+        discriminator_len,
+        entries.join(","),
+        errors,
+    )
+}
+
+/// Build the compile error for a compact schema over the limit.
+fn compact_overflow_message(program_name: &str, tools: &[(String, String)], total: usize) -> String {
+    let envelope = "(envelope)";
+    let width = tools
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(std::iter::once(envelope.len()))
+        .max()
+        .unwrap_or(0);
+    let tool_bytes: usize = tools.iter().map(|(_, json)| json.len()).sum();
+
+    let mut lines = vec![format!(
+        "compact MCP schema for `{}` is {} bytes, {} over the {}-byte return_data limit:",
+        program_name,
+        total,
+        total - RETURN_DATA_LIMIT,
+        RETURN_DATA_LIMIT,
+    )];
+    for (name, json) in tools {
+        lines.push(format!("  {:<width$}  {:>4} bytes", name, json.len()));
+    }
+    lines.push(format!(
+        "  {:<width$}  {:>4} bytes",
+        envelope,
+        total - tool_bytes,
+    ));
+    lines.push(COMPACT_HINT.to_string());
+    lines.join("\n")
+}
+
+/// Build the full schema with every description, for verbose pages.
+///
+/// Tools keep declaration order and the built-in `list_tools` comes last,
+/// matching the compact schema. Fails for a `discriminator_len` other than
+/// 1, 2, 4 or 8.
+pub fn build_verbose_schema(
+    program_name: &str,
+    program_desc: &str,
+    instructions: &[InstructionInfo],
+    errors: &[McpError],
+    discriminator_len: usize,
+) -> Result<McpSchema, String> {
+    let mut builder = McpSchemaBuilder::new(program_name)
+        .discriminator_len(discriminator_len)
+        .map_err(|e| e.to_string())?;
+    if !program_desc.is_empty() {
+        builder = builder.description(program_desc);
+    }
+    for err in errors {
+        builder = match err.message {
+            Some(ref message) => builder.error_desc(err.code, &err.name, message),
+            None => builder.error(err.code, &err.name),
+        };
+    }
+
+    for (i, ix) in instructions.iter().enumerate() {
+        let mut tool = McpToolBuilder::new(&ix.tool_name);
+        if !ix.tool_desc.is_empty() {
+            tool = tool.description(&ix.tool_desc);
+        }
+        for acc in &ix.accounts {
+            tool = match (acc.is_optional, acc.description.is_empty()) {
+                (false, true) => tool.account(&acc.name, acc.is_signer, acc.is_writable),
+                (false, false) => tool.account_with_desc(
+                    &acc.name,
+                    &acc.description,
+                    acc.is_signer,
+                    acc.is_writable,
+                ),
+                (true, true) => tool.optional_account(&acc.name, acc.is_signer, acc.is_writable),
+                (true, false) => tool.optional_account_desc(
+                    &acc.name,
+                    &acc.description,
+                    acc.is_signer,
+                    acc.is_writable,
+                ),
+            };
+        }
+        if let Some(ref acc) = ix.remaining_accounts {
+            tool = if acc.description.is_empty() {
+                tool.remaining_accounts(&acc.name, acc.is_signer, acc.is_writable)
+            } else {
+                tool.remaining_accounts_desc(&acc.name, &acc.description, acc.is_signer, acc.is_writable)
+            };
+        }
+        for arg in &ix.args {
+            tool = if arg.description.is_empty() {
+                tool.arg(&arg.name, arg.arg_type)
+            } else {
+                tool.arg_desc(&arg.name, &arg.description, arg.arg_type)
+            };
+        }
+        for alias in &ix.aliases {
+            tool = tool.alias(alias);
+        }
+        if let Some(layout) = return_layout(i, ix) {
+            tool = tool.returns_layout(layout);
+        }
+        builder = builder.add_tool(tool.build());
+    }
+
+    Ok(builder
+        .add_tool(
+            McpToolBuilder::new("list_tools")
+                .description(LIST_TOOLS_DESCRIPTION)
+                .build(),
         )
+        .build())
+}
+
+/// Serialize one verbose page per tool, indexed by cursor.
+pub fn generate_schema_pages(schema: &McpSchema) -> Vec<Vec<u8>> {
+    (0..schema.tools.len())
+        .map(|cursor| generate_paginated_schema_bytes(schema, cursor as u8))
+        .collect()
+}
+
+/// Compact property key for an account, with signer/writable name suffix
+fn compact_account_key(acc: &AccountMeta) -> String {
+    let suffix = match (acc.is_signer, acc.is_writable) {
+        (true, true) => "_sw",
+        (true, false) => "_s",
+        (false, true) => "_w",
+        (false, false) => "",
+    };
+    format!("{}{}", escape_json(&acc.name), suffix)
+}
+
+/// Schema type of an account, `"pubkey?"` when it may be left out
+const fn account_type(acc: &AccountMeta) -> &'static str {
+    if acc.is_optional {
+        OPTIONAL_ACCOUNT_TYPE
+    } else {
+        "pubkey"
     }
 }
 
@@ -113,7 +765,9 @@ fn escape_json(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mcpsol_core::ArgType;
     use crate::program::{AccountMeta, ArgInfo, InstructionInfo};
+    use crate::returns::ReturnInfo;
     use crate::discriminator::instruction_discriminator;
     use syn::Ident;
     use proc_macro2::Span;
@@ -135,8 +789,9 @@ mod tests {
                 args: vec![
                     ArgInfo {
                         name: "amount".to_string(),
+                        ty: syn::parse_quote!(u64),
                         rust_type: "u64".to_string(),
-                        json_type: r#"{"type":"integer","minimum":0}"#.to_string(),
+                        arg_type: ArgType::U64,
                         description: String::new(),
                     },
                 ],
@@ -145,21 +800,39 @@ mod tests {
                         name: "counter".to_string(),
                         is_signer: false,
                         is_writable: true,
+                        is_optional: false,
                         description: String::new(),
                     },
                     AccountMeta {
                         name: "authority".to_string(),
                         is_signer: true,
                         is_writable: false,
+                        is_optional: false,
                         description: String::new(),
                     },
                 ],
+                accounts_span: Some(Span::call_site()),
+                remaining_accounts: None,
                 accounts_type: Some("Modify".to_string()),
                 use_context: true,
+                ctx_by_ref: false,
+                aliases: Vec::new(),
+                unchecked: false,
+                discriminator_len: 8,
+                returns: None,
             },
         ];
 
-        let schema = generate_schema_json("test_program", "A test program", &instructions);
+        let payloads = generate_schema_payloads(
+            "test_program",
+            "A test program",
+            &instructions,
+            &[],
+            SchemaOverflow::Error,
+            8,
+        )
+        .unwrap();
+        let schema = String::from_utf8(fixed(payloads.compact)).unwrap();
 
         // Verify compact format
         assert!(schema.contains(r#""v":"2024-11-05""#));
@@ -170,8 +843,8 @@ mod tests {
         assert!(schema.contains(r#""counter_w":"pubkey""#));
         assert!(schema.contains(r#""authority_s":"pubkey""#));
 
-        // Verify args with compact types
-        assert!(schema.contains(r#""amount":"int""#));
+        // Verify args keep their exact width
+        assert!(schema.contains(r#""amount":"u64""#));
 
         // Verify discriminator
         assert!(schema.contains(r#""d":"0b12680968ae3b21""#));
@@ -182,4 +855,359 @@ mod tests {
         // Print for manual inspection
         println!("Generated schema ({} bytes):\n{}", schema.len(), schema);
     }
+
+    #[test]
+    fn test_generate_verbose_pages() {
+        let instructions = vec![InstructionInfo {
+            fn_name: Ident::new("increment", Span::call_site()),
+            tool_name: "increment".to_string(),
+            tool_desc: "Increase counter value".to_string(),
+            discriminator: instruction_discriminator("increment"),
+            args: vec![ArgInfo {
+                name: "amount".to_string(),
+                ty: syn::parse_quote!(u64),
+                rust_type: "u64".to_string(),
+                arg_type: ArgType::U64,
+                description: String::new(),
+            }],
+            accounts: vec![AccountMeta {
+                name: "counter".to_string(),
+                is_signer: false,
+                is_writable: true,
+                is_optional: false,
+                description: String::new(),
+            }],
+            accounts_span: None,
+            remaining_accounts: None,
+            accounts_type: None,
+            use_context: false,
+            ctx_by_ref: false,
+            aliases: Vec::new(),
+            unchecked: false,
+            discriminator_len: 8,
+            returns: None,
+        }];
+
+        let schema = build_verbose_schema("test_program", "A test program", &instructions, &[], 8).unwrap();
+        let pages = generate_schema_pages(&schema);
+        assert_eq!(pages.len(), 2);
+
+        let first = std::str::from_utf8(&pages[0]).unwrap();
+        println!("Page 0 ({} bytes):\n{}", first.len(), first);
+        assert!(first.contains(r#""description":"A test program""#));
+        assert!(first.contains(r#""description":"Increase counter value""#));
+        assert!(first.contains(r#""counter":{"type":"pubkey","writable":true}"#));
+        assert!(first.contains(r#""nextCursor":"1""#));
+
+        let last = std::str::from_utf8(&pages[1]).unwrap();
+        assert!(last.contains(r#""name":"list_tools""#));
+        assert!(!last.contains("nextCursor"));
+    }
+
+    fn fixed(payload: Payload) -> Vec<u8> {
+        match payload {
+            Payload::Fixed(bytes) => bytes,
+            Payload::FirstPage => panic!("compact schema should be fixed"),
+            Payload::Spliced { label, .. } => panic!("{} should be fixed", label),
+        }
+    }
+
+    fn described_tool(name: &str, desc: &str) -> InstructionInfo {
+        InstructionInfo {
+            fn_name: Ident::new(name, Span::call_site()),
+            tool_name: name.to_string(),
+            tool_desc: desc.to_string(),
+            discriminator: instruction_discriminator(name),
+            args: vec![],
+            accounts: vec![AccountMeta {
+                name: "state".to_string(),
+                is_signer: false,
+                is_writable: true,
+                is_optional: false,
+                description: String::new(),
+            }],
+            accounts_span: None,
+            remaining_accounts: None,
+            accounts_type: None,
+            use_context: false,
+            ctx_by_ref: false,
+            aliases: Vec::new(),
+            unchecked: false,
+            discriminator_len: 8,
+            returns: None,
+        }
+    }
+
+    #[test]
+    fn test_compact_overflow_policies() {
+        let desc = "Performs a moderately involved operation on the program state account";
+        let instructions: Vec<_> = (0..10)
+            .map(|i| described_tool(&format!("tool_{}", i), desc))
+            .collect();
+
+        let err = generate_schema_payloads("big", "", &instructions, &[], SchemaOverflow::Error, 8)
+            .err()
+            .unwrap();
+        println!("{}", err);
+        assert!(err.contains("over the 1024-byte return_data limit"));
+        assert!(err.contains("tool_9"));
+        assert!(err.contains("(envelope)"));
+
+        let stripped = generate_schema_payloads("big", "", &instructions, &[], SchemaOverflow::Strip, 8)
+            .unwrap();
+        let compact = fixed(stripped.compact);
+        assert!(compact.len() <= RETURN_DATA_LIMIT);
+        assert!(!String::from_utf8(compact).unwrap().contains(desc));
+
+        let paged = generate_schema_payloads("big", "", &instructions, &[], SchemaOverflow::Paginate, 8)
+            .unwrap();
+        assert!(matches!(paged.compact, Payload::FirstPage));
+    }
+
+    #[test]
+    fn test_page_overflow_policies() {
+        let desc = "x".repeat(RETURN_DATA_LIMIT);
+        let instructions = vec![described_tool("verbose", &desc)];
+
+        let err = generate_schema_payloads("big", "", &instructions, &[], SchemaOverflow::Paginate, 8)
+            .err()
+            .unwrap();
+        assert!(err.contains("page 0 (`verbose`)"));
+
+        let stripped = generate_schema_payloads("big", "", &instructions, &[], SchemaOverflow::Strip, 8)
+            .unwrap();
+        assert!(fixed(stripped.compact).len() <= RETURN_DATA_LIMIT);
+        let page = fixed(stripped.pages.into_iter().next().unwrap());
+        assert!(page.len() <= RETURN_DATA_LIMIT);
+    }
+
+    #[test]
+    fn test_inferred_accounts_are_spliced() {
+        let mut ix = described_tool("reset", "");
+        ix.accounts = vec![];
+        ix.accounts_type = Some("Modify".to_string());
+        ix.use_context = true;
+
+        let payloads = generate_schema_payloads("p", "", &[ix], &[], SchemaOverflow::Error, 8).unwrap();
+        let Payload::Spliced { parts, fallback, .. } = payloads.compact else {
+            panic!("compact schema should be spliced");
+        };
+        assert!(matches!(fallback, Fallback::Error(_)));
+
+        let fragments: Vec<_> = parts
+            .iter()
+            .filter_map(|part| match part {
+                SchemaPart::Accounts { accounts_type, fragment, comma } => {
+                    Some((accounts_type.as_str(), *fragment, *comma))
+                }
+                SchemaPart::Bytes(_) | SchemaPart::Returns(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            fragments,
+            vec![
+                ("Modify", AccountsFragment::CompactProperties, false),
+                ("Modify", AccountsFragment::CompactRequired, false),
+            ]
+        );
+        assert!(matches!(payloads.pages[0], Payload::Spliced { .. }));
+        assert!(matches!(payloads.pages[1], Payload::Fixed(_)));
+    }
+
+    #[test]
+    fn test_return_layouts_are_spliced() {
+        let mut balance = described_tool("balance", "");
+        balance.returns = Some(ReturnInfo {
+            ty: syn::parse_quote!(u64),
+            layout: Some("\"u64\"".into()),
+        });
+        let payloads =
+            generate_schema_payloads("p", "", &[balance.clone()], &[], SchemaOverflow::Error, 8).unwrap();
+        let Payload::Fixed(compact) = payloads.compact else {
+            panic!("a known layout needs no splicing");
+        };
+        assert!(String::from_utf8(compact).unwrap().contains(r#""o":"u64""#));
+
+        let mut info = described_tool("info", "");
+        info.returns = Some(ReturnInfo { ty: syn::parse_quote!(VaultInfo), layout: None });
+        let payloads =
+            generate_schema_payloads("p", "", &[balance, info], &[], SchemaOverflow::Error, 8).unwrap();
+        let Payload::Spliced { parts, .. } = payloads.compact else {
+            panic!("compact schema should be spliced");
+        };
+        let idx = parts.iter().position(|part| matches!(part, SchemaPart::Returns(_))).unwrap();
+        let (SchemaPart::Bytes(before), SchemaPart::Bytes(after)) = (&parts[idx - 1], &parts[idx + 1])
+        else {
+            panic!("expected bytes around the return layout");
+        };
+        assert!(before.ends_with(br#","o":"#));
+        assert!(after.starts_with(b"}"));
+        assert!(matches!(payloads.pages[0], Payload::Fixed(_)));
+        assert!(matches!(payloads.pages[1], Payload::Spliced { .. }));
+    }
+
+    #[test]
+    fn test_rejects_placeholder_text() {
+        let check = |ix: InstructionInfo| {
+            generate_schema_payloads("p", "", &[ix], &[], SchemaOverflow::Error, 8).err()
+        };
+        let err = check(described_tool("__mcpsol_accounts_0__", "")).unwrap();
+        assert!(err.contains("`__mcpsol_accounts_0__` contains `__mcpsol_`, which is reserved"));
+        assert!(check(described_tool("t", "Returns \"__mcpsol_returns_0__\"")).is_some());
+
+        let mut ix = described_tool("t", "");
+        ix.accounts[0].name = "__mcpsol_state".into();
+        assert!(check(ix).is_some());
+        assert!(check(described_tool("t", "mcpsol accounts")).is_none());
+    }
+
+    #[test]
+    fn test_remaining_accounts_follow_spliced_accounts() {
+        let mut ix = described_tool("approve", "");
+        ix.accounts = vec![];
+        ix.accounts_type = Some("Approve".to_string());
+        ix.use_context = true;
+        ix.remaining_accounts = Some(AccountMeta {
+            name: "signers".to_string(),
+            is_signer: true,
+            is_writable: false,
+            is_optional: false,
+            description: String::new(),
+        });
+
+        let payloads = generate_schema_payloads("p", "", &[ix], &[], SchemaOverflow::Error, 8).unwrap();
+        let Payload::Spliced { parts, .. } = payloads.compact else {
+            panic!("compact schema should be spliced");
+        };
+        let (idx, comma) = parts
+            .iter()
+            .enumerate()
+            .find_map(|(i, part)| match part {
+                SchemaPart::Accounts { fragment: AccountsFragment::CompactProperties, comma, .. } => {
+                    Some((i, *comma))
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert!(comma);
+        let SchemaPart::Bytes(next) = &parts[idx + 1] else {
+            panic!("expected bytes after the account fragment");
+        };
+        assert!(next.starts_with(br#""signers_s":"pubkey[]"}"#));
+    }
+
+    #[test]
+    fn test_account_fragments() {
+        let fragments = account_fragments(&[
+            AccountMeta {
+                name: "counter".to_string(),
+                is_signer: false,
+                is_writable: true,
+                is_optional: false,
+                description: "The counter".to_string(),
+            },
+            AccountMeta {
+                name: "authority".to_string(),
+                is_signer: true,
+                is_writable: false,
+                is_optional: true,
+                description: String::new(),
+            },
+        ]);
+        assert_eq!(fragments.compact_properties, r#""counter_w":"pubkey","authority_s":"pubkey?""#);
+        assert_eq!(fragments.compact_required, r#""counter_w","authority_s""#);
+        assert!(fragments.verbose.contains(r#""authority":{"type":"pubkey?","signer":true}"#));
+        assert!(fragments.verbose.contains(r#""description":"The counter""#));
+        assert!(!fragments.verbose_stripped.contains("description"));
+    }
+
+    #[test]
+    fn test_compact_schema_matches_builder() {
+        let arg = |name: &str, rust_type: &str| ArgInfo {
+            name: name.to_string(),
+            ty: syn::parse_str(rust_type).unwrap(),
+            rust_type: rust_type.to_string(),
+            arg_type: ArgType::from_rust_type(rust_type),
+            description: String::new(),
+        };
+        let mut ix = described_tool("configure", "Set \"limits\"");
+        ix.args = vec![
+            arg("fee_bps", "u16"),
+            arg("delta", "i64"),
+            arg("flags", "u8"),
+            arg("cap", "u128"),
+            arg("enabled", "bool"),
+            arg("admin", "Pubkey"),
+            arg("label", "String"),
+            arg("memo", "Vec<u8>"),
+            arg("seed", "[u8; 16]"),
+        ];
+
+        let payloads = generate_schema_payloads("widths", "", &[ix], &[], SchemaOverflow::Error, 8).unwrap();
+        let macro_schema = fixed(payloads.compact);
+
+        let builder_schema = McpSchemaBuilder::new("widths")
+            .add_tool(
+                McpToolBuilder::new("configure")
+                    .description("Set \"limits\"")
+                    .account("state", false, true)
+                    .arg("fee_bps", ArgType::U16)
+                    .arg("delta", ArgType::I64)
+                    .arg("flags", ArgType::U8)
+                    .arg("cap", ArgType::U128)
+                    .arg("enabled", ArgType::Bool)
+                    .arg("admin", ArgType::Pubkey)
+                    .arg("label", ArgType::String)
+                    .arg("memo", ArgType::Bytes)
+                    .arg("seed", ArgType::FixedBytes(16))
+                    .build(),
+            )
+            .add_tool(McpToolBuilder::new("list_tools").build())
+            .build();
+
+        assert_eq!(
+            String::from_utf8(macro_schema).unwrap(),
+            mcpsol_core::generate_compact_schema(&builder_schema)
+        );
+    }
+
+    #[test]
+    fn test_errors_published() {
+        let item: syn::ItemEnum = syn::parse_quote! {
+            pub enum VaultError {
+                /// Vault balance too low
+                InsufficientFunds,
+                Frozen = 10,
+                Closed,
+            }
+        };
+        let errors = crate::errors::error_variants(&item).unwrap();
+        let codes: Vec<u32> = errors.iter().map(|e| e.code).collect();
+        assert_eq!(codes, [6000, 6010, 6011]);
+
+        let ix = described_tool("withdraw", "Withdraw");
+        let payloads =
+            generate_schema_payloads("vault", "", &[ix], &errors, SchemaOverflow::Error, 8).unwrap();
+        let builder_schema = McpSchemaBuilder::new("vault")
+            .add_tool(
+                McpToolBuilder::new("withdraw")
+                    .description("Withdraw")
+                    .account("state", false, true)
+                    .build(),
+            )
+            .add_tool(McpToolBuilder::new("list_tools").build())
+            .error_desc(6000, "InsufficientFunds", "Vault balance too low")
+            .error(6010, "Frozen")
+            .error(6011, "Closed")
+            .build();
+        assert_eq!(
+            String::from_utf8(fixed(payloads.compact)).unwrap(),
+            mcpsol_core::generate_compact_schema(&builder_schema)
+        );
+
+        let first = String::from_utf8(fixed(payloads.pages.into_iter().next().unwrap())).unwrap();
+        assert!(first.contains(
+            r#""errors":[{"code":6000,"name":"InsufficientFunds","message":"Vault balance too low"},"#
+        ));
+    }
 }
@@ -5,19 +5,26 @@
 //! - Instruction discriminator routing
 //! - The `list_tools` instruction for MCP schema discovery
 
-use proc_macro2::TokenStream;
-use quote::quote;
+use mcpsol_core::ArgType;
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::{FnArg, Ident, Pat, Type};
 
+use crate::attrs::{doc_summary, ArgAttrs, DispatchHooks, InstructionAttrs};
 use crate::discriminator::instruction_discriminator;
+use crate::errors::{error_variants, ErrorVariant};
+use crate::mcp_gen::{Fallback, Payload, SchemaPart, SchemaPayloads, RETURN_DATA_LIMIT};
+use crate::returns::ReturnInfo;
 
 /// Information about a function argument
 #[derive(Clone)]
 pub struct ArgInfo {
     pub name: String,
+    pub ty: Type,
     pub rust_type: String,
-    pub json_type: String,
-    #[allow(dead_code)] // Reserved for future schema expansion
+    /// Type published in the schema
+    pub arg_type: ArgType,
     pub description: String,
 }
 
@@ -27,11 +34,13 @@ pub struct AccountMeta {
     pub name: String,
     pub is_signer: bool,
     pub is_writable: bool,
-    #[allow(dead_code)] // Reserved for future schema expansion
+    /// The program id may be passed in its place to leave it out
+    pub is_optional: bool,
     pub description: String,
 }
 
 /// Information about a single instruction extracted from the module
+#[derive(Clone)]
 pub struct InstructionInfo {
     pub fn_name: Ident,
     pub tool_name: String,
@@ -39,13 +48,80 @@ pub struct InstructionInfo {
     pub discriminator: [u8; 8],
     pub args: Vec<ArgInfo>,
     pub accounts: Vec<AccountMeta>,
+    /// Span of the `accounts = "..."` string, `None` when it was omitted
+    pub accounts_span: Option<Span>,
+    /// Variadic trailing account list passed as `ctx.remaining_accounts`
+    pub remaining_accounts: Option<AccountMeta>,
     pub accounts_type: Option<String>, // e.g., "Initialize" from Context<Initialize>
     /// Whether to build Context wrapper. Auto-detected from first param or set via `context = true/false`
     pub use_context: bool,
+    /// Whether the handler borrows its Context (`ctx: &Context<..>`)
+    pub ctx_by_ref: bool,
+    /// Deprecated former names, dispatched to this handler as well
+    pub aliases: Vec<String>,
+    /// Opted out of the dispatcher's account checks for no-Context handlers
+    pub unchecked: bool,
+    /// Leading bytes of `discriminator` that tag the instruction data
+    pub discriminator_len: usize,
+    /// Value the handler returns through return data, if any
+    pub returns: Option<ReturnInfo>,
 }
 
-/// Extract instruction info from functions marked with #[mcp_instruction]
-pub fn extract_instructions(items: &[syn::Item]) -> Vec<InstructionInfo> {
+impl InstructionInfo {
+    /// Whether the account list comes from the `#[derive(Accounts)]` metadata
+    /// of the Context type rather than an `accounts` string.
+    pub const fn infers_accounts(&self) -> bool {
+        self.accounts_span.is_none() && self.derives_accounts()
+    }
+
+    /// Whether the Context type provides `AccountsMeta` from `#[derive(Accounts)]`.
+    pub const fn derives_accounts(&self) -> bool {
+        self.use_context && self.accounts_type.is_some()
+    }
+
+    /// The bytes this instruction's data starts with
+    pub fn tag(&self) -> &[u8] {
+        &self.discriminator[..self.discriminator_len]
+    }
+
+    /// Whether the return layout comes from `McpReturn::LAYOUT` rather than
+    /// being known while the macro expands.
+    pub fn splices_returns(&self) -> bool {
+        self.returns.as_ref().is_some_and(|ret| ret.layout.is_none())
+    }
+}
+
+/// Collect the variants of `#[mcp_error]` enums declared in the module.
+///
+/// Codes must be unique across enums, since clients look errors up by code.
+pub fn extract_errors(items: &[syn::Item]) -> syn::Result<Vec<ErrorVariant>> {
+    let mut all: Vec<ErrorVariant> = Vec::new();
+    for item in items {
+        let syn::Item::Enum(item) = item else {
+            continue;
+        };
+        if !item.attrs.iter().any(|a| a.path().is_ident("mcp_error")) {
+            continue;
+        }
+        for variant in error_variants(item)? {
+            if let Some(prev) = all.iter().find(|v| v.code == variant.code) {
+                return Err(syn::Error::new_spanned(
+                    &variant.ident,
+                    format!("error code {} is already used by `{}`", variant.code, prev.ident),
+                ));
+            }
+            all.push(variant);
+        }
+    }
+    Ok(all)
+}
+
+/// Extract instruction info from functions marked with #[mcp_instruction],
+/// tagged with the first `discriminator_len` bytes of their discriminators
+pub fn extract_instructions(
+    items: &[syn::Item],
+    discriminator_len: usize,
+) -> syn::Result<Vec<InstructionInfo>> {
     let mut instructions = Vec::new();
 
     for item in items {
@@ -54,25 +130,26 @@ pub fn extract_instructions(items: &[syn::Item]) -> Vec<InstructionInfo> {
             for attr in &func.attrs {
                 if attr.path().is_ident("mcp_instruction") {
                     let fn_name = func.sig.ident.clone();
+                    let attrs = InstructionAttrs::from_attr(attr)?;
 
-                    // Parse attribute to get name and description
-                    // Convert the entire attribute meta to string for parsing
-                    let attr_str = match &attr.meta {
-                        syn::Meta::List(list) => list.tokens.to_string(),
-                        syn::Meta::NameValue(nv) => quote::quote!(#nv).to_string(),
-                        syn::Meta::Path(_) => String::new(),
-                    };
-
-                    let tool_name = extract_attr_value(&attr_str, "name")
-                        .unwrap_or_else(|| fn_name.to_string());
-                    let tool_desc = extract_attr_value(&attr_str, "description")
+                    let tool_name = attrs.name.unwrap_or_else(|| fn_name.to_string());
+                    let tool_desc = attrs
+                        .description
+                        .or_else(|| doc_summary(&func.attrs))
                         .unwrap_or_default();
-                    let accounts_str = extract_attr_value(&attr_str, "accounts")
-                        .unwrap_or_default();
-                    let accounts = parse_accounts_attr(&accounts_str);
+                    let accounts = attrs.accounts.unwrap_or_default();
+                    let accounts_span = attrs.accounts_span;
+                    let remaining_accounts = attrs.remaining_accounts;
+                    let aliases = attrs.aliases.unwrap_or_default();
+                    if aliases.contains(&tool_name) {
+                        return Err(syn::Error::new_spanned(
+                            attr,
+                            format!("`{}` cannot be an alias of itself", tool_name),
+                        ));
+                    }
 
-                    // Parse explicit context = true/false attribute
-                    let explicit_context = extract_attr_value(&attr_str, "context");
+                    // Explicit context = true/false attribute
+                    let explicit_context = attrs.context;
 
                     let discriminator = instruction_discriminator(&tool_name);
 
@@ -80,6 +157,7 @@ pub fn extract_instructions(items: &[syn::Item]) -> Vec<InstructionInfo> {
                     let mut args = Vec::new();
                     let mut accounts_type = None;
                     let mut detected_context = false;
+                    let mut ctx_by_ref = false;
 
                     for (idx, input) in func.sig.inputs.iter().enumerate() {
                         if let FnArg::Typed(pat_type) = input {
@@ -89,6 +167,7 @@ pub fn extract_instructions(items: &[syn::Item]) -> Vec<InstructionInfo> {
                                 // If we found a Context type, this instruction uses context
                                 if accounts_type.is_some() {
                                     detected_context = true;
+                                    ctx_by_ref = matches!(&*pat_type.ty, Type::Reference(_));
                                     continue; // Skip ctx in args
                                 }
                                 // Not a Context - include as arg (for no-Context signatures)
@@ -103,7 +182,6 @@ pub fn extract_instructions(items: &[syn::Item]) -> Vec<InstructionInfo> {
 
                             // Get argument type
                             let rust_type = type_to_string(&pat_type.ty);
-                            let json_type = rust_type_to_json_schema(&rust_type);
 
                             // Skip program_id and accounts slice for no-Context handlers
                             // They have signatures like: fn(program_id: &Pubkey, accounts: &[AccountInfo], ...)
@@ -114,11 +192,34 @@ pub fn extract_instructions(items: &[syn::Item]) -> Vec<InstructionInfo> {
                                 continue; // Skip accounts slice
                             }
 
+                            // The schema publishes arrays as `[u8;N]`, read without a prefix
+                            if matches!(&*pat_type.ty, Type::Array(_)) && get_type_size(&rust_type).is_none() {
+                                return Err(syn::Error::new_spanned(
+                                    &pat_type.ty,
+                                    "array arguments must be `[u8; N]` with a literal length",
+                                ));
+                            }
+
+                            let arg_attrs = ArgAttrs::from_attrs(&pat_type.attrs)?;
+                            let arg_type = match arg_attrs.schema_type {
+                                Some(arg_type) => arg_type,
+                                None => schema_arg_type(&rust_type).ok_or_else(|| {
+                                    syn::Error::new_spanned(
+                                        &pat_type.ty,
+                                        format!(
+                                            "`{}` has no schema type; name the one it is encoded as \
+                                             with #[mcp_arg(schema_type = \"...\")]",
+                                            rust_type
+                                        ),
+                                    )
+                                })?,
+                            };
                             args.push(ArgInfo {
                                 name: arg_name,
+                                ty: (*pat_type.ty).clone(),
                                 rust_type,
-                                json_type,
-                                description: String::new(),
+                                arg_type,
+                                description: arg_attrs.description.unwrap_or_default(),
                             });
                         }
                     }
@@ -127,11 +228,14 @@ pub fn extract_instructions(items: &[syn::Item]) -> Vec<InstructionInfo> {
                     // 1. Explicit `context = true` forces Context
                     // 2. Explicit `context = false` forces no Context
                     // 3. Otherwise, auto-detect from first parameter
-                    let use_context = match explicit_context.as_deref() {
-                        Some("true") => true,
-                        Some("false") => false,
-                        _ => detected_context, // Auto-detect
-                    };
+                    let use_context = explicit_context.unwrap_or(detected_context);
+                    if use_context && attrs.unchecked {
+                        return Err(syn::Error::new_spanned(
+                            attr,
+                            "`unchecked` only applies to handlers without a Context, \
+                             whose accounts are checked by #[derive(Accounts)] instead",
+                        ));
+                    }
 
                     instructions.push(InstructionInfo {
                         fn_name,
@@ -140,15 +244,76 @@ pub fn extract_instructions(items: &[syn::Item]) -> Vec<InstructionInfo> {
                         discriminator,
                         args,
                         accounts,
+                        accounts_span,
+                        remaining_accounts,
                         accounts_type,
                         use_context,
+                        ctx_by_ref,
+                        aliases,
+                        unchecked: attrs.unchecked,
+                        discriminator_len,
+                        returns: ReturnInfo::from_output(&func.sig.output),
                     });
                 }
             }
         }
     }
 
-    instructions
+    check_dispatch_keys(&instructions)?;
+    Ok(instructions)
+}
+
+/// Instructions the dispatcher handles itself, ahead of any tool
+const RESERVED_TOOLS: &[&str] = &["list_tools"];
+
+/// Reject names the dispatcher could not route unambiguously: a tool name
+/// used twice, two names whose discriminators collide, or a clash with a
+/// reserved instruction. Aliases count as names, and discriminators are
+/// compared over the bytes that tag instruction data.
+fn check_dispatch_keys(instructions: &[InstructionInfo]) -> syn::Result<()> {
+    let mut seen: Vec<(&str, [u8; 8], &Ident)> = Vec::new();
+    for ix in instructions {
+        let len = ix.discriminator_len;
+        let aliases = ix.aliases.iter().map(|a| (a.as_str(), instruction_discriminator(a)));
+        for (name, disc) in core::iter::once((ix.tool_name.as_str(), ix.discriminator)).chain(aliases) {
+            if let Some(reserved) = RESERVED_TOOLS
+                .iter()
+                .find(|r| **r == name || instruction_discriminator(r)[..len] == disc[..len])
+            {
+                return Err(syn::Error::new_spanned(
+                    &ix.fn_name,
+                    format!(
+                        "tool `{}` of `{}` clashes with the built-in `{}` instruction",
+                        name, ix.fn_name, reserved
+                    ),
+                ));
+            }
+            if let Some((prev_name, _, prev_fn)) = seen.iter().find(|(_, d, _)| d[..len] == disc[..len]) {
+                let msg = if *prev_name == name {
+                    format!("tool name `{}` is used by both `{}` and `{}`", name, prev_fn, ix.fn_name)
+                } else if len < 8 {
+                    format!(
+                        "first {} discriminator bytes of `{}` in `{}` collide with `{}` in `{}`; \
+                         use a longer discriminator_len",
+                        len, name, ix.fn_name, prev_name, prev_fn
+                    )
+                } else {
+                    format!(
+                        "discriminator of `{}` in `{}` collides with `{}` in `{}`",
+                        name, ix.fn_name, prev_name, prev_fn
+                    )
+                };
+                let mut err = syn::Error::new_spanned(&ix.fn_name, msg);
+                err.combine(syn::Error::new_spanned(
+                    prev_fn,
+                    format!("`{}` is first declared by `{}` here", prev_name, prev_fn),
+                ));
+                return Err(err);
+            }
+            seen.push((name, disc, &ix.fn_name));
+        }
+    }
+    Ok(())
 }
 
 /// Extract the accounts type from Context<'info, AccountsType<'info>>
@@ -178,28 +343,6 @@ fn type_to_string(ty: &Type) -> String {
     quote!(#ty).to_string().replace(" ", "")
 }
 
-/// Map Rust types to JSON Schema type objects
-fn rust_type_to_json_schema(rust_type: &str) -> String {
-    match rust_type {
-        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" =>
-            r#"{"type":"integer","minimum":0}"#.to_string(),
-        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" =>
-            r#"{"type":"integer"}"#.to_string(),
-        "bool" =>
-            r#"{"type":"boolean"}"#.to_string(),
-        "String" =>
-            r#"{"type":"string"}"#.to_string(),
-        t if t.starts_with("Pubkey") || t.contains("Pubkey") =>
-            r#"{"type":"string","format":"solana-pubkey","description":"Base58-encoded 32-byte public key"}"#.to_string(),
-        t if t.starts_with("[u8;") =>
-            r#"{"type":"string","contentEncoding":"base64"}"#.to_string(),
-        t if t.starts_with("Vec<u8>") =>
-            r#"{"type":"string","contentEncoding":"base64"}"#.to_string(),
-        _ =>
-            r#"{"type":"string"}"#.to_string(),
-    }
-}
-
 /// Get the byte size of a Rust type for compile-time offset calculation.
 ///
 /// Returns `Some(size)` for known fixed-size types, `None` for variable-size types.
@@ -211,7 +354,7 @@ fn get_type_size(rust_type: &str) -> Option<usize> {
         "u32" | "i32" => Some(4),
         "u64" | "i64" => Some(8),
         "u128" | "i128" => Some(16),
-        t if t.starts_with("Pubkey") || t.contains("Pubkey") => Some(32),
+        t if is_pubkey(t) => Some(32),
         // Parse [u8; N] patterns
         t if t.starts_with("[u8;") => {
             // Extract N from "[u8;N]"
@@ -226,12 +369,24 @@ fn get_type_size(rust_type: &str) -> Option<usize> {
     }
 }
 
+/// Schema type of a Rust type the schema can describe, `None` for user types.
+fn schema_arg_type(rust_type: &str) -> Option<ArgType> {
+    let arg_type = ArgType::from_rust_type(rust_type);
+    // `from_rust_type` reads anything it does not know as a string
+    (arg_type != ArgType::String || rust_type == "String").then_some(arg_type)
+}
+
+/// Whether a type string names `Pubkey`, bare or path-qualified.
+fn is_pubkey(rust_type: &str) -> bool {
+    rust_type == "Pubkey" || rust_type.ends_with("::Pubkey")
+}
+
 /// Calculate the total expected instruction data length for compile-time validation.
 ///
 /// Returns `Some(len)` if all arguments have known fixed sizes, `None` otherwise.
-/// The returned length includes the 8-byte discriminator.
-fn calculate_expected_len(args: &[ArgInfo]) -> Option<usize> {
-    let mut total: usize = 8; // discriminator
+/// The returned length includes the `discriminator_len`-byte discriminator.
+fn calculate_expected_len(args: &[ArgInfo], discriminator_len: usize) -> Option<usize> {
+    let mut total: usize = discriminator_len;
     for arg in args {
         match get_type_size(&arg.rust_type) {
             Some(size) => total += size,
@@ -245,9 +400,9 @@ fn calculate_expected_len(args: &[ArgInfo]) -> Option<usize> {
 ///
 /// Returns `Some(offsets)` where offsets[i] is the byte offset for arg[i],
 /// or `None` if any argument has variable size.
-fn calculate_arg_offsets(args: &[ArgInfo]) -> Option<Vec<usize>> {
+fn calculate_arg_offsets(args: &[ArgInfo], discriminator_len: usize) -> Option<Vec<usize>> {
     let mut offsets = Vec::with_capacity(args.len());
-    let mut offset: usize = 8; // Start after discriminator
+    let mut offset: usize = discriminator_len; // Start after discriminator
 
     for arg in args {
         offsets.push(offset);
@@ -259,205 +414,442 @@ fn calculate_arg_offsets(args: &[ArgInfo]) -> Option<Vec<usize>> {
     Some(offsets)
 }
 
+/// How the generated dispatcher finds the handler for a discriminator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DispatchStrategy {
+    /// A `match` on the u64 discriminator
+    Match,
+    /// Binary search over the sorted discriminators
+    BinarySearch,
+    /// A 256-entry table indexed by the one discriminator byte that differs
+    /// across all routes, then a single full compare
+    ByteHash,
+}
+
+impl DispatchStrategy {
+    /// Parse the `dispatch` attribute value.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "match" => Some(Self::Match),
+            "binary_search" => Some(Self::BinarySearch),
+            "byte_hash" => Some(Self::ByteHash),
+            _ => None,
+        }
+    }
+}
+
+/// Largest number of routed discriminators, `list_tools` included, that
+/// dispatch through a plain `match` unless another strategy is requested
+const MATCH_ROUTES: usize = 8;
+
+/// Index of the first discriminator byte whose value differs across all
+/// `keys`, which makes it a perfect hash of them.
+fn distinct_byte(keys: &[u64]) -> Option<usize> {
+    if keys.len() > 255 {
+        return None;
+    }
+    (0..8).find(|&byte| {
+        let mut seen = [false; 256];
+        keys.iter()
+            .all(|key| !std::mem::replace(&mut seen[(key >> (8 * byte)) as u8 as usize], true))
+    })
+}
+
+/// Pick the dispatch strategy for `keys`: the requested one, or by count a
+/// `match` for small programs and a byte hash, or failing that a binary
+/// search, for large ones.
+fn choose_strategy(requested: Option<DispatchStrategy>, keys: &[u64]) -> Result<DispatchStrategy, String> {
+    match requested {
+        Some(DispatchStrategy::ByteHash) if distinct_byte(keys).is_none() => Err(format!(
+            "dispatch = \"byte_hash\" needs a discriminator byte that differs across all {} \
+             routes, and none does; use dispatch = \"binary_search\"",
+            keys.len()
+        )),
+        Some(strategy) => Ok(strategy),
+        None if keys.len() <= MATCH_ROUTES => Ok(DispatchStrategy::Match),
+        None if distinct_byte(keys).is_some() => Ok(DispatchStrategy::ByteHash),
+        None => Ok(DispatchStrategy::BinarySearch),
+    }
+}
+
 /// Generate the instruction dispatcher (process_instruction function)
 ///
+/// It is emitted inside the program module as a public function, so other
+/// programs and host-side tests can call it in-process.
+///
+/// `hooks` wrap every tool except the built-in `list_tools`, and the fallback
+/// receives any instruction data no tool claims, including data shorter
+/// than a discriminator.
+///
+/// Each tool's body becomes an inlined handler function, and `dispatch`
+/// decides how a discriminator reaches it (see [`DispatchStrategy`]).
+///
+/// Tools are tagged with the first `discriminator_len` discriminator bytes,
+/// read as one integer. `list_tools` always answers to its full 8 bytes,
+/// checked ahead of the tools when their tags are shorter.
+///
 /// This generates an optimized dispatcher with:
-/// - Single upfront bounds check for discriminator (8 bytes minimum)
+/// - Single upfront bounds check for discriminator
 /// - Unsafe direct discriminator read (~5 CU vs ~50 CU)
 /// - Per-instruction bounds check using compile-time EXPECTED_LEN
 /// - Unsafe argument reads at compile-time offsets (~5 CU vs ~70 CU per arg)
 pub fn generate_dispatcher(
-    mod_name: &Ident,
     instructions: &[InstructionInfo],
-) -> TokenStream {
-    let mut match_arms = Vec::new();
+    hooks: &DispatchHooks,
+    dispatch: Option<DispatchStrategy>,
+    discriminator_len: usize,
+) -> Result<TokenStream, String> {
+    let list_tools_handler = format_ident!("__mcpsol_list_tools");
+    let list_tools_u64 = u64::from_le_bytes(instruction_discriminator("list_tools"));
+    let full = discriminator_len == 8;
+    // The tag as the dispatcher reads it: leading bytes, little-endian
+    let tag_u64 = |discriminator: [u8; 8]| {
+        let mut bytes = [0u8; 8];
+        bytes[..discriminator_len].copy_from_slice(&discriminator[..discriminator_len]);
+        u64::from_le_bytes(bytes)
+    };
 
+    // Every discriminator the dispatcher accepts, with its handler;
+    // deprecated names route to the handler of their replacement
+    let mut routes = Vec::new();
+    if full {
+        routes.push((list_tools_u64, list_tools_handler.clone()));
+    }
+    let mut handlers = Vec::new();
     for ix in instructions {
-        // Convert [u8; 8] discriminator to u64 for faster matching (single instruction comparison)
-        let disc_u64 = u64::from_le_bytes(ix.discriminator);
-        let fn_name = &ix.fn_name;
+        let handler = format_ident!("__mcpsol_ix_{}", ix.fn_name);
+        routes.push((tag_u64(ix.discriminator), handler.clone()));
+        for alias in &ix.aliases {
+            routes.push((tag_u64(instruction_discriminator(alias)), handler.clone()));
+        }
+
+        let body = generate_tool_body(ix, hooks);
+        handlers.push(quote! {
+            #[doc(hidden)]
+            #[inline(always)]
+            #[allow(unused_variables)]
+            fn #handler(
+                program_id: &pinocchio::pubkey::Pubkey,
+                accounts: &[pinocchio::account_info::AccountInfo],
+                instruction_data: &[u8],
+            ) -> pinocchio::ProgramResult {
+                #body
+            }
+        });
+    }
 
-        // Generate optimized argument parsing code
-        let (arg_parsing, arg_names) = generate_arg_parsing_optimized(&ix.args);
+    let unmatched = match hooks.fallback {
+        Some(ref fallback) => quote! { #fallback(program_id, accounts, instruction_data) },
+        None => quote! { Err(mcpsol::pinocchio::program_error::ProgramError::InvalidInstructionData) },
+    };
 
-        // Build the context only if use_context is true
-        let ctx_building = if ix.use_context {
-            if let Some(ref accounts_type) = ix.accounts_type {
-                let accounts_ty = Ident::new(accounts_type, fn_name.span());
-                quote! {
-                    let ctx = mcpsol::context::Context::new(
-                        program_id,
-                        <#accounts_ty as mcpsol::context::Accounts>::try_accounts(program_id, accounts)?,
-                        &[]  // remaining_accounts
-                    );
+    // Short tags are read at their own width; list_tools first needs all 8 bytes
+    let read_tag = match discriminator_len {
+        1 => quote! { u64::from(*instruction_data.as_ptr()) },
+        2 => quote! { u64::from(core::ptr::read_unaligned(instruction_data.as_ptr() as *const u16)) },
+        4 => quote! { u64::from(core::ptr::read_unaligned(instruction_data.as_ptr() as *const u32)) },
+        _ => quote! { core::ptr::read_unaligned(instruction_data.as_ptr() as *const u64) },
+    };
+    let list_tools_check = (!full).then(|| {
+        quote! {
+            // list_tools keeps its full discriminator
+            if instruction_data.len() >= 8
+                // SAFETY: Length >= 8 verified first
+                && unsafe { core::ptr::read_unaligned(instruction_data.as_ptr() as *const u64) }
+                    == #list_tools_u64
+            {
+                return self::#list_tools_handler(program_id, accounts, instruction_data);
+            }
+        }
+    });
+
+    let keys: Vec<u64> = routes.iter().map(|(key, _)| *key).collect();
+    let routing = match choose_strategy(dispatch, &keys)? {
+        DispatchStrategy::Match => {
+            let arms = routes.iter().map(|(key, handler)| {
+                quote! { #key => self::#handler(program_id, accounts, instruction_data), }
+            });
+            quote! {
+                match discriminator {
+                    #(#arms)*
+                    _ => #unmatched,
                 }
-            } else {
-                // use_context = true but no accounts type detected - still build minimal context
-                quote! {}
             }
-        } else {
-            // No context wrapper - maximum performance path (~30 CU total)
-            quote! {}
-        };
-
-        // Generate the function call
-        let fn_call = if ix.use_context {
-            // With Context - pass ctx as first arg
-            if arg_names.is_empty() {
-                quote! { #mod_name::#fn_name(ctx)? }
-            } else {
-                quote! { #mod_name::#fn_name(ctx, #(#arg_names),*)? }
+        }
+        DispatchStrategy::BinarySearch => {
+            routes.sort_by_key(|(key, _)| *key);
+            let table = route_table(&routes);
+            quote! {
+                #table
+                match ROUTES.binary_search_by_key(&discriminator, |route| route.0) {
+                    Ok(idx) => (ROUTES[idx].1)(program_id, accounts, instruction_data),
+                    Err(_) => #unmatched,
+                }
             }
-        } else {
-            // Without Context - pass (program_id, accounts, args...)
-            // Handler signature: fn(program_id: &Pubkey, accounts: &[AccountInfo], ...args)
-            if arg_names.is_empty() {
-                quote! { #mod_name::#fn_name(program_id, accounts)? }
-            } else {
-                quote! { #mod_name::#fn_name(program_id, accounts, #(#arg_names),*)? }
+        }
+        DispatchStrategy::ByteHash => {
+            let byte = distinct_byte(&keys).unwrap_or_default();
+            let mut slots = [0u8; 256];
+            for (idx, key) in keys.iter().enumerate() {
+                // Routes are fewer than 256, checked by distinct_byte
+                slots[(key >> (8 * byte)) as u8 as usize] = idx as u8 + 1;
             }
-        };
-
-        let arm = quote! {
-            #disc_u64 => {
-                #arg_parsing
-                #ctx_building
-                #fn_call;
-                Ok(())
+            let table = route_table(&routes);
+            let shift = 8 * byte as u32;
+            quote! {
+                #table
+                // Slot of each value of the hashed byte: 0 when no route
+                // has it, otherwise the ROUTES index plus one
+                const SLOTS: [u8; 256] = [#(#slots),*];
+                match SLOTS[(discriminator >> #shift) as u8 as usize] {
+                    0 => #unmatched,
+                    slot => {
+                        let (key, handler) = ROUTES[slot as usize - 1];
+                        if key == discriminator {
+                            handler(program_id, accounts, instruction_data)
+                        } else {
+                            #unmatched
+                        }
+                    }
+                }
             }
-        };
-        match_arms.push(arm);
-    }
-
-    // Add list_tools discriminator as u64
-    let list_tools_disc = instruction_discriminator("list_tools");
-    let list_tools_u64 = u64::from_le_bytes(list_tools_disc);
+        }
+    };
 
-    quote! {
+    Ok(quote! {
         /// Process incoming instructions (optimized: ~30 CU framework overhead)
-        pub fn __mcpsol_process_instruction(
+        ///
+        /// This is the program's entrypoint handler; call it directly to run
+        /// an instruction in-process.
+        pub fn process_instruction(
             program_id: &pinocchio::pubkey::Pubkey,
             accounts: &[pinocchio::account_info::AccountInfo],
             instruction_data: &[u8],
         ) -> pinocchio::ProgramResult {
+            #list_tools_check
+
             // Single bounds check for discriminator
-            if instruction_data.len() < 8 {
-                return Err(mcpsol::pinocchio::program_error::ProgramError::InvalidInstructionData);
+            if instruction_data.len() < #discriminator_len {
+                return #unmatched;
             }
 
-            // SAFETY: Length >= 8 verified above
-            // Optimization: Read as u64 for single-instruction comparison (~5 CU)
-            let discriminator = unsafe {
-                core::ptr::read_unaligned(instruction_data.as_ptr() as *const u64)
+            // SAFETY: Length >= discriminator_len verified above
+            // Optimization: Read as one integer for single-instruction comparison (~5 CU)
+            let discriminator = unsafe { #read_tag };
+
+            #routing
+        }
+
+        /// Built-in list_tools instruction: compact schema by default, a
+        /// cursor byte selects a verbose page with full descriptions
+        #[doc(hidden)]
+        #[inline(always)]
+        #[allow(unused_variables)]
+        fn #list_tools_handler(
+            program_id: &pinocchio::pubkey::Pubkey,
+            accounts: &[pinocchio::account_info::AccountInfo],
+            instruction_data: &[u8],
+        ) -> pinocchio::ProgramResult {
+            let schema = match instruction_data.get(8) {
+                Some(&cursor) => self::MCP_SCHEMA_PAGES
+                    .get(cursor as usize)
+                    .copied()
+                    .unwrap_or(&[]),
+                None => self::MCP_SCHEMA_BYTES,
             };
+            pinocchio::program::set_return_data(schema);
+            Ok(())
+        }
 
-            match discriminator {
-                // Built-in list_tools instruction
-                #list_tools_u64 => {
-                    pinocchio::program::set_return_data(#mod_name::MCP_SCHEMA_BYTES);
-                    Ok(())
-                }
-                // User-defined instructions
-                #(#match_arms)*
-                _ => Err(mcpsol::pinocchio::program_error::ProgramError::InvalidInstructionData),
+        // User-defined instructions
+        #(#handlers)*
+    })
+}
+
+/// Generate the `ROUTES` table of discriminators and handlers, in `routes` order.
+fn route_table(routes: &[(u64, Ident)]) -> TokenStream {
+    let len = routes.len();
+    let entries = routes.iter().map(|(key, handler)| quote! { (#key, self::#handler) });
+    quote! {
+        const ROUTES: [(
+            u64,
+            fn(
+                &pinocchio::pubkey::Pubkey,
+                &[pinocchio::account_info::AccountInfo],
+                &[u8],
+            ) -> pinocchio::ProgramResult,
+        ); #len] = [#(#entries),*];
+    }
+}
+
+/// Generate the body of one tool's handler: hooks, account checks, argument
+/// decoding, the call and deferred account work.
+fn generate_tool_body(ix: &InstructionInfo, hooks: &DispatchHooks) -> TokenStream {
+    let fn_name = &ix.fn_name;
+
+    // Generate optimized argument parsing code
+    let (arg_parsing, arg_names) = generate_arg_parsing_optimized(&ix.args, ix.discriminator_len);
+
+    // Build the context only if use_context is true
+    let ctx_building = if ix.use_context {
+        if let Some(ref accounts_type) = ix.accounts_type {
+            let accounts_ty = Ident::new(accounts_type, fn_name.span());
+            quote! {
+                let ctx = mcpsol::context::Context::new(
+                    program_id,
+                    <#accounts_ty as mcpsol::context::Accounts>::try_accounts(program_id, accounts)?,
+                    // Accounts past those the struct consumed
+                    accounts
+                        .get(<#accounts_ty as mcpsol::context::Accounts>::LEN..)
+                        .unwrap_or(&[]),
+                );
             }
+        } else {
+            // use_context = true but no accounts type detected - still build minimal context
+            quote! {}
+        }
+    } else {
+        // No context wrapper - maximum performance path (~30 CU total)
+        quote! {}
+    };
+
+    // Generate the function call
+    let fn_call = if ix.use_context {
+        // With Context - pass ctx as first arg, borrowed if the handler only reads it
+        let ctx = if ix.ctx_by_ref { quote! { &ctx } } else { quote! { ctx } };
+        if arg_names.is_empty() {
+            quote! { self::#fn_name(#ctx)? }
+        } else {
+            quote! { self::#fn_name(#ctx, #(#arg_names),*)? }
+        }
+    } else {
+        // Without Context - pass (program_id, accounts, args...)
+        // Handler signature: fn(program_id: &Pubkey, accounts: &[AccountInfo], ...args)
+        if arg_names.is_empty() {
+            quote! { self::#fn_name(program_id, accounts)? }
+        } else {
+            quote! { self::#fn_name(program_id, accounts, #(#arg_names),*)? }
         }
+    };
+
+    // Deferred account work such as `close` runs once the handler succeeds
+    let exit = match (&ix.accounts_type, ix.use_context) {
+        (Some(accounts_type), true) => {
+            let accounts_ty = Ident::new(accounts_type, fn_name.span());
+            quote! { <#accounts_ty as mcpsol::context::Accounts>::exit(program_id, accounts)?; }
+        }
+        _ => quote! {},
+    };
+
+    let tool_name = &ix.tool_name;
+    let before = hooks.before.as_ref().map(|before| quote! { #before(#tool_name, accounts)?; });
+    let after = hooks.after.as_ref().map(|after| quote! { #after(#tool_name, accounts)?; });
+
+    let account_checks = if ix.use_context || ix.unchecked {
+        quote! {}
+    } else {
+        generate_account_checks(&ix.accounts)
+    };
+
+    // Returned values reach return data only once every step succeeded
+    let (binding, set_return) = match ix.returns {
+        Some(_) => (
+            quote! { let __mcpsol_return = },
+            quote! {
+                mcpsol::returns::McpReturn::with_bytes(
+                    &__mcpsol_return,
+                    pinocchio::program::set_return_data,
+                );
+            },
+        ),
+        None => (quote! {}, quote! {}),
+    };
+
+    quote! {
+        #before
+        #account_checks
+        #arg_parsing
+        #ctx_building
+        #binding #fn_call;
+        #exit
+        #after
+        #set_return
+        Ok(())
     }
 }
 
-/// Generate code to parse instruction arguments from data bytes
-fn generate_arg_parsing(args: &[ArgInfo]) -> (TokenStream, Vec<Ident>) {
-    if args.is_empty() {
-        return (quote! { let _ = data; }, vec![]);
+/// Generate the checks a no-Context handler gets from its `accounts` string:
+/// enough accounts, then each declared signer and writable flag.
+///
+/// One length comparison, then a flag test per flagged account.
+fn generate_account_checks(accounts: &[AccountMeta]) -> TokenStream {
+    if accounts.is_empty() {
+        return quote! {};
     }
+    let count = accounts.len();
+    let checks = accounts.iter().enumerate().map(|(idx, acc)| {
+        let signer = acc.is_signer.then(|| quote! {
+            if !account.is_signer() {
+                return Err(mcpsol::error::McpSolError::MissingSigner.into());
+            }
+        });
+        let writable = acc.is_writable.then(|| quote! {
+            if !account.is_writable() {
+                return Err(mcpsol::error::McpSolError::NotWritable.into());
+            }
+        });
+        if signer.is_none() && writable.is_none() {
+            return quote! {};
+        }
+        // An optional account left out by passing the program id is not checked
+        let present = if acc.is_optional {
+            quote! { account.key() != program_id }
+        } else {
+            quote! { true }
+        };
+        quote! {{
+            // SAFETY: the length was checked above
+            let account = unsafe { accounts.get_unchecked(#idx) };
+            if #present {
+                #signer
+                #writable
+            }
+        }}
+    });
 
+    quote! {
+        if accounts.len() < #count {
+            return Err(mcpsol::error::McpSolError::MissingAccount.into());
+        }
+        #(#checks)*
+    }
+}
+
+/// Generate code to decode instruction arguments in order through `McpArgDecode`.
+///
+/// Used when any argument has a variable or unknown size. Each type must
+/// implement `mcpsol::args::McpArgDecode`; the trait bound error is spanned
+/// at the argument's type so unsupported types fail to compile.
+fn generate_arg_parsing(args: &[ArgInfo], discriminator_len: usize) -> (TokenStream, Vec<Ident>) {
     let mut parsing_code = Vec::new();
     let mut arg_names = Vec::new();
-    let offset_code = quote! { let mut __offset: usize = 0; };
 
     for arg in args {
-        let arg_name = Ident::new(&arg.name, proc_macro2::Span::call_site());
+        let arg_name = Ident::new(&arg.name, Span::call_site());
         arg_names.push(arg_name.clone());
 
-        let parse_expr = match arg.rust_type.as_str() {
-            "u8" => quote! {
-                let #arg_name: u8 = data.get(__offset)
-                    .copied()
-                    .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?;
-                __offset += 1;
-            },
-            "u16" => quote! {
-                let #arg_name: u16 = u16::from_le_bytes(
-                    data.get(__offset..__offset + 2)
-                        .and_then(|s| s.try_into().ok())
-                        .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?
-                );
-                __offset += 2;
-            },
-            "u32" => quote! {
-                let #arg_name: u32 = u32::from_le_bytes(
-                    data.get(__offset..__offset + 4)
-                        .and_then(|s| s.try_into().ok())
-                        .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?
-                );
-                __offset += 4;
-            },
-            "u64" => quote! {
-                let #arg_name: u64 = u64::from_le_bytes(
-                    data.get(__offset..__offset + 8)
-                        .and_then(|s| s.try_into().ok())
-                        .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?
-                );
-                __offset += 8;
-            },
-            "i8" => quote! {
-                let #arg_name: i8 = data.get(__offset)
-                    .map(|&b| b as i8)
-                    .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?;
-                __offset += 1;
-            },
-            "i16" => quote! {
-                let #arg_name: i16 = i16::from_le_bytes(
-                    data.get(__offset..__offset + 2)
-                        .and_then(|s| s.try_into().ok())
-                        .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?
-                );
-                __offset += 2;
-            },
-            "i32" => quote! {
-                let #arg_name: i32 = i32::from_le_bytes(
-                    data.get(__offset..__offset + 4)
-                        .and_then(|s| s.try_into().ok())
-                        .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?
-                );
-                __offset += 4;
-            },
-            "i64" => quote! {
-                let #arg_name: i64 = i64::from_le_bytes(
-                    data.get(__offset..__offset + 8)
-                        .and_then(|s| s.try_into().ok())
-                        .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?
-                );
-                __offset += 8;
-            },
-            "bool" => quote! {
-                let #arg_name: bool = data.get(__offset)
-                    .map(|&b| b != 0)
-                    .ok_or(pinocchio::program_error::ProgramError::InvalidInstructionData)?;
-                __offset += 1;
-            },
-            // Default: try to read as raw bytes (for types we don't recognize)
-            _ => quote! {
-                // Unknown type - skip parsing, caller must handle
-                let #arg_name = ();
-            },
-        };
-
-        parsing_code.push(parse_expr);
+        let ty = &arg.ty;
+        parsing_code.push(quote_spanned! {ty.span()=>
+            let #arg_name: #ty = <#ty as mcpsol::args::McpArgDecode>::decode(
+                instruction_data,
+                &mut __offset,
+            )?;
+        });
     }
 
     let combined = quote! {
-        #offset_code
+        // Arguments follow the discriminator
+        let mut __offset: usize = #discriminator_len;
         #(#parsing_code)*
     };
 
@@ -471,26 +863,23 @@ fn generate_arg_parsing(args: &[ArgInfo]) -> (TokenStream, Vec<Ident>) {
 /// - Single bounds check with EXPECTED_LEN const
 /// - Unsafe direct reads with SAFETY comments
 /// - debug_assert! for extra verification in debug builds
-fn generate_arg_parsing_optimized(args: &[ArgInfo]) -> (TokenStream, Vec<Ident>) {
+fn generate_arg_parsing_optimized(args: &[ArgInfo], discriminator_len: usize) -> (TokenStream, Vec<Ident>) {
     if args.is_empty() {
         return (quote! {}, vec![]);
     }
 
     // Try to calculate compile-time offsets
-    let offsets = match calculate_arg_offsets(args) {
+    let offsets = match calculate_arg_offsets(args, discriminator_len) {
         Some(offsets) => offsets,
         None => {
-            // Fall back to legacy parsing for variable-size args
-            return generate_arg_parsing(args);
+            // Variable-size args are decoded sequentially
+            return generate_arg_parsing(args, discriminator_len);
         }
     };
 
-    let expected_len = match calculate_expected_len(args) {
+    let expected_len = match calculate_expected_len(args, discriminator_len) {
         Some(len) => len,
-        None => {
-            // Fall back to legacy parsing
-            return generate_arg_parsing(args);
-        }
+        None => return generate_arg_parsing(args, discriminator_len),
     };
 
     let mut parsing_code = Vec::new();
@@ -572,11 +961,39 @@ fn generate_arg_parsing_optimized(args: &[ArgInfo]) -> (TokenStream, Vec<Ident>)
             "bool" => quote! {
                 // SAFETY: instruction_data.len() >= __EXPECTED_LEN checked above
                 debug_assert!(#offset < instruction_data.len());
-                let #arg_name: bool = unsafe {
-                    *instruction_data.as_ptr().add(#offset) != 0
+                let #arg_name: bool = match unsafe { *instruction_data.as_ptr().add(#offset) } {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(mcpsol::pinocchio::program_error::ProgramError::InvalidInstructionData),
+                };
+            },
+            "u128" => quote! {
+                // SAFETY: instruction_data.len() >= __EXPECTED_LEN checked above
+                debug_assert!(#offset + 16 <= instruction_data.len());
+                let #arg_name: u128 = unsafe {
+                    core::ptr::read_unaligned(instruction_data.as_ptr().add(#offset) as *const u128)
                 };
             },
-            t if t.starts_with("Pubkey") || t.contains("Pubkey") => quote! {
+            "i128" => quote! {
+                // SAFETY: instruction_data.len() >= __EXPECTED_LEN checked above
+                debug_assert!(#offset + 16 <= instruction_data.len());
+                let #arg_name: i128 = unsafe {
+                    core::ptr::read_unaligned(instruction_data.as_ptr().add(#offset) as *const i128)
+                };
+            },
+            t if t.starts_with("[u8;") => {
+                let len = get_type_size(t).unwrap_or_default();
+                quote! {
+                    // SAFETY: instruction_data.len() >= __EXPECTED_LEN checked above
+                    debug_assert!(#offset + #len <= instruction_data.len());
+                    let #arg_name: [u8; #len] = unsafe {
+                        core::ptr::read_unaligned(
+                            instruction_data.as_ptr().add(#offset) as *const [u8; #len]
+                        )
+                    };
+                }
+            }
+            t if is_pubkey(t) => quote! {
                 // SAFETY: instruction_data.len() >= __EXPECTED_LEN checked above
                 debug_assert!(#offset + 32 <= instruction_data.len());
                 let #arg_name = unsafe {
@@ -586,11 +1003,8 @@ fn generate_arg_parsing_optimized(args: &[ArgInfo]) -> (TokenStream, Vec<Ident>)
                     pinocchio::pubkey::Pubkey::from(bytes)
                 };
             },
-            // Unknown fixed-size type - use legacy parsing
-            _ => {
-                // Fall back to legacy for this unknown type
-                return generate_arg_parsing(args);
-            }
+            // Types without a known layout are decoded through McpArgDecode
+            _ => return generate_arg_parsing(args, discriminator_len),
         };
 
         parsing_code.push(parse_expr);
@@ -604,84 +1018,320 @@ fn generate_arg_parsing_optimized(args: &[ArgInfo]) -> (TokenStream, Vec<Ident>)
 }
 
 /// Generate the list_tools instruction that returns MCP schema
-pub fn generate_list_tools(schema_json: &str) -> TokenStream {
+pub fn generate_list_tools(payloads: &SchemaPayloads, instructions: &[InstructionInfo]) -> TokenStream {
     let list_tools_disc = instruction_discriminator("list_tools");
 
-    // Convert schema JSON to byte array literal for zero-cost access
-    let schema_bytes: Vec<u8> = schema_json.bytes().collect();
+    let compact = generate_payload(&payloads.compact);
+    let pages = payloads.pages.iter().map(generate_payload);
+    let account_checks = instructions.iter().filter_map(generate_account_check);
 
     quote! {
         /// MCP schema as JSON bytes (auto-generated, zero runtime overhead)
-        pub const MCP_SCHEMA_BYTES: &[u8] = &[#(#schema_bytes),*];
+        pub const MCP_SCHEMA_BYTES: &[u8] = #compact;
 
         /// Legacy alias for backwards compatibility
         pub const MCP_SCHEMA_JSON: &[u8] = MCP_SCHEMA_BYTES;
 
+        /// Verbose schema pages with full descriptions, one tool per page.
+        /// Served by `list_tools` when a cursor byte follows the discriminator.
+        pub const MCP_SCHEMA_PAGES: &[&[u8]] = &[#(#pages),*];
+
         /// Discriminator for list_tools instruction
         pub const LIST_TOOLS_DISCRIMINATOR: [u8; 8] = [#(#list_tools_disc),*];
+
+        #(#account_checks)*
     }
 }
 
-/// Generate the entrypoint macro invocation
-pub fn generate_entrypoint() -> TokenStream {
-    quote! {
-        pinocchio::entrypoint!(__mcpsol_process_instruction);
-    }
+/// Generate the const expression for one schema payload.
+///
+/// Spliced payloads are assembled from `AccountsMeta` fragments in const
+/// context, where the return_data budget is also enforced.
+fn generate_payload(payload: &Payload) -> TokenStream {
+    let (label, parts, fallback) = match payload {
+        Payload::Fixed(bytes) => return quote! { &[#(#bytes),*] },
+        Payload::FirstPage => return quote! { MCP_SCHEMA_PAGES[0] },
+        Payload::Spliced { label, parts, fallback } => (label, parts, fallback),
+    };
+
+    let limit = RETURN_DATA_LIMIT;
+    let parts = parts.iter().map(generate_schema_part);
+    let (fallback_items, selected) = match fallback {
+        Fallback::Error(hint) => {
+            let msg = format!("{} exceeds the {}-byte return_data limit; {}", label, limit, hint);
+            (quote! { const _: () = assert!(LEN <= #limit, #msg); }, quote! { &BYTES })
+        }
+        Fallback::FirstPage => (
+            quote! {},
+            quote! { if LEN <= #limit { &BYTES } else { MCP_SCHEMA_PAGES[0] } },
+        ),
+        Fallback::Stripped(stripped) => {
+            let stripped = stripped.iter().map(generate_schema_part);
+            let msg = format!(
+                "{} exceeds the {}-byte return_data limit even without descriptions",
+                label, limit,
+            );
+            (
+                quote! {
+                    const STRIPPED_PARTS: &[mcpsol::schema::SchemaPart] = &[#(#stripped),*];
+                    const STRIPPED_LEN: usize = mcpsol::schema::schema_len(STRIPPED_PARTS);
+                    const STRIPPED: [u8; STRIPPED_LEN] =
+                        mcpsol::schema::schema_bytes::<STRIPPED_LEN>(STRIPPED_PARTS);
+                    const _: () = assert!(LEN <= #limit || STRIPPED_LEN <= #limit, #msg);
+                },
+                quote! { if LEN <= #limit { &BYTES } else { &STRIPPED } },
+            )
+        }
+    };
+
+    quote! {{
+        const PARTS: &[mcpsol::schema::SchemaPart] = &[#(#parts),*];
+        const LEN: usize = mcpsol::schema::schema_len(PARTS);
+        const BYTES: [u8; LEN] = mcpsol::schema::schema_bytes::<LEN>(PARTS);
+        #fallback_items
+        #selected
+    }}
 }
 
-fn extract_attr_value(attr_str: &str, key: &str) -> Option<String> {
-    // Handle various whitespace patterns around = sign
-    // The tokenizer might produce "key = \"", "key =\n\"", etc.
-    for pattern in [
-        format!("{} = \"", key),
-        format!("{} =\n\"", key),
-        format!("{}= \"", key),
-        format!("{}=\n\"", key),
-        format!("{} =\"", key),
-        format!("{}=\"", key),
-    ] {
-        if let Some(start) = attr_str.find(&pattern) {
-            let value_start = start + pattern.len();
-            if let Some(end) = attr_str[value_start..].find('"') {
-                return Some(attr_str[value_start..value_start + end].to_string());
+fn generate_schema_part(part: &SchemaPart) -> TokenStream {
+    match part {
+        SchemaPart::Bytes(bytes) => {
+            let lit = Literal::byte_string(bytes);
+            quote! { mcpsol::schema::SchemaPart::Bytes(#lit) }
+        }
+        SchemaPart::Accounts { accounts_type, fragment, comma } => {
+            let ty = Ident::new(accounts_type, Span::call_site());
+            let name = Ident::new(fragment.const_name(), Span::call_site());
+            let variant = if *comma {
+                quote! { AccountsThenComma }
+            } else {
+                quote! { Accounts }
+            };
+            quote! {
+                mcpsol::schema::SchemaPart::#variant(
+                    <#ty<'static> as mcpsol::context::AccountsMeta>::#name
+                )
             }
         }
+        SchemaPart::Returns(ty) => quote! {
+            mcpsol::schema::SchemaPart::Layout(<#ty as mcpsol::returns::McpReturn>::LAYOUT)
+        },
     }
-    None
 }
 
-/// Parse accounts attribute string into AccountMeta list
-/// Format: "name:flags, name:flags" where flags can be "signer", "mut", or "signer,mut"
-fn parse_accounts_attr(accounts_str: &str) -> Vec<AccountMeta> {
-    if accounts_str.is_empty() {
-        return Vec::new();
+/// When an instruction gives both an `accounts` string and a derived Context,
+/// assert at compile time that the two lists agree.
+fn generate_account_check(ix: &InstructionInfo) -> Option<TokenStream> {
+    let span = ix.accounts_span?;
+    let accounts_type = ix.accounts_type.as_ref().filter(|_| ix.use_context)?;
+    let ty = Ident::new(accounts_type, Span::call_site());
+
+    let declared = ix.accounts.iter().map(account_meta_def);
+    let msg = format!(
+        "`accounts` of `{}` does not match #[derive(Accounts)] on `{}`",
+        ix.tool_name, accounts_type,
+    );
+
+    Some(quote_spanned! {span=>
+        const _: () = assert!(
+            mcpsol::schema::accounts_match(
+                &[#(#declared),*],
+                <#ty<'static> as mcpsol::context::AccountsMeta>::ACCOUNTS,
+            ),
+            #msg
+        );
+    })
+}
+
+/// Pieces shared by the `cpi` and `instruction` builders of one instruction
+struct CallParts {
+    /// `&[AccountMetaDef]` expression with the fixed accounts' flags
+    defs: TokenStream,
+    /// Number of fixed accounts
+    len: TokenStream,
+    arg_names: Vec<Ident>,
+    /// `name: Type,` for each argument
+    params: TokenStream,
+    /// Block evaluating to the instruction data, in the dispatcher's layout
+    data: TokenStream,
+}
+
+fn call_parts(ix: &InstructionInfo) -> CallParts {
+    // Flags come from #[derive(Accounts)] when the Context provides them
+    let (defs, len) = match ix.accounts_type.as_ref().filter(|_| ix.derives_accounts()) {
+        Some(accounts_type) => {
+            let ty = Ident::new(accounts_type, Span::call_site());
+            let defs = quote! { <#ty<'static> as mcpsol::context::AccountsMeta>::ACCOUNTS };
+            (defs.clone(), quote! { #defs.len() })
+        }
+        None => {
+            let defs = ix.accounts.iter().map(account_meta_def);
+            let len = ix.accounts.len();
+            (quote! { &[#(#defs),*] }, quote! { #len })
+        }
+    };
+
+    let arg_names: Vec<_> = ix
+        .args
+        .iter()
+        .map(|arg| Ident::new(&arg.name, Span::call_site()))
+        .collect();
+    let arg_types = ix.args.iter().map(|arg| &arg.ty);
+    let params = quote! { #(#arg_names: #arg_types,)* };
+
+    let tag = ix.tag();
+    let capacity = calculate_expected_len(&ix.args, tag.len()).unwrap_or(tag.len());
+    let data = quote! {{
+        let mut data = Vec::with_capacity(#capacity);
+        data.extend_from_slice(&[#(#tag),*]);
+        #(mcpsol::args::McpArgEncode::encode(&#arg_names, &mut data);)*
+        data
+    }};
+
+    CallParts { defs, len, arg_names, params, data }
+}
+
+/// Generate the `cpi` module: per instruction, a function that encodes the
+/// arguments in the dispatcher's layout and invokes the program, and a
+/// `_signed` variant taking PDA signer seeds.
+///
+/// The module is behind the program crate's own `cpi` feature, so callers
+/// opt in from their dependency declaration, and is always there for the
+/// program's own tests.
+pub fn generate_cpi(instructions: &[InstructionInfo]) -> TokenStream {
+    let fns = instructions.iter().map(|ix| {
+        let fn_name = &ix.fn_name;
+        let signed_name = format_ident!("{}_signed", fn_name);
+        let doc = format!("Invoke `{}` on `program_id`.", ix.tool_name);
+        let signed_doc = format!(
+            "Invoke `{}` on `program_id`, signing for PDAs with `signers`.",
+            ix.tool_name,
+        );
+        let CallParts { defs, len, arg_names, params, data } = call_parts(ix);
+
+        let (remaining_param, remaining_arg, remaining) = match &ix.remaining_accounts {
+            Some(meta) => {
+                let def = account_meta_def(meta);
+                (
+                    quote! { remaining_accounts: &[&AccountInfo], },
+                    quote! { remaining_accounts, },
+                    quote! { Some((&#def, remaining_accounts)) },
+                )
+            }
+            None => (quote! {}, quote! {}, quote! { None }),
+        };
+
+        quote! {
+            #[doc = #doc]
+            pub fn #fn_name(
+                program_id: &Pubkey,
+                accounts: &[&AccountInfo; #len],
+                #remaining_param
+                #params
+            ) -> mcpsol::Result<()> {
+                #signed_name(program_id, accounts, #remaining_arg #(#arg_names,)* &[])
+            }
+
+            #[doc = #signed_doc]
+            pub fn #signed_name(
+                program_id: &Pubkey,
+                accounts: &[&AccountInfo; #len],
+                #remaining_param
+                #params
+                signers: &[mcpsol::pinocchio::instruction::Signer],
+            ) -> mcpsol::Result<()> {
+                let data = #data;
+                mcpsol::cpi::invoke_signed(program_id, &data, #defs, accounts, #remaining, signers)
+            }
+        }
+    });
+
+    quote! {
+        /// Typed cross-program invocation of this program's instructions
+        #[cfg(any(feature = "cpi", test))]
+        pub mod cpi {
+            #[allow(unused_imports)]
+            use super::*;
+            use mcpsol::pinocchio::account_info::AccountInfo;
+            use mcpsol::pinocchio::pubkey::Pubkey;
+
+            #(#fns)*
+        }
     }
+}
 
-    accounts_str
-        .split(',')
-        .filter_map(|part| {
-            let part = part.trim();
-            if part.is_empty() {
-                return None;
+/// Generate the `instruction` module of off-chain builders returning
+/// solana-sdk `Instruction`s, behind the program crate's `client` feature.
+///
+/// Signatures mirror the `cpi` module with account keys in place of
+/// account infos. solana-sdk types are spelled out in full, since `Pubkey`
+/// in argument types still means the program's `[u8; 32]` alias.
+pub fn generate_client(instructions: &[InstructionInfo]) -> TokenStream {
+    let fns = instructions.iter().map(|ix| {
+        let fn_name = &ix.fn_name;
+        let doc = format!("Build a `{}` instruction for `program_id`.", ix.tool_name);
+        let CallParts { defs, len, params, data, .. } = call_parts(ix);
+
+        let (remaining_param, remaining) = match &ix.remaining_accounts {
+            Some(meta) => {
+                let def = account_meta_def(meta);
+                (
+                    quote! { remaining_accounts: &[mcpsol::client::Pubkey], },
+                    quote! { Some((&#def, remaining_accounts)) },
+                )
             }
+            None => (quote! {}, quote! { None }),
+        };
 
-            let (name, flags) = if let Some(colon_pos) = part.find(':') {
-                (part[..colon_pos].trim(), part[colon_pos + 1..].trim())
-            } else {
-                (part, "")
-            };
+        quote! {
+            #[doc = #doc]
+            pub fn #fn_name(
+                program_id: &mcpsol::client::Pubkey,
+                accounts: &[mcpsol::client::Pubkey; #len],
+                #remaining_param
+                #params
+            ) -> mcpsol::client::Instruction {
+                mcpsol::client::instruction(program_id, #data, #defs, accounts, #remaining)
+            }
+        }
+    });
 
-            let is_signer = flags.contains("signer");
-            let is_writable = flags.contains("mut");
+    quote! {
+        /// Typed off-chain builders for this program's instructions
+        #[cfg(feature = "client")]
+        pub mod instruction {
+            #[allow(unused_imports)]
+            use super::*;
 
-            Some(AccountMeta {
-                name: name.to_string(),
-                is_signer,
-                is_writable,
-                description: String::new(),
-            })
-        })
-        .collect()
+            #(#fns)*
+        }
+    }
+}
+
+/// `mcpsol::context::AccountMetaDef` literal for an account
+pub(crate) fn account_meta_def(acc: &AccountMeta) -> TokenStream {
+    let AccountMeta { name, description, is_signer, is_writable, is_optional } = acc;
+    quote! {
+        mcpsol::context::AccountMetaDef {
+            name: #name,
+            description: #description,
+            is_signer: #is_signer,
+            is_writable: #is_writable,
+            is_optional: #is_optional,
+        }
+    }
+}
+
+/// Generate the entrypoint macro invocation, compiled out by the program
+/// crate's `no-entrypoint` feature so it can be linked into other programs
+/// and test crates.
+pub fn generate_entrypoint(mod_name: &Ident) -> TokenStream {
+    quote! {
+        #[cfg(not(feature = "no-entrypoint"))]
+        use #mod_name::process_instruction as __mcpsol_process_instruction;
+        #[cfg(not(feature = "no-entrypoint"))]
+        pinocchio::entrypoint!(__mcpsol_process_instruction);
+    }
 }
 
 #[cfg(test)]
@@ -689,20 +1339,272 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_extract_attr_value() {
-        // Test different spacing patterns
-        let attr1 = r#"name = "increment" , description = "Increase counter""#;
-        let attr2 = r#"name= "increment", description= "Increase counter""#;
-        let attr3 = r#"name ="increment" , description ="Increase counter""#;
-        let attr4 = r#"name="increment",description="Increase counter""#;
-        
-        for (i, attr) in [attr1, attr2, attr3, attr4].iter().enumerate() {
-            println!("Test {}: {:?}", i+1, attr);
-            let name = extract_attr_value(attr, "name");
-            let desc = extract_attr_value(attr, "description");
-            println!("  name: {:?}, description: {:?}", name, desc);
-            assert!(name.is_some(), "name should be found in test {}", i+1);
-            assert!(desc.is_some(), "description should be found in test {}", i+1);
+    fn test_extract_instructions() {
+        let module: syn::ItemMod = syn::parse_quote! {
+            mod counter {
+                #[mcp_instruction(
+                    name = "increment",
+                    description = "Increase counter",
+                    accounts = "counter:mut, authority:signer"
+                )]
+                pub fn increment(ctx: Context<'info, Modify<'info>>, amount: u64) -> Result<()> {
+                    Ok(())
+                }
+
+                /// Reset the counter to zero
+                #[mcp_instruction]
+                pub fn reset(ctx: Context<'info, Modify<'info>>) -> Result<()> {
+                    Ok(())
+                }
+            }
+        };
+        let (_, items) = module.content.unwrap();
+        let instructions = extract_instructions(&items, 8).unwrap();
+
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].tool_name, "increment");
+        assert_eq!(instructions[0].tool_desc, "Increase counter");
+        assert!(instructions[0].accounts[0].is_writable);
+        assert!(instructions[0].accounts[1].is_signer);
+        assert_eq!(instructions[0].args[0].name, "amount");
+        assert!(instructions[0].use_context);
+        assert_eq!(instructions[1].tool_name, "reset");
+        assert_eq!(instructions[1].tool_desc, "Reset the counter to zero");
+    }
+
+    #[test]
+    fn test_borrowed_context() {
+        let module: syn::ItemMod = syn::parse_quote! {
+            mod counter {
+                #[mcp_instruction]
+                pub fn owned(ctx: Context<'info, Modify<'info>>) -> Result<()> {
+                    Ok(())
+                }
+
+                #[mcp_instruction]
+                pub fn borrowed(ctx: &Context<'info, Modify<'info>>, amount: u64) -> Result<()> {
+                    Ok(())
+                }
+            }
+        };
+        let (_, items) = module.content.unwrap();
+        let instructions = extract_instructions(&items, 8).unwrap();
+        let hooks = DispatchHooks::default();
+
+        assert!(!instructions[0].ctx_by_ref);
+        let owned = generate_tool_body(&instructions[0], &hooks).to_string();
+        assert!(owned.contains("self :: owned (ctx)"));
+
+        assert!(instructions[1].ctx_by_ref);
+        assert_eq!(instructions[1].accounts_type.as_deref(), Some("Modify"));
+        let borrowed = generate_tool_body(&instructions[1], &hooks).to_string();
+        assert!(borrowed.contains("self :: borrowed (& ctx , amount)"));
+    }
+
+    #[test]
+    fn test_extract_instructions_rejects_unknown_key() {
+        let module: syn::ItemMod = syn::parse_quote! {
+            mod counter {
+                #[mcp_instruction(nmae = "increment")]
+                pub fn increment(ctx: Context<'info, Modify<'info>>) -> Result<()> {
+                    Ok(())
+                }
+            }
+        };
+        let (_, items) = module.content.unwrap();
+        let err = extract_instructions(&items, 8).err().unwrap();
+        assert!(err.to_string().contains("unknown `mcp_instruction` key `nmae`"));
+    }
+
+    fn dispatch_error(module: syn::ItemMod) -> String {
+        let (_, items) = module.content.unwrap();
+        extract_instructions(&items, 8).err().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_rejects_ambiguous_dispatch() {
+        let duplicate = dispatch_error(syn::parse_quote! {
+            mod counter {
+                #[mcp_instruction]
+                pub fn increment(ctx: Context<'info, Modify<'info>>) -> Result<()> { Ok(()) }
+                #[mcp_instruction(name = "increment")]
+                pub fn bump(ctx: Context<'info, Modify<'info>>) -> Result<()> { Ok(()) }
+            }
+        });
+        assert_eq!(duplicate, "tool name `increment` is used by both `increment` and `bump`");
+
+        let via_alias = dispatch_error(syn::parse_quote! {
+            mod counter {
+                #[mcp_instruction(aliases = ["add"])]
+                pub fn increment(ctx: Context<'info, Modify<'info>>) -> Result<()> { Ok(()) }
+                #[mcp_instruction]
+                pub fn add(ctx: Context<'info, Modify<'info>>) -> Result<()> { Ok(()) }
+            }
+        });
+        assert_eq!(via_alias, "tool name `add` is used by both `increment` and `add`");
+
+        let reserved = dispatch_error(syn::parse_quote! {
+            mod counter {
+                #[mcp_instruction(name = "list_tools")]
+                pub fn tools(ctx: Context<'info, Modify<'info>>) -> Result<()> { Ok(()) }
+            }
+        });
+        assert_eq!(reserved, "tool `list_tools` of `tools` clashes with the built-in `list_tools` instruction");
+    }
+
+    #[test]
+    fn test_rejects_discriminator_collision() {
+        // No two short names collide under SHA256, so force one
+        let module: syn::ItemMod = syn::parse_quote! {
+            mod counter {
+                #[mcp_instruction]
+                pub fn increment(ctx: Context<'info, Modify<'info>>) -> Result<()> { Ok(()) }
+                #[mcp_instruction]
+                pub fn reset(ctx: Context<'info, Modify<'info>>) -> Result<()> { Ok(()) }
+            }
+        };
+        let (_, items) = module.content.unwrap();
+        let mut instructions = extract_instructions(&items, 8).unwrap();
+        instructions[1].discriminator = instructions[0].discriminator;
+
+        let err = check_dispatch_keys(&instructions).err().unwrap();
+        let messages: Vec<String> = err.into_iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "discriminator of `reset` in `reset` collides with `increment` in `increment`",
+                "`increment` is first declared by `increment` here",
+            ]
+        );
+
+        // Short tags only need their leading bytes to collide
+        let mut instructions = extract_instructions(&items, 1).unwrap();
+        instructions[1].discriminator[0] = instructions[0].discriminator[0];
+        let err = check_dispatch_keys(&instructions).err().unwrap();
+        assert!(err.to_string().starts_with("first 1 discriminator bytes of `reset`"));
+    }
+
+    #[test]
+    fn test_dispatch_strategy_by_route_count() {
+        use DispatchStrategy::*;
+
+        // Byte 1 is the only distinct byte
+        let few: Vec<u64> = (0..4).map(|i| i << 8).collect();
+        assert_eq!(distinct_byte(&few), Some(1));
+        assert_eq!(choose_strategy(None, &few), Ok(Match));
+        assert_eq!(choose_strategy(Some(BinarySearch), &few), Ok(BinarySearch));
+
+        let many: Vec<u64> = (0..20).map(|i| i << 16).collect();
+        assert_eq!(distinct_byte(&many), Some(2));
+        assert_eq!(choose_strategy(None, &many), Ok(ByteHash));
+        assert_eq!(choose_strategy(Some(Match), &many), Ok(Match));
+
+        // Every byte repeats a value somewhere
+        let paired: Vec<u64> = (0..20).map(|i| (i / 2) | ((i % 2) << 8)).collect();
+        assert_eq!(distinct_byte(&paired), None);
+        assert_eq!(choose_strategy(None, &paired), Ok(BinarySearch));
+        let forced = choose_strategy(Some(ByteHash), &paired).err().unwrap();
+        assert!(forced.contains("use dispatch = \"binary_search\""));
+    }
+
+    fn args_of(module: syn::ItemMod) -> Vec<ArgInfo> {
+        let (_, items) = module.content.unwrap();
+        extract_instructions(&items, 8).unwrap().remove(0).args
+    }
+
+    #[test]
+    fn test_fixed_args_use_compile_time_offsets() {
+        let args = args_of(syn::parse_quote! {
+            mod m {
+                #[mcp_instruction(accounts = "state:mut")]
+                pub fn set(ctx: Context<'info, S<'info>>, big: u128, seed: [u8; 16], owner: Pubkey) -> Result<()> {
+                    Ok(())
+                }
+            }
+        });
+        let (code, names) = generate_arg_parsing_optimized(&args, 8);
+        let code = code.to_string();
+
+        assert_eq!(names.len(), 3);
+        // discriminator + u128 + [u8; 16] + Pubkey
+        assert!(code.contains("const __EXPECTED_LEN : usize = 72usize"));
+        assert!(!code.contains("McpArgDecode"));
+
+        // A 2-byte tag moves every offset
+        let (code, _) = generate_arg_parsing_optimized(&args, 2);
+        let code = code.to_string();
+        assert!(code.contains("const __EXPECTED_LEN : usize = 66usize"));
+        assert!(code.contains("instruction_data . as_ptr () . add (18usize)"));
+    }
+
+    #[test]
+    fn test_variable_args_decode_through_trait() {
+        let args = args_of(syn::parse_quote! {
+            mod m {
+                #[mcp_instruction(accounts = "state:mut")]
+                pub fn set(
+                    ctx: Context<'info, S<'info>>,
+                    id: u64,
+                    label: String,
+                    #[mcp_arg(schema_type = "[u8;16]")] range: Range,
+                ) -> Result<()> {
+                    Ok(())
+                }
+            }
+        });
+        let (code, _) = generate_arg_parsing_optimized(&args, 8);
+        let code = code.to_string();
+
+        assert!(code.contains("let mut __offset : usize = 8"));
+        for ty in ["u64", "String", "Range"] {
+            assert!(code.contains(&format!("< {} as mcpsol :: args :: McpArgDecode > :: decode", ty)));
+        }
+        assert!(!code.contains("= ()"));
+    }
+
+    #[test]
+    fn test_user_types_declare_a_schema_type() {
+        let args = args_of(syn::parse_quote! {
+            mod m {
+                #[mcp_instruction(accounts = "state:mut")]
+                pub fn set(
+                    ctx: Context<'info, S<'info>>,
+                    #[mcp_arg(schema_type = "[u8;16]")] range: Range,
+                ) -> Result<()> {
+                    Ok(())
+                }
+            }
+        });
+        assert_eq!(args[0].arg_type, ArgType::FixedBytes(16));
+
+        let module: syn::ItemMod = syn::parse_quote! {
+            mod m {
+                #[mcp_instruction(accounts = "state:mut")]
+                pub fn set(ctx: Context<'info, S<'info>>, range: Range) -> Result<()> {
+                    Ok(())
+                }
+            }
+        };
+        let (_, items) = module.content.unwrap();
+        let err = extract_instructions(&items, 8).err().unwrap();
+        assert!(err.to_string().contains("`Range` has no schema type"));
+    }
+
+    #[test]
+    fn test_arrays_need_a_byte_layout() {
+        for ty in ["[u8; LEN]", "[u16; 4]"] {
+            let ty: Type = syn::parse_str(ty).unwrap();
+            let module: syn::ItemMod = syn::parse_quote! {
+                mod m {
+                    #[mcp_instruction(accounts = "state:mut")]
+                    pub fn set(ctx: Context<'info, S<'info>>, seed: #ty) -> Result<()> {
+                        Ok(())
+                    }
+                }
+            };
+            let (_, items) = module.content.unwrap();
+            let err = extract_instructions(&items, 8).err().unwrap();
+            assert!(err.to_string().contains("literal length"));
         }
     }
 }
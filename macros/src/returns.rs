@@ -0,0 +1,171 @@
+//! Return value layouts and `#[derive(McpReturn)]`.
+//!
+//! Handlers returning an integer, `bool` or `Pubkey` get their layout
+//! written into the schema while the macro expands. Any other return type
+//! goes through its `McpReturn::LAYOUT`, spliced in during const evaluation
+//! like derived account lists.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Expr, ExprLit, Fields, Lit, Type};
+
+/// Return type of an instruction handler that produces return data
+#[derive(Clone)]
+pub struct ReturnInfo {
+    pub ty: Type,
+    /// Layout JSON when the macro can tell it from the type, `None` when it
+    /// comes from `McpReturn::LAYOUT`
+    pub layout: Option<String>,
+}
+
+impl ReturnInfo {
+    /// The `T` of a handler returning `Result<T>`, unless `T` is `()`
+    pub fn from_output(output: &syn::ReturnType) -> Option<Self> {
+        let syn::ReturnType::Type(_, ty) = output else {
+            return None;
+        };
+        let Type::Path(path) = &**ty else {
+            return None;
+        };
+        let last = path.path.segments.last()?;
+        if last.ident != "Result" {
+            return None;
+        }
+        let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+            return None;
+        };
+        let ty = args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        })?;
+        if matches!(&ty, Type::Tuple(tuple) if tuple.elems.is_empty()) {
+            return None;
+        }
+        let layout = value_layout(&ty).map(|name| format!("\"{}\"", name));
+        Some(Self { ty, layout })
+    }
+}
+
+/// Layout name of an integer, `bool` or `Pubkey`
+fn value_layout(ty: &Type) -> Option<&'static str> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let ident = &path.path.segments.last()?.ident;
+    Some(match ident.to_string().as_str() {
+        "u8" => "u8",
+        "u16" => "u16",
+        "u32" => "u32",
+        "u64" => "u64",
+        "u128" => "u128",
+        "i8" => "i8",
+        "i16" => "i16",
+        "i32" => "i32",
+        "i64" => "i64",
+        "i128" => "i128",
+        "bool" => "bool",
+        "Pubkey" => "pubkey",
+        _ => return None,
+    })
+}
+
+/// Layout name of a struct field: a value, or a fixed-size array of values
+/// written `[u8;7]`
+fn field_layout(ty: &Type) -> Option<String> {
+    if let Type::Array(array) = ty {
+        let Expr::Lit(ExprLit { lit: Lit::Int(len), .. }) = &array.len else {
+            return None;
+        };
+        let elem = field_layout(&array.elem)?;
+        return Some(format!("[{};{}]", elem, len.base10_digits()));
+    }
+    value_layout(ty).map(str::to_string)
+}
+
+/// Implement `McpReturn` for a `Pod` struct, returned as its bytes with
+/// the fields as its layout.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            name,
+            "`McpReturn` can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "`McpReturn` can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut entries = Vec::new();
+    for field in &fields.named {
+        let layout = field_layout(&field.ty).ok_or_else(|| {
+            syn::Error::new_spanned(
+                &field.ty,
+                "fields of a derived `McpReturn` must be integers, bool, Pubkey or \
+                 fixed-size arrays of them; implement `McpReturn` by hand instead",
+            )
+        })?;
+        let field = field.ident.as_ref().map(ToString::to_string).unwrap_or_default();
+        entries.push(format!("\"{}\":\"{}\"", field, layout));
+    }
+    let layout = format!("{{{}}}", entries.join(","));
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics mcpsol::returns::McpReturn for #name #ty_generics #where_clause {
+            const LAYOUT: &'static str = #layout;
+
+            #[inline(always)]
+            fn with_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+                f(mcpsol::bytemuck::bytes_of(self))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout_of(output: &syn::ReturnType) -> Option<Option<String>> {
+        ReturnInfo::from_output(output).map(|info| info.layout)
+    }
+
+    #[test]
+    fn test_return_layouts() {
+        assert_eq!(layout_of(&syn::parse_quote!(-> Result<u64>)), Some(Some("\"u64\"".into())));
+        assert_eq!(
+            layout_of(&syn::parse_quote!(-> mcpsol::Result<pinocchio::pubkey::Pubkey>)),
+            Some(Some("\"pubkey\"".into()))
+        );
+        assert_eq!(layout_of(&syn::parse_quote!(-> Result<VaultInfo>)), Some(None));
+        assert_eq!(layout_of(&syn::parse_quote!(-> Result<()>)), None);
+        assert_eq!(layout_of(&syn::parse_quote!(-> ProgramResult)), None);
+        assert_eq!(layout_of(&syn::parse_quote!()), None);
+    }
+
+    #[test]
+    fn test_derived_layout() {
+        let input: DeriveInput = syn::parse_quote! {
+            struct VaultInfo {
+                balance: u64,
+                owner: Pubkey,
+                bump: u8,
+                _pad: [u8; 7],
+            }
+        };
+        let code = expand(&input).unwrap().to_string();
+        assert!(code.contains(
+            r#""{\"balance\":\"u64\",\"owner\":\"pubkey\",\"bump\":\"u8\",\"_pad\":\"[u8;7]\"}""#
+        ));
+
+        let nested: DeriveInput = syn::parse_quote! {
+            struct Outer { inner: VaultInfo }
+        };
+        let err = expand(&nested).err().unwrap().to_string();
+        assert!(err.starts_with("fields of a derived `McpReturn` must be"));
+    }
+}
@@ -1,4 +1,6 @@
-use pinocchio::account_info::AccountInfo;
+use core::marker::PhantomData;
+
+use pinocchio::account_info::{AccountInfo, Ref, RefMut};
 use pinocchio::pubkey::Pubkey;
 
 use crate::error::{McpSolError, Result};
@@ -32,16 +34,83 @@ impl<'a, T: AccountDeserialize> Account<'a, T> {
 
     /// Create Account without owner verification
     ///
-    /// # Safety
-    /// This is unsafe in most contexts. Only use when you have verified
-    /// the account owner through other means (e.g., PDA derivation).
-    /// Prefer `try_from_with_owner` for security.
-    pub fn try_from(info: &'a AccountInfo) -> Result<Self> {
+    /// # Security
+    /// Only use when you have verified the account owner through other
+    /// means (e.g., PDA derivation). Prefer `try_from_with_owner`.
+    pub fn try_from_unchecked(info: &'a AccountInfo) -> Result<Self> {
         let data = T::try_deserialize(&info.try_borrow_data()?)?;
         Ok(Self { info, data })
     }
 }
 
+/// Zero-copy account wrapper for `Pod` account types
+///
+/// Unlike [`Account`], which copies the data out, `load`/`load_mut` borrow
+/// a typed view straight over the account bytes, so writes land in place.
+///
+/// # Security
+/// Construction verifies the discriminator and length; the owner is checked
+/// by `try_from_with_owner` or by `#[derive(Accounts)]`.
+pub struct AccountLoader<'a, T: AccountData + bytemuck::Pod> {
+    pub info: &'a AccountInfo,
+    _data: PhantomData<T>,
+}
+
+impl<'a, T: AccountData + bytemuck::Pod> AccountLoader<'a, T> {
+    /// Create AccountLoader with owner verification
+    pub fn try_from_with_owner(info: &'a AccountInfo, expected_owner: &Pubkey) -> Result<Self> {
+        // SECURITY: Verify account owner before trusting data
+        // Safety: owner() returns a valid pointer to the account's owner pubkey
+        if unsafe { info.owner() } != expected_owner {
+            return Err(McpSolError::InvalidOwner.into());
+        }
+        Self::try_from_unchecked(info)
+    }
+
+    /// Create AccountLoader after checking the discriminator and length only,
+    /// leaving the owner to the caller
+    pub fn try_from_unchecked(info: &'a AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        if data.len() < T::DISCRIMINATOR.len() + core::mem::size_of::<T>() {
+            return Err(McpSolError::SerializationError.into());
+        }
+        if data[..T::DISCRIMINATOR.len()] != T::DISCRIMINATOR {
+            return Err(McpSolError::InvalidAccount.into());
+        }
+        Ok(Self {
+            info,
+            _data: PhantomData,
+        })
+    }
+
+    /// Borrow the account data as `&T`
+    pub fn load(&self) -> Result<Ref<'a, T>> {
+        Ref::filter_map(self.info.try_borrow_data()?, |data| {
+            let start = T::DISCRIMINATOR.len();
+            let body = data.get(start..start + core::mem::size_of::<T>())?;
+            bytemuck::try_from_bytes(body).ok()
+        })
+        .map_err(|_| McpSolError::SerializationError.into())
+    }
+
+    /// Borrow the account data as `&mut T`; the account must be writable
+    pub fn load_mut(&self) -> Result<RefMut<'a, T>> {
+        if !self.info.is_writable() {
+            return Err(McpSolError::NotWritable.into());
+        }
+        RefMut::filter_map(self.info.try_borrow_mut_data()?, |data| {
+            let start = T::DISCRIMINATOR.len();
+            let body = data.get_mut(start..start + core::mem::size_of::<T>())?;
+            bytemuck::try_from_bytes_mut(body).ok()
+        })
+        .map_err(|_| McpSolError::SerializationError.into())
+    }
+
+    pub fn key(&self) -> &Pubkey {
+        self.info.key()
+    }
+}
+
 /// Signer account wrapper
 pub struct Signer<'a> {
     pub info: &'a AccountInfo,
@@ -134,11 +203,61 @@ impl<'a> UncheckedAccount<'a> {
     /// # Security
     /// No validation is performed. Caller must verify owner, signer status,
     /// writability, and data validity as needed.
-    pub fn try_from(info: &'a AccountInfo) -> Result<Self> {
+    pub const fn try_from(info: &'a AccountInfo) -> Result<Self> {
         Ok(Self { info })
     }
 }
 
+/// Access to the underlying `AccountInfo` of an account wrapper
+///
+/// Lets `#[derive(Accounts)]` constraints read keys regardless of the
+/// field's wrapper type.
+pub trait AsAccountInfo {
+    fn as_account_info(&self) -> &AccountInfo;
+}
+
+impl AsAccountInfo for AccountInfo {
+    fn as_account_info(&self) -> &AccountInfo {
+        self
+    }
+}
+
+impl<T: AccountDeserialize> AsAccountInfo for Account<'_, T> {
+    fn as_account_info(&self) -> &AccountInfo {
+        self.info
+    }
+}
+
+impl<T: AccountData + bytemuck::Pod> AsAccountInfo for AccountLoader<'_, T> {
+    fn as_account_info(&self) -> &AccountInfo {
+        self.info
+    }
+}
+
+impl AsAccountInfo for Signer<'_> {
+    fn as_account_info(&self) -> &AccountInfo {
+        self.info
+    }
+}
+
+impl AsAccountInfo for SystemAccount<'_> {
+    fn as_account_info(&self) -> &AccountInfo {
+        self.info
+    }
+}
+
+impl AsAccountInfo for Program<'_> {
+    fn as_account_info(&self) -> &AccountInfo {
+        self.info
+    }
+}
+
+impl AsAccountInfo for UncheckedAccount<'_> {
+    fn as_account_info(&self) -> &AccountInfo {
+        self.info
+    }
+}
+
 /// Trait for deserializing account data
 pub trait AccountDeserialize: Sized {
     fn try_deserialize(data: &[u8]) -> Result<Self>;
@@ -154,6 +273,6 @@ pub trait AccountData: AccountDeserialize + AccountSerialize {
     /// 8-byte discriminator for account type identification
     const DISCRIMINATOR: [u8; 8];
 
-    /// Space required for this account (excluding discriminator)
+    /// Space required for this account, including the discriminator
     const SPACE: usize;
 }
@@ -0,0 +1,247 @@
+//! Borsh decoding for instruction arguments.
+//!
+//! The generated dispatcher reads fixed-size arguments at compile-time
+//! offsets. Once an instruction takes a variable-size argument (`String`,
+//! `Vec<u8>`, or a user type), every argument is decoded in order through
+//! [`McpArgDecode`], advancing a shared offset with bounds checks.
+//!
+//! Implement the trait for your own types to use them as instruction
+//! arguments. The schema cannot describe them, so each such argument names
+//! the schema type of its encoding with `#[mcp_arg(schema_type = "...")]`:
+//!
+//! ```ignore
+//! pub struct Range { pub lo: u64, pub hi: u64 }
+//!
+//! impl McpArgDecode for Range {
+//!     fn decode(data: &[u8], offset: &mut usize) -> Result<Self> {
+//!         Ok(Self {
+//!             lo: u64::decode(data, offset)?,
+//!             hi: u64::decode(data, offset)?,
+//!         })
+//!     }
+//! }
+//!
+//! #[mcp_instruction(accounts = "state:mut")]
+//! pub fn set_range(
+//!     ctx: Context<'info, SetRange<'info>>,
+//!     #[mcp_arg(schema_type = "[u8;16]")] range: Range,
+//! ) -> Result<()> {
+//!     // ...
+//! }
+//! ```
+//!
+//! [`McpArgEncode`] is the inverse, used by the generated `cpi` module to
+//! build instruction data for calls into a program.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::error::Result;
+
+/// Decode a value from instruction data in Borsh layout.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be used as an instruction argument",
+    label = "implement `McpArgDecode` for `{Self}` to decode it",
+)]
+pub trait McpArgDecode: Sized {
+    /// Decode `Self` starting at `*offset` and advance the offset past it.
+    ///
+    /// Returns `InvalidInstructionData` when `data` is too short or the
+    /// bytes are not a valid encoding.
+    fn decode(data: &[u8], offset: &mut usize) -> Result<Self>;
+}
+
+/// Take `len` bytes at `*offset` and advance the offset.
+#[inline(always)]
+pub fn take<'a>(data: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let end = offset
+        .checked_add(len)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let bytes = data
+        .get(*offset..end)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    *offset = end;
+    Ok(bytes)
+}
+
+macro_rules! impl_decode_int {
+    ($($ty:ty),*) => {$(
+        impl McpArgDecode for $ty {
+            #[inline(always)]
+            fn decode(data: &[u8], offset: &mut usize) -> Result<Self> {
+                let bytes = take(data, offset, core::mem::size_of::<$ty>())?;
+                let bytes = bytes
+                    .try_into()
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(<$ty>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+impl_decode_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+/// `0` or `1`, as Borsh writes it; other bytes are rejected
+impl McpArgDecode for bool {
+    #[inline(always)]
+    fn decode(data: &[u8], offset: &mut usize) -> Result<Self> {
+        match u8::decode(data, offset)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// Fixed-size byte arrays, including `Pubkey`
+impl<const N: usize> McpArgDecode for [u8; N] {
+    #[inline(always)]
+    fn decode(data: &[u8], offset: &mut usize) -> Result<Self> {
+        take(data, offset, N)?
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)
+    }
+}
+
+/// `u32` little-endian length followed by the bytes
+impl McpArgDecode for Vec<u8> {
+    fn decode(data: &[u8], offset: &mut usize) -> Result<Self> {
+        let len = u32::decode(data, offset)? as usize;
+        Ok(take(data, offset, len)?.to_vec())
+    }
+}
+
+/// `u32` little-endian length followed by UTF-8 bytes
+impl McpArgDecode for String {
+    fn decode(data: &[u8], offset: &mut usize) -> Result<Self> {
+        let bytes = Vec::<u8>::decode(data, offset)?;
+        String::from_utf8(bytes).map_err(|_| ProgramError::InvalidInstructionData)
+    }
+}
+
+/// Encode a value into instruction data in Borsh layout.
+///
+/// Must produce exactly the bytes [`McpArgDecode`] reads back.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be encoded as an instruction argument",
+    label = "implement `McpArgEncode` for `{Self}` to call it through `cpi`",
+)]
+pub trait McpArgEncode {
+    /// Append the encoding of `self` to `data`.
+    fn encode(&self, data: &mut Vec<u8>);
+}
+
+macro_rules! impl_encode_int {
+    ($($ty:ty),*) => {$(
+        impl McpArgEncode for $ty {
+            #[inline(always)]
+            fn encode(&self, data: &mut Vec<u8>) {
+                data.extend_from_slice(&self.to_le_bytes());
+            }
+        }
+    )*};
+}
+
+impl_encode_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl McpArgEncode for bool {
+    #[inline(always)]
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.push(u8::from(*self));
+    }
+}
+
+impl<const N: usize> McpArgEncode for [u8; N] {
+    #[inline(always)]
+    fn encode(&self, data: &mut Vec<u8>) {
+        data.extend_from_slice(self);
+    }
+}
+
+impl McpArgEncode for [u8] {
+    fn encode(&self, data: &mut Vec<u8>) {
+        (self.len() as u32).encode(data);
+        data.extend_from_slice(self);
+    }
+}
+
+impl McpArgEncode for Vec<u8> {
+    fn encode(&self, data: &mut Vec<u8>) {
+        self.as_slice().encode(data);
+    }
+}
+
+impl McpArgEncode for String {
+    fn encode(&self, data: &mut Vec<u8>) {
+        self.as_bytes().encode(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_sequence() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&u128::MAX.to_le_bytes());
+        data.extend_from_slice(&(-5i128).to_le_bytes());
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(b"abc");
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&[0xAA, 0xBB]);
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        data.push(1);
+
+        let mut offset = 0;
+        assert_eq!(u128::decode(&data, &mut offset).unwrap(), u128::MAX);
+        assert_eq!(i128::decode(&data, &mut offset).unwrap(), -5);
+        assert_eq!(<[u8; 32]>::decode(&data, &mut offset).unwrap(), [7u8; 32]);
+        assert_eq!(String::decode(&data, &mut offset).unwrap(), "abc");
+        assert_eq!(Vec::<u8>::decode(&data, &mut offset).unwrap(), vec![0xAA, 0xBB]);
+        assert_eq!(<[u8; 4]>::decode(&data, &mut offset).unwrap(), [1, 2, 3, 4]);
+        assert!(bool::decode(&data, &mut offset).unwrap());
+        assert_eq!(offset, data.len());
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let mut data = Vec::new();
+        (-7i64).encode(&mut data);
+        true.encode(&mut data);
+        [9u8; 32].encode(&mut data);
+        String::from("héllo").encode(&mut data);
+        vec![1u8, 2].encode(&mut data);
+
+        let mut offset = 0;
+        assert_eq!(i64::decode(&data, &mut offset).unwrap(), -7);
+        assert!(bool::decode(&data, &mut offset).unwrap());
+        assert_eq!(<[u8; 32]>::decode(&data, &mut offset).unwrap(), [9u8; 32]);
+        assert_eq!(String::decode(&data, &mut offset).unwrap(), "héllo");
+        assert_eq!(Vec::<u8>::decode(&data, &mut offset).unwrap(), vec![1, 2]);
+        assert_eq!(offset, data.len());
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_and_invalid() {
+        let err = ProgramError::InvalidInstructionData;
+
+        // Length prefix claims more bytes than remain
+        let mut data = 10u32.to_le_bytes().to_vec();
+        data.extend_from_slice(b"short");
+        assert_eq!(String::decode(&data, &mut 0).unwrap_err(), err);
+
+        // Maximum length prefix with no payload
+        assert_eq!(Vec::<u8>::decode(&u32::MAX.to_le_bytes(), &mut 0).unwrap_err(), err);
+
+        // Invalid UTF-8
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.push(0xFF);
+        assert_eq!(String::decode(&data, &mut 0).unwrap_err(), err);
+
+        assert_eq!(u64::decode(&[0; 7], &mut 0).unwrap_err(), err);
+
+        // Booleans other than 0 and 1
+        assert_eq!(bool::decode(&[2], &mut 0).unwrap_err(), err);
+    }
+}
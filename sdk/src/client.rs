@@ -0,0 +1,50 @@
+//! Off-chain instruction building for `#[mcp_program]` programs.
+//!
+//! A program's `instruction` module, compiled with its `client` feature,
+//! has one builder per instruction that encodes the arguments and calls
+//! [`instruction`] here with the instruction's account flags.
+
+pub use solana_sdk::instruction::{AccountMeta, Instruction};
+pub use solana_sdk::pubkey::Pubkey;
+
+use crate::context::AccountMetaDef;
+
+/// Build an instruction for `program_id` with `data`, passing `accounts`
+/// with the signer and writable flags of `defs`, followed by `remaining`
+/// accounts that all share one set of flags.
+///
+/// Pass `program_id` for an optional account to leave it out; it is then
+/// neither a signer nor writable.
+pub fn instruction(
+    program_id: &Pubkey,
+    data: Vec<u8>,
+    defs: &[AccountMetaDef],
+    accounts: &[Pubkey],
+    remaining: Option<(&AccountMetaDef, &[Pubkey])>,
+) -> Instruction {
+    let (remaining_def, remaining) = match remaining {
+        Some((def, keys)) => (Some(def), keys),
+        None => (None, &[][..]),
+    };
+    let flags = defs.iter().chain(core::iter::repeat(remaining_def).flatten());
+
+    let accounts = accounts
+        .iter()
+        .chain(remaining)
+        .zip(flags)
+        .map(|(pubkey, def)| {
+            let present = !def.is_optional || pubkey != program_id;
+            AccountMeta {
+                pubkey: *pubkey,
+                is_signer: present && def.is_signer,
+                is_writable: present && def.is_writable,
+            }
+        })
+        .collect();
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
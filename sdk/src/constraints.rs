@@ -0,0 +1,80 @@
+//! Checks behind the `#[account(...)]` constraints of `#[derive(Accounts)]`.
+//!
+//! Each check maps to one [`McpSolError`] so a failed transaction says which
+//! constraint rejected it.
+
+use pinocchio::account_info::AccountInfo;
+use pinocchio::pubkey::{self, Pubkey};
+
+use crate::error::{McpSolError, Result};
+
+/// `owner = <program>`: the account is owned by `owner`.
+#[inline(always)]
+pub fn check_owner(info: &AccountInfo, owner: &[u8]) -> Result<()> {
+    // Safety: owner() returns a valid pointer to the account's owner pubkey
+    if unsafe { info.owner() }.as_slice() != owner {
+        return Err(McpSolError::InvalidOwner.into());
+    }
+    Ok(())
+}
+
+/// `address = <key>`: the account key equals `expected`.
+#[inline(always)]
+pub fn check_address(info: &AccountInfo, expected: &[u8]) -> Result<()> {
+    if info.key().as_slice() != expected {
+        return Err(McpSolError::AddressMismatch.into());
+    }
+    Ok(())
+}
+
+/// `has_one = target`: a key stored in account data equals `target`'s key.
+#[inline(always)]
+pub fn check_has_one(stored: &[u8], target: &AccountInfo) -> Result<()> {
+    if stored != target.key().as_slice() {
+        return Err(McpSolError::HasOneMismatch.into());
+    }
+    Ok(())
+}
+
+/// `seeds = [...], bump`: the account is the PDA of `seeds` under `program_id`.
+///
+/// With a known `bump` the address is recomputed directly; otherwise the
+/// canonical bump is searched for. Returns the bump that matched.
+pub fn check_seeds(
+    info: &AccountInfo,
+    seeds: &[&[u8]],
+    bump: Option<u8>,
+    program_id: &Pubkey,
+) -> Result<u8> {
+    let (expected, bump) = match bump {
+        Some(bump) => {
+            let bump_seed = [bump];
+            let mut with_bump = [&[][..]; pubkey::MAX_SEEDS];
+            let len = seeds.len() + 1;
+            if len > pubkey::MAX_SEEDS {
+                return Err(McpSolError::SeedsMismatch.into());
+            }
+            with_bump[..seeds.len()].copy_from_slice(seeds);
+            with_bump[seeds.len()] = &bump_seed;
+            let address = pubkey::checked_create_program_address(&with_bump[..len], program_id)
+                .map_err(|_| McpSolError::SeedsMismatch)?;
+            (address, bump)
+        }
+        None => pubkey::try_find_program_address(seeds, program_id)
+            .ok_or(McpSolError::SeedsMismatch)?,
+    };
+
+    if info.key() != &expected {
+        return Err(McpSolError::SeedsMismatch.into());
+    }
+    Ok(bump)
+}
+
+/// `constraint = expr`: the expression holds.
+#[inline(always)]
+pub fn check_constraint(holds: bool) -> Result<()> {
+    if !holds {
+        return Err(McpSolError::ConstraintViolation.into());
+    }
+    Ok(())
+}
@@ -11,7 +11,7 @@ pub struct Context<'info, T: Accounts<'info>> {
 }
 
 impl<'info, T: Accounts<'info>> Context<'info, T> {
-    pub fn new(
+    pub const fn new(
         program_id: &'info Pubkey,
         accounts: T,
         remaining_accounts: &'info [AccountInfo],
@@ -26,11 +26,52 @@ impl<'info, T: Accounts<'info>> Context<'info, T> {
 
 /// Trait for account structs that can be validated and loaded
 pub trait Accounts<'info>: Sized {
+    /// Number of leading accounts consumed by [`Accounts::try_accounts`].
+    ///
+    /// Accounts past this count become `Context::remaining_accounts`.
+    const LEN: usize;
+
     /// Try to load accounts from the provided account infos
     fn try_accounts(
         program_id: &Pubkey,
         accounts: &'info [AccountInfo],
     ) -> Result<Self>;
+
+    /// Run after the handler returns, e.g. to close accounts marked
+    /// `#[account(close = ..)]`. The generated dispatcher calls this.
+    fn exit(_program_id: &Pubkey, _accounts: &'info [AccountInfo]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Static metadata for one account of an [`Accounts`] struct
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountMetaDef {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    /// The program id may be passed in its place to leave it out
+    pub is_optional: bool,
+}
+
+/// Schema metadata emitted by `#[derive(Accounts)]`
+///
+/// `#[mcp_program]` builds a tool's account list from this when the
+/// instruction has no `accounts = "..."` string, and checks the string
+/// against it when both are given.
+pub trait AccountsMeta {
+    /// Accounts in declaration order, with the accounts of nested
+    /// `Accounts` fields in their place, named `<field>_<name>`
+    const ACCOUNTS: &'static [AccountMetaDef];
+    /// Compact schema properties, e.g. `"counter_w":"pubkey"`
+    const COMPACT_PROPERTIES: &'static str;
+    /// Compact schema required keys, e.g. `"counter_w"`
+    const COMPACT_REQUIRED: &'static str;
+    /// Verbose schema parameters with descriptions
+    const VERBOSE_PROPERTIES: &'static str;
+    /// Verbose schema parameters without descriptions
+    const VERBOSE_PROPERTIES_STRIPPED: &'static str;
 }
 
 /// Builder for creating context from raw entrypoint data
@@ -40,7 +81,7 @@ pub struct ContextBuilder<'info> {
 }
 
 impl<'info> ContextBuilder<'info> {
-    pub fn new(program_id: &'info Pubkey, accounts: &'info [AccountInfo]) -> Self {
+    pub const fn new(program_id: &'info Pubkey, accounts: &'info [AccountInfo]) -> Self {
         Self {
             program_id,
             accounts,
@@ -49,6 +90,7 @@ impl<'info> ContextBuilder<'info> {
 
     pub fn build<T: Accounts<'info>>(self) -> Result<Context<'info, T>> {
         let accounts = T::try_accounts(self.program_id, self.accounts)?;
-        Ok(Context::new(self.program_id, accounts, &[]))
+        let remaining = self.accounts.get(T::LEN..).unwrap_or(&[]);
+        Ok(Context::new(self.program_id, accounts, remaining))
     }
 }
@@ -0,0 +1,97 @@
+//! Cross-program invocation into `#[mcp_program]` programs.
+//!
+//! A program's `cpi` module, compiled with its `cpi` feature, has one
+//! function per instruction that encodes the arguments and calls
+//! [`invoke_signed`] here with the instruction's account flags.
+//!
+//! With the `test-utils` feature, off-chain builds record each invocation,
+//! which does nothing without the runtime, and `take_invocation` returns what
+//! was last sent so tests can check it.
+
+use pinocchio::account_info::AccountInfo;
+use pinocchio::cpi::slice_invoke_signed;
+use pinocchio::instruction::{AccountMeta, Instruction, Signer};
+use pinocchio::pubkey::Pubkey;
+
+use crate::context::AccountMetaDef;
+use crate::error::Result;
+
+/// Invoke `program_id` with `data`, passing `accounts` with the signer and
+/// writable flags of `defs`, followed by `remaining` accounts that all share
+/// one set of flags.
+///
+/// An optional account left out by passing the program itself is neither
+/// a signer nor writable.
+///
+/// `signers` holds the seeds of any PDA among the signer accounts; pass an
+/// empty slice when every signer signed the outer transaction.
+pub fn invoke_signed(
+    program_id: &Pubkey,
+    data: &[u8],
+    defs: &[AccountMetaDef],
+    accounts: &[&AccountInfo],
+    remaining: Option<(&AccountMetaDef, &[&AccountInfo])>,
+    signers: &[Signer],
+) -> Result<()> {
+    let (remaining_def, remaining) = match remaining {
+        Some((def, infos)) => (Some(def), infos),
+        None => (None, &[][..]),
+    };
+    let flags = defs.iter().chain(core::iter::repeat(remaining_def).flatten());
+
+    let infos: Vec<&AccountInfo> = accounts.iter().chain(remaining).copied().collect();
+    let metas: Vec<AccountMeta> = infos
+        .iter()
+        .zip(flags)
+        .map(|(info, def)| {
+            let present = !def.is_optional || info.key() != program_id;
+            AccountMeta::new(info.key(), present && def.is_writable, present && def.is_signer)
+        })
+        .collect();
+
+    let instruction = Instruction {
+        program_id,
+        accounts: &metas,
+        data,
+    };
+    #[cfg(all(feature = "test-utils", not(target_os = "solana")))]
+    record(&instruction);
+    slice_invoke_signed(&instruction, &infos, signers)
+}
+
+/// An instruction [`invoke_signed`] passed to the runtime
+#[cfg(all(feature = "test-utils", not(target_os = "solana")))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub program_id: Pubkey,
+    /// Key, writable and signer flags of each account, in order
+    pub accounts: Vec<(Pubkey, bool, bool)>,
+    pub data: Vec<u8>,
+}
+
+#[cfg(all(feature = "test-utils", not(target_os = "solana")))]
+std::thread_local! {
+    static LAST_INVOCATION: core::cell::RefCell<Option<Invocation>> =
+        const { core::cell::RefCell::new(None) };
+}
+
+#[cfg(all(feature = "test-utils", not(target_os = "solana")))]
+fn record(instruction: &Instruction) {
+    let invocation = Invocation {
+        program_id: *instruction.program_id,
+        accounts: instruction
+            .accounts
+            .iter()
+            .map(|meta| (*meta.pubkey, meta.is_writable, meta.is_signer))
+            .collect(),
+        data: instruction.data.to_vec(),
+    };
+    LAST_INVOCATION.with(|last| *last.borrow_mut() = Some(invocation));
+}
+
+/// Take the instruction last passed to the runtime on this thread.
+#[cfg(all(feature = "test-utils", not(target_os = "solana")))]
+pub fn take_invocation() -> Option<Invocation> {
+    LAST_INVOCATION.with(|last| last.borrow_mut().take())
+}
+
@@ -2,10 +2,13 @@
 
 use pinocchio::program_error::ProgramError;
 
+pub use mcpsol_core::CUSTOM_ERROR_OFFSET;
+
 /// Errors that can be returned by mcpsol programs.
 ///
 /// These are converted to Solana's `ProgramError::Custom(code)` where
-/// the code is the discriminant value.
+/// the code is the discriminant value, always below [`CUSTOM_ERROR_OFFSET`].
+/// Program-defined `#[mcp_error]` enums start at the offset.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
 #[non_exhaustive]
@@ -22,12 +25,18 @@ pub enum McpSolError {
     NotWritable = 4,
     /// Account owner mismatch (wrong program owns account)
     InvalidOwner = 5,
-    /// Constraint violation (e.g., PDA mismatch, has_one check)
+    /// Constraint violation (e.g., a failed `constraint = expr`)
     ConstraintViolation = 6,
     /// Serialization/deserialization error
     SerializationError = 7,
     /// Arithmetic overflow/underflow
     Overflow = 8,
+    /// Account key differs from the `address` constraint
+    AddressMismatch = 9,
+    /// Stored key differs from the account named by `has_one`
+    HasOneMismatch = 10,
+    /// Account is not the PDA of its `seeds` and `bump`
+    SeedsMismatch = 11,
 }
 
 impl From<McpSolError> for ProgramError {
@@ -27,10 +27,18 @@
 //! ```
 
 pub mod account;
+pub mod args;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod constraints;
 pub mod context;
+pub mod cpi;
 pub mod error;
+pub mod lifecycle;
 pub mod mcp;
 pub mod read;
+pub mod returns;
+pub mod schema;
 pub mod traits;
 
 /// Re-export mcpsol-core for compact schema generation
@@ -38,10 +46,12 @@ pub use mcpsol_core as core;
 
 pub mod prelude {
     pub use crate::account::*;
+    pub use crate::args::{McpArgDecode, McpArgEncode};
     pub use crate::context::*;
     pub use crate::error::{McpSolError, Result};
     pub use crate::mcp::*;
     pub use crate::read;
+    pub use crate::returns::McpReturn;
     pub use crate::traits::*;
     pub use mcpsol_macros::*;
     pub use pinocchio::account_info::AccountInfo;
@@ -71,5 +81,11 @@ pub use error::Result;
 /// Re-export pinocchio for convenience
 pub use pinocchio;
 
+/// Re-export pinocchio-log for macro-generated error logging
+pub use pinocchio_log;
+
 /// Re-export serde_json for macro-generated code
 pub use serde_json;
+
+/// Re-export bytemuck for derived `McpReturn` impls
+pub use bytemuck;
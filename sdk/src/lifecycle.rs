@@ -0,0 +1,159 @@
+//! Account creation, resizing and closing behind `#[account(init)]`,
+//! `#[account(realloc = ..)]` and `#[account(close = ..)]`.
+//!
+//! Funding goes through the system program so the payer's signature
+//! authorizes it; PDAs sign with the seeds given to the derive.
+//!
+//! The system instructions are encoded here, on this crate's pinocchio,
+//! rather than taken from `pinocchio-system`, which is built on a newer one.
+
+use pinocchio::account_info::AccountInfo;
+use pinocchio::instruction::{AccountMeta, Instruction, Signer};
+use pinocchio::program::invoke_signed;
+use pinocchio::program_error::ProgramError;
+use pinocchio::pubkey::Pubkey;
+use pinocchio::sysvars::rent::Rent;
+use pinocchio::sysvars::Sysvar;
+
+use crate::error::Result;
+
+/// System program ID (all zeros)
+const SYSTEM_PROGRAM_ID: Pubkey = [0u8; 32];
+
+/// `init`: create `account` with `space` bytes owned by `owner`, funded
+/// rent-exempt by `payer`, and write `discriminator` at the start.
+///
+/// An account that was already sent lamports is topped up, allocated and
+/// assigned instead, since `CreateAccount` rejects non-empty balances.
+pub fn init_account(
+    account: &AccountInfo,
+    payer: &AccountInfo,
+    space: usize,
+    owner: &Pubkey,
+    discriminator: &[u8],
+    signers: &[Signer],
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(space);
+    let current = account.lamports();
+
+    if current == 0 {
+        create_account(payer, account, required, space as u64, owner, signers)?;
+    } else {
+        if required > current {
+            transfer(payer, account, required - current)?;
+        }
+        allocate(account, space as u64, signers)?;
+        assign(account, owner, signers)?;
+    }
+
+    account
+        .try_borrow_mut_data()?
+        .get_mut(..discriminator.len())
+        .ok_or(ProgramError::AccountDataTooSmall)?
+        .copy_from_slice(discriminator);
+    Ok(())
+}
+
+/// `realloc = new_len`: resize `account`, keeping it rent-exempt.
+///
+/// `payer` covers a larger balance and receives any excess on shrinking.
+pub fn realloc_account(account: &AccountInfo, payer: &AccountInfo, new_len: usize) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(new_len);
+    let current = account.lamports();
+
+    if required > current {
+        transfer(payer, account, required - current)?;
+    } else if current > required {
+        move_lamports(account, payer, current - required)?;
+    }
+
+    // Bytes past the old length may still hold data from an earlier shrink
+    // in this transaction, so a growing account is zeroed
+    account.realloc(new_len, new_len > account.data_len())
+}
+
+/// `close = destination`: move all lamports to `destination`, drop the
+/// data and hand the account back to the system program.
+pub fn close_account(account: &AccountInfo, destination: &AccountInfo) -> Result<()> {
+    move_lamports(account, destination, account.lamports())?;
+    account.realloc(0, false)?;
+    // Safety: no data or lamport borrows are held past this point
+    unsafe { account.assign(&SYSTEM_PROGRAM_ID) };
+    Ok(())
+}
+
+/// Move lamports directly between accounts; `from` must be owned by the
+/// executing program.
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> Result<()> {
+    let mut to_lamports = to.try_borrow_mut_lamports()?;
+    *to_lamports = to_lamports
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let mut from_lamports = from.try_borrow_mut_lamports()?;
+    *from_lamports = from_lamports
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    Ok(())
+}
+
+// System program instructions: a u32 index followed by bincode arguments
+
+fn create_account(
+    from: &AccountInfo,
+    to: &AccountInfo,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+    signers: &[Signer],
+) -> Result<()> {
+    let mut data = [0u8; 52];
+    data[4..12].copy_from_slice(&lamports.to_le_bytes());
+    data[12..20].copy_from_slice(&space.to_le_bytes());
+    data[20..52].copy_from_slice(owner);
+    let metas = [
+        AccountMeta::writable_signer(from.key()),
+        AccountMeta::writable_signer(to.key()),
+    ];
+    invoke_system(&metas, &data, &[from, to], signers)
+}
+
+fn assign(account: &AccountInfo, owner: &Pubkey, signers: &[Signer]) -> Result<()> {
+    let mut data = [0u8; 36];
+    data[0] = 1;
+    data[4..36].copy_from_slice(owner);
+    let metas = [AccountMeta::writable_signer(account.key())];
+    invoke_system(&metas, &data, &[account], signers)
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> Result<()> {
+    let mut data = [0u8; 12];
+    data[0] = 2;
+    data[4..12].copy_from_slice(&lamports.to_le_bytes());
+    let metas = [
+        AccountMeta::writable_signer(from.key()),
+        AccountMeta::writable(to.key()),
+    ];
+    invoke_system(&metas, &data, &[from, to], &[])
+}
+
+fn allocate(account: &AccountInfo, space: u64, signers: &[Signer]) -> Result<()> {
+    let mut data = [0u8; 12];
+    data[0] = 8;
+    data[4..12].copy_from_slice(&space.to_le_bytes());
+    let metas = [AccountMeta::writable_signer(account.key())];
+    invoke_system(&metas, &data, &[account], signers)
+}
+
+fn invoke_system<const N: usize>(
+    metas: &[AccountMeta; N],
+    data: &[u8],
+    accounts: &[&AccountInfo; N],
+    signers: &[Signer],
+) -> Result<()> {
+    let instruction = Instruction {
+        program_id: &SYSTEM_PROGRAM_ID,
+        accounts: metas,
+        data,
+    };
+    invoke_signed(&instruction, accounts, signers)
+}
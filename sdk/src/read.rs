@@ -245,9 +245,9 @@ mod tests {
     #[test]
     fn test_read_bool() {
         let data = [0x00, 0x01, 0xFF];
-        assert_eq!(unsafe { read_bool_unchecked(&data, 0) }, false);
-        assert_eq!(unsafe { read_bool_unchecked(&data, 1) }, true);
-        assert_eq!(unsafe { read_bool_unchecked(&data, 2) }, true); // Any non-zero is true
+        assert!(!unsafe { read_bool_unchecked(&data, 0) });
+        assert!(unsafe { read_bool_unchecked(&data, 1) });
+        assert!(unsafe { read_bool_unchecked(&data, 2) }); // Any non-zero is true
     }
 
     #[test]
@@ -0,0 +1,99 @@
+//! Typed instruction return values.
+//!
+//! A handler under `#[mcp_instruction]` may return `Result<T>` for any
+//! `T: McpReturn`. Once it succeeds, the generated dispatcher hands the
+//! encoded value to `set_return_data`, and the schema advertises
+//! [`McpReturn::LAYOUT`] so clients can decode it.
+//!
+//! Integers, `bool` and `Pubkey` are covered here. `#[derive(McpReturn)]`
+//! covers `Pod` structs, returned as their in-memory bytes:
+//!
+//! ```ignore
+//! #[derive(McpReturn, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+//! #[repr(C)]
+//! pub struct VaultInfo {
+//!     pub balance: u64,
+//!     pub owner: Pubkey,
+//! }
+//!
+//! #[mcp_instruction(accounts = "vault")]
+//! pub fn get_info(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<VaultInfo> {
+//!     // ...
+//! }
+//! ```
+
+use pinocchio::pubkey::Pubkey;
+
+/// A value an instruction returns through return data.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be returned from an instruction",
+    label = "implement `McpReturn` for `{Self}`, or derive it on a `Pod` struct",
+)]
+pub trait McpReturn {
+    /// Layout advertised in the schema, as JSON: a type name such as
+    /// `"u64"`, or an object of field names to their layouts in order.
+    /// Fixed-size arrays are written `"[u8;16]"`.
+    const LAYOUT: &'static str;
+
+    /// Pass the encoded value to `f`, e.g. `set_return_data`.
+    ///
+    /// Integers are little-endian and `bool` is one byte, matching how
+    /// [`McpArgDecode`](crate::args::McpArgDecode) reads arguments.
+    fn with_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R;
+}
+
+macro_rules! impl_return_int {
+    ($($ty:ty => $layout:literal),*) => {$(
+        impl McpReturn for $ty {
+            const LAYOUT: &'static str = concat!("\"", $layout, "\"");
+
+            #[inline(always)]
+            fn with_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+                f(&self.to_le_bytes())
+            }
+        }
+    )*};
+}
+
+impl_return_int!(
+    u8 => "u8", u16 => "u16", u32 => "u32", u64 => "u64", u128 => "u128",
+    i8 => "i8", i16 => "i16", i32 => "i32", i64 => "i64", i128 => "i128"
+);
+
+impl McpReturn for bool {
+    const LAYOUT: &'static str = "\"bool\"";
+
+    #[inline(always)]
+    fn with_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        f(&[u8::from(*self)])
+    }
+}
+
+impl McpReturn for Pubkey {
+    const LAYOUT: &'static str = "\"pubkey\"";
+
+    #[inline(always)]
+    fn with_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        f(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode<T: McpReturn>(value: &T) -> Vec<u8> {
+        value.with_bytes(<[u8]>::to_vec)
+    }
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(encode(&0x0102u16), [2, 1]);
+        assert_eq!(encode(&-2i32), [0xfe, 0xff, 0xff, 0xff]);
+        assert_eq!(encode(&true), [1]);
+        assert_eq!(encode(&[7u8; 32]), [7; 32]);
+
+        assert_eq!(u128::LAYOUT, "\"u128\"");
+        assert_eq!(Pubkey::LAYOUT, "\"pubkey\"");
+    }
+}
@@ -0,0 +1,423 @@
+//! Compile-time schema assembly for `#[mcp_program]`.
+//!
+//! When an instruction takes its account list from `#[derive(Accounts)]`,
+//! the macro cannot see those accounts while it expands. It emits the
+//! schema as [`SchemaPart`]s instead, and the derive's
+//! [`AccountsMeta`](crate::context::AccountsMeta) fragments are spliced in
+//! during const evaluation. Return layouts from
+//! [`McpReturn`](crate::returns::McpReturn) are spliced the same way.
+//!
+//! A derived struct with nested `Accounts` fields cannot see its nested
+//! accounts either. It lists its fields as [`AccountsField`]s, flattens them
+//! with [`flatten_accounts`] and renders its fragments with
+//! [`account_fragment`].
+
+use crate::context::AccountMetaDef;
+
+/// One piece of a schema payload
+#[derive(Debug, Clone, Copy)]
+pub enum SchemaPart {
+    /// Bytes known when the macro expands
+    Bytes(&'static [u8]),
+    /// Account fragment from `AccountsMeta`
+    Accounts(&'static str),
+    /// Account fragment followed by a comma, unless the fragment is empty
+    AccountsThenComma(&'static str),
+    /// Return layout from `McpReturn`
+    Layout(&'static str),
+}
+
+impl SchemaPart {
+    const fn bytes(&self) -> &'static [u8] {
+        match self {
+            Self::Bytes(bytes) => bytes,
+            Self::Accounts(s) | Self::AccountsThenComma(s) | Self::Layout(s) => s.as_bytes(),
+        }
+    }
+
+    const fn trailing_comma(&self) -> bool {
+        match self {
+            Self::AccountsThenComma(s) => !s.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+/// Total length of the assembled payload
+pub const fn schema_len(parts: &[SchemaPart]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        len += parts[i].bytes().len();
+        if parts[i].trailing_comma() {
+            len += 1;
+        }
+        i += 1;
+    }
+    len
+}
+
+/// Assemble the payload; `N` must be [`schema_len`] of the same parts
+pub const fn schema_bytes<const N: usize>(parts: &[SchemaPart]) -> [u8; N] {
+    let mut out = [0u8; N];
+    let mut pos = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        let bytes = parts[i].bytes();
+        let mut j = 0;
+        while j < bytes.len() {
+            out[pos] = bytes[j];
+            pos += 1;
+            j += 1;
+        }
+        if parts[i].trailing_comma() {
+            out[pos] = b',';
+            pos += 1;
+        }
+        i += 1;
+    }
+    out
+}
+
+/// One field of a `#[derive(Accounts)]` struct with nested fields
+#[derive(Debug, Clone, Copy)]
+pub enum AccountsField {
+    /// A single account
+    Account(AccountMetaDef),
+    /// The flattened accounts of a nested struct, named `<field>_<name>`
+    Nested(&'static str, &'static [AccountMetaDef]),
+}
+
+/// Number of accounts once nested fields are flattened
+pub const fn accounts_len(fields: &[AccountsField]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < fields.len() {
+        len += match fields[i] {
+            AccountsField::Account(_) => 1,
+            AccountsField::Nested(_, defs) => defs.len(),
+        };
+        i += 1;
+    }
+    len
+}
+
+/// Total length of the prefixed names of nested accounts
+pub const fn nested_names_len(fields: &[AccountsField]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < fields.len() {
+        if let AccountsField::Nested(prefix, defs) = fields[i] {
+            let mut j = 0;
+            while j < defs.len() {
+                len += prefix.len() + 1 + defs[j].name.len();
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+    len
+}
+
+/// The prefixed names of nested accounts, back to back; `N` must be
+/// [`nested_names_len`] of the same fields
+pub const fn nested_names<const N: usize>(fields: &[AccountsField]) -> [u8; N] {
+    let mut out = [0u8; N];
+    let mut pos = 0;
+    let mut i = 0;
+    while i < fields.len() {
+        if let AccountsField::Nested(prefix, defs) = fields[i] {
+            let mut j = 0;
+            while j < defs.len() {
+                pos = copy_into(&mut out, pos, prefix.as_bytes());
+                pos = copy_into(&mut out, pos, b"_");
+                pos = copy_into(&mut out, pos, defs[j].name.as_bytes());
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Flatten `fields` into one account list, taking nested accounts' names
+/// from `names`; `N` must be [`accounts_len`] of the same fields
+pub const fn flatten_accounts<const N: usize>(
+    fields: &[AccountsField],
+    mut names: &'static [u8],
+) -> [AccountMetaDef; N] {
+    let mut out = [AccountMetaDef {
+        name: "",
+        description: "",
+        is_signer: false,
+        is_writable: false,
+        is_optional: false,
+    }; N];
+    let mut pos = 0;
+    let mut i = 0;
+    while i < fields.len() {
+        match fields[i] {
+            AccountsField::Account(def) => {
+                out[pos] = def;
+                pos += 1;
+            }
+            AccountsField::Nested(prefix, defs) => {
+                let mut j = 0;
+                while j < defs.len() {
+                    let (name, rest) = names.split_at(prefix.len() + 1 + defs[j].name.len());
+                    names = rest;
+                    out[pos] = defs[j];
+                    out[pos].name = utf8(name);
+                    pos += 1;
+                    j += 1;
+                }
+            }
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Bytes joined from `&str`s back as a `&str`
+pub const fn utf8(bytes: &'static [u8]) -> &'static str {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(_) => "",
+    }
+}
+
+/// An `AccountsMeta` fragment rendered from an account list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountFragment {
+    CompactProperties,
+    CompactRequired,
+    VerboseProperties,
+    VerbosePropertiesStripped,
+}
+
+/// Length of `fragment` rendered from `defs`
+pub const fn account_fragment_len(defs: &[AccountMetaDef], fragment: AccountFragment) -> usize {
+    render_accounts(defs, fragment, &mut [])
+}
+
+/// Render `fragment` from `defs` the way mcpsol-core renders tools; `N`
+/// must be [`account_fragment_len`] of the same arguments
+pub const fn account_fragment<const N: usize>(
+    defs: &[AccountMetaDef],
+    fragment: AccountFragment,
+) -> [u8; N] {
+    let mut out = [0u8; N];
+    render_accounts(defs, fragment, &mut out);
+    out
+}
+
+/// Write the fragment into `out`, as far as it fits, and return its length
+const fn render_accounts(
+    defs: &[AccountMetaDef],
+    fragment: AccountFragment,
+    out: &mut [u8],
+) -> usize {
+    let mut pos = 0;
+    let mut i = 0;
+    while i < defs.len() {
+        let def = &defs[i];
+        if i > 0 {
+            pos = copy_into(out, pos, b",");
+        }
+        pos = copy_into(out, pos, b"\"");
+        pos = escape_into(out, pos, def.name.as_bytes());
+        let ty: &[u8] = if def.is_optional {
+            mcpsol_core::OPTIONAL_ACCOUNT_TYPE.as_bytes()
+        } else {
+            b"pubkey"
+        };
+        match fragment {
+            AccountFragment::CompactProperties | AccountFragment::CompactRequired => {
+                let suffix: &[u8] = match (def.is_signer, def.is_writable) {
+                    (true, true) => b"_sw",
+                    (true, false) => b"_s",
+                    (false, true) => b"_w",
+                    (false, false) => b"",
+                };
+                pos = copy_into(out, pos, suffix);
+                pos = copy_into(out, pos, b"\"");
+                if matches!(fragment, AccountFragment::CompactProperties) {
+                    pos = copy_into(out, pos, b":\"");
+                    pos = copy_into(out, pos, ty);
+                    pos = copy_into(out, pos, b"\"");
+                }
+            }
+            AccountFragment::VerboseProperties | AccountFragment::VerbosePropertiesStripped => {
+                pos = copy_into(out, pos, b"\":{\"type\":\"");
+                pos = copy_into(out, pos, ty);
+                pos = copy_into(out, pos, b"\"");
+                if def.is_signer {
+                    pos = copy_into(out, pos, b",\"signer\":true");
+                }
+                if def.is_writable {
+                    pos = copy_into(out, pos, b",\"writable\":true");
+                }
+                let described = matches!(fragment, AccountFragment::VerboseProperties);
+                if described && !def.description.is_empty() {
+                    pos = copy_into(out, pos, b",\"description\":\"");
+                    pos = escape_into(out, pos, def.description.as_bytes());
+                    pos = copy_into(out, pos, b"\"");
+                }
+                pos = copy_into(out, pos, b"}");
+            }
+        }
+        i += 1;
+    }
+    pos
+}
+
+/// Copy `bytes` to `out` at `pos`, dropping what does not fit, and return
+/// the position after them
+const fn copy_into(out: &mut [u8], mut pos: usize, bytes: &[u8]) -> usize {
+    let mut i = 0;
+    while i < bytes.len() {
+        if pos < out.len() {
+            out[pos] = bytes[i];
+        }
+        pos += 1;
+        i += 1;
+    }
+    pos
+}
+
+/// [`copy_into`] with JSON string escaping
+const fn escape_into(out: &mut [u8], mut pos: usize, bytes: &[u8]) -> usize {
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped: &[u8] = match bytes[i] {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\n' => b"\\n",
+            b'\r' => b"\\r",
+            b'\t' => b"\\t",
+            _ => core::slice::from_ref(&bytes[i]),
+        };
+        pos = copy_into(out, pos, escaped);
+        i += 1;
+    }
+    pos
+}
+
+/// Whether two account lists agree on names, order, and signer, writable
+/// and optional flags.
+///
+/// Descriptions are not compared.
+pub const fn accounts_match(declared: &[AccountMetaDef], derived: &[AccountMetaDef]) -> bool {
+    if declared.len() != derived.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < declared.len() {
+        let (a, b) = (&declared[i], &derived[i]);
+        if !str_eq(a.name, b.name)
+            || a.is_signer != b.is_signer
+            || a.is_writable != b.is_writable
+            || a.is_optional != b.is_optional
+        {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn def(name: &'static str, is_signer: bool, is_writable: bool) -> AccountMetaDef {
+        AccountMetaDef {
+            name,
+            description: "",
+            is_signer,
+            is_writable,
+            is_optional: false,
+        }
+    }
+
+    #[test]
+    fn test_schema_assembly() {
+        const PARTS: &[SchemaPart] = &[
+            SchemaPart::Bytes(b"{"),
+            SchemaPart::AccountsThenComma("\"a_w\":\"pubkey\""),
+            SchemaPart::Bytes(b"\"x\":\"int\"}"),
+        ];
+        const LEN: usize = schema_len(PARTS);
+        const BYTES: [u8; LEN] = schema_bytes::<LEN>(PARTS);
+        assert_eq!(&BYTES, b"{\"a_w\":\"pubkey\",\"x\":\"int\"}");
+
+        const EMPTY: &[SchemaPart] = &[
+            SchemaPart::Bytes(b"{"),
+            SchemaPart::AccountsThenComma(""),
+            SchemaPart::Bytes(b"}"),
+        ];
+        assert_eq!(schema_bytes::<2>(EMPTY), *b"{}");
+    }
+
+    #[test]
+    fn test_nested_accounts_flatten() {
+        const INNER: &[AccountMetaDef] = &[def("from", false, true), def("authority", true, false)];
+        const FIELDS: &[AccountsField] = &[
+            AccountsField::Account(def("payer", true, true)),
+            AccountsField::Nested("transfer", INNER),
+        ];
+        const NAMES_LEN: usize = nested_names_len(FIELDS);
+        const NAMES: [u8; NAMES_LEN] = nested_names::<NAMES_LEN>(FIELDS);
+        const LEN: usize = accounts_len(FIELDS);
+        const DEFS: [AccountMetaDef; LEN] = flatten_accounts::<LEN>(FIELDS, &NAMES);
+
+        let names: Vec<_> = DEFS.iter().map(|def| def.name).collect();
+        assert_eq!(names, ["payer", "transfer_from", "transfer_authority"]);
+        assert!(DEFS[2].is_signer && !DEFS[2].is_writable);
+
+        let render = |fragment| {
+            let mut out = vec![0; account_fragment_len(&DEFS, fragment)];
+            render_accounts(&DEFS, fragment, &mut out);
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            render(AccountFragment::CompactRequired),
+            r#""payer_sw","transfer_from_w","transfer_authority_s""#
+        );
+
+        let described = [AccountMetaDef {
+            description: "Say \"hi\"\n",
+            is_optional: true,
+            ..def("memo", false, false)
+        }];
+        let mut out = vec![0; account_fragment_len(&described, AccountFragment::VerboseProperties)];
+        render_accounts(&described, AccountFragment::VerboseProperties, &mut out);
+        assert_eq!(out, br#""memo":{"type":"pubkey?","description":"Say \"hi\"\n"}"#);
+    }
+
+    #[test]
+    fn test_accounts_match() {
+        let derived = [def("counter", false, true), def("authority", true, false)];
+        assert!(accounts_match(&derived, &derived));
+        assert!(!accounts_match(&[def("counter", false, true)], &derived));
+        assert!(!accounts_match(
+            &[def("counter", false, false), def("authority", true, false)],
+            &derived
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+//! `#[derive(Accounts)]` against account infos laid out like the runtime's
+//! input buffer, plus the lifecycle and CPI helpers that run on them.
+
+mod common;
+
+use common::{infos, Infos, TestAccount, PROGRAM_ID};
+use mcpsol::prelude::*;
+
+const ADMIN: Pubkey = [9; 32];
+
+/// Vault state with the authority stored after the discriminator
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, McpAccount)]
+#[repr(C)]
+pub struct Vault {
+    pub authority: [u8; 32],
+    pub amount: u64,
+}
+
+fn vault_data(authority: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = Vault::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&Vault { authority, amount }));
+    data
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// Vault to withdraw from
+    #[account(mut, has_one = authority, constraint = vault.data.amount > 0)]
+    pub vault: Account<'info, Vault>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+    #[account(address = ADMIN)]
+    pub admin: &'info AccountInfo,
+    #[account(owner = [0u8; 32])]
+    pub wallet: &'info AccountInfo,
+}
+
+fn withdraw_accounts(vault_owner: Pubkey, stored: Pubkey, amount: u64, admin: Pubkey) -> Infos {
+    infos(&[
+        TestAccount::new(1).owner(vault_owner).writable().data(vault_data(stored, amount)),
+        TestAccount::new(2).signer(),
+        TestAccount { key: admin, ..TestAccount::new(0) },
+        TestAccount::new(4).owner([0; 32]),
+        TestAccount::new(5),
+    ])
+}
+
+fn withdraw(infos: &Infos) -> mcpsol::Result<()> {
+    Withdraw::try_accounts(&PROGRAM_ID, &infos.infos).map(|_| ())
+}
+
+#[test]
+fn test_constraints_pass() {
+    let infos = withdraw_accounts(PROGRAM_ID, [2; 32], 10, ADMIN);
+    let ctx = ContextBuilder::new(&PROGRAM_ID, &infos.infos)
+        .build::<Withdraw>()
+        .unwrap();
+
+    assert_eq!(ctx.accounts.vault.data.amount, 10);
+    assert_eq!(ctx.accounts.admin.key(), &ADMIN);
+    // The fifth account was not consumed by the struct
+    assert_eq!(ctx.remaining_accounts.len(), 1);
+    assert_eq!(ctx.remaining_accounts[0].key(), &[5; 32]);
+}
+
+#[test]
+fn test_each_constraint_maps_to_its_error() {
+    // Account<T> must be owned by the program unless `owner` says otherwise
+    let infos = withdraw_accounts([3; 32], [2; 32], 10, ADMIN);
+    assert_eq!(withdraw(&infos), Err(McpSolError::InvalidOwner.into()));
+
+    let infos = withdraw_accounts(PROGRAM_ID, [8; 32], 10, ADMIN);
+    assert_eq!(withdraw(&infos), Err(McpSolError::HasOneMismatch.into()));
+
+    let infos = withdraw_accounts(PROGRAM_ID, [2; 32], 0, ADMIN);
+    assert_eq!(withdraw(&infos), Err(McpSolError::ConstraintViolation.into()));
+
+    let infos = withdraw_accounts(PROGRAM_ID, [2; 32], 10, [6; 32]);
+    assert_eq!(withdraw(&infos), Err(McpSolError::AddressMismatch.into()));
+
+    // Explicit owner on a raw AccountInfo
+    let mut accounts = withdraw_accounts(PROGRAM_ID, [2; 32], 10, ADMIN);
+    accounts.infos.swap(3, 4);
+    assert_eq!(withdraw(&accounts), Err(McpSolError::InvalidOwner.into()));
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    /// Vault PDA
+    #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump)]
+    pub vault: &'info AccountInfo,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+}
+
+#[test]
+fn test_seeds_exported_to_schema() {
+    let verbose = <Deposit<'static> as AccountsMeta>::VERBOSE_PROPERTIES;
+    assert!(verbose.contains(r#""description":"Vault PDA. seeds=[\"vault\", authority]""#));
+    // Compact properties carry no descriptions
+    assert!(!<Deposit<'static> as AccountsMeta>::COMPACT_PROPERTIES.contains("seeds"));
+}
+
+#[derive(Accounts)]
+pub struct Create<'info> {
+    #[account(init, payer = payer, space = Vault::SPACE)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, signer)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePda<'info> {
+    #[account(init, payer = payer, space = Vault::SPACE, seeds = [b"vault", payer.key().as_ref()], bump)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, signer)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Resize<'info> {
+    #[account(mut, realloc = Vault::SPACE + 32, payer = payer)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, signer)]
+    pub payer: Signer<'info>,
+}
+
+#[test]
+fn test_init_account_schema() {
+    // A keypair account signs its own creation; a PDA is signed for by seeds
+    assert_eq!(
+        <Create<'static> as AccountsMeta>::COMPACT_PROPERTIES,
+        r#""vault_sw":"pubkey","payer_sw":"pubkey""#
+    );
+    assert_eq!(
+        <CreatePda<'static> as AccountsMeta>::COMPACT_PROPERTIES,
+        r#""vault_w":"pubkey","payer_sw":"pubkey""#
+    );
+    assert_eq!(
+        <Resize<'static> as AccountsMeta>::COMPACT_PROPERTIES,
+        r#""vault_w":"pubkey","payer_sw":"pubkey""#
+    );
+
+    // Checked before any CPI is attempted
+    let infos = infos(&[
+        TestAccount::new(1).owner([0; 32]).writable(),
+        TestAccount::new(2).signer().writable(),
+    ]);
+    let result = Create::try_accounts(&PROGRAM_ID, &infos.infos).map(|_| ());
+    assert_eq!(result, Err(McpSolError::MissingSigner.into()));
+}
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    #[account(mut, close = receiver)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub receiver: &'info AccountInfo,
+}
+
+#[test]
+fn test_close_on_exit() {
+    let infos = infos(&[
+        TestAccount::new(1).writable().data(vault_data([2; 32], 10)),
+        TestAccount::new(2).writable(),
+    ]);
+    let (vault, receiver) = (&infos.infos[0], &infos.infos[1]);
+
+    // Loading leaves the account alone; exit runs after the handler
+    Close::try_accounts(&PROGRAM_ID, &infos.infos).unwrap();
+    assert_eq!(vault.lamports(), 1_000_000);
+
+    Close::exit(&PROGRAM_ID, &infos.infos).unwrap();
+    assert_eq!(vault.lamports(), 0);
+    assert_eq!(vault.data_len(), 0);
+    assert!(vault.is_owned_by(&[0; 32]));
+    assert_eq!(receiver.lamports(), 2_000_000);
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    #[account(mut, has_one = authority)]
+    pub vault: AccountLoader<'info, Vault>,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+}
+
+#[test]
+fn test_loader_writes_in_place() {
+    let infos = infos(&[
+        TestAccount::new(1).writable().data(vault_data([2; 32], 10)),
+        TestAccount::new(2).signer(),
+    ]);
+    let accounts = Update::try_accounts(&PROGRAM_ID, &infos.infos).unwrap();
+
+    accounts.vault.load_mut().unwrap().amount += 5;
+    assert_eq!(accounts.vault.load().unwrap().amount, 15);
+    // The write is visible in the raw account bytes
+    let data = infos.infos[0].try_borrow_data().unwrap();
+    assert_eq!(data[40..48], 15u64.to_le_bytes());
+}
+
+#[test]
+fn test_loader_checks() {
+    // has_one reads through the loader
+    let accounts = infos(&[
+        TestAccount::new(1).writable().data(vault_data([8; 32], 10)),
+        TestAccount::new(2).signer(),
+    ]);
+    let result = Update::try_accounts(&PROGRAM_ID, &accounts.infos).map(|_| ());
+    assert_eq!(result, Err(McpSolError::HasOneMismatch.into()));
+
+    let mut data = vault_data([2; 32], 10);
+    data[0] ^= 1;
+    let accounts = infos(&[TestAccount::new(1).data(data)]);
+    let result = AccountLoader::<Vault>::try_from_unchecked(&accounts.infos[0]).map(|_| ());
+    assert_eq!(result, Err(McpSolError::InvalidAccount.into()));
+
+    let accounts = infos(&[TestAccount::new(1).owner([3; 32]).data(vault_data([2; 32], 10))]);
+    let result = AccountLoader::<Vault>::try_from_with_owner(&accounts.infos[0], &PROGRAM_ID).map(|_| ());
+    assert_eq!(result, Err(McpSolError::InvalidOwner.into()));
+
+    // Read-only accounts can be loaded but not mutated
+    let accounts = infos(&[TestAccount::new(1).data(vault_data([2; 32], 10))]);
+    let loader = AccountLoader::<Vault>::try_from_unchecked(&accounts.infos[0]).unwrap();
+    assert_eq!(loader.load().unwrap().amount, 10);
+    assert_eq!(loader.load_mut().map(|_| ()), Err(McpSolError::NotWritable.into()));
+}
+
+#[test]
+fn test_cpi_takes_flags_from_accounts_meta() {
+    let accounts = infos(&[
+        TestAccount::new(1).writable().data(vault_data([2; 32], 10)),
+        TestAccount::new(2).signer(),
+        TestAccount::new(3),
+    ]);
+    let (fixed, extra) = accounts.infos.split_at(2);
+    let fixed: Vec<&AccountInfo> = fixed.iter().collect();
+    let extra: Vec<&AccountInfo> = extra.iter().collect();
+    let defs = <Update<'static> as AccountsMeta>::ACCOUNTS;
+    let remaining = AccountMetaDef {
+        name: "extra",
+        description: "",
+        is_signer: false,
+        is_writable: false,
+        is_optional: false,
+    };
+
+    let invoke = || {
+        mcpsol::cpi::invoke_signed(&PROGRAM_ID, &[0; 8], defs, &fixed, Some((&remaining, &extra)), &[])
+    };
+    assert_eq!(invoke(), Ok(()));
+    let sent = mcpsol::cpi::take_invocation().unwrap();
+    assert_eq!(sent.program_id, PROGRAM_ID);
+    assert_eq!(
+        sent.accounts,
+        [([1; 32], true, false), ([2; 32], false, true), ([3; 32], false, false)]
+    );
+    assert_eq!(sent.data, [0; 8]);
+
+    // vault is passed writable, so an outstanding borrow blocks the call
+    let data = accounts.infos[0].try_borrow_data().unwrap();
+    assert_eq!(invoke(), Err(ProgramError::AccountBorrowFailed));
+    drop(data);
+
+    // authority is read-only and may stay borrowed
+    let _data = accounts.infos[1].try_borrow_data().unwrap();
+    assert_eq!(invoke(), Ok(()));
+}
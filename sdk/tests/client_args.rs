@@ -0,0 +1,88 @@
+//! Instructions built from the published schema by
+//! `McpClient::build_instruction` match the typed `instruction` builders and
+//! decode in the generated dispatcher.
+
+mod common;
+
+use base64::Engine;
+use common::{TestAccount, PROGRAM_ID};
+use mcpsol::prelude::*;
+use mcpsol_client::{McpClient, ParsedSchema};
+
+const SEED: [u8; 16] = *b"fixed-size bytes";
+const OWNER: Pubkey = [5; 32];
+const LABEL: &str = "treasury";
+
+#[derive(Accounts)]
+pub struct Configure<'info> {
+    #[account(signer)]
+    pub admin: Signer<'info>,
+}
+
+#[mcp_program(name = "client_args")]
+pub mod client_args {
+    use super::*;
+
+    #[mcp_instruction(description = "Store a seed")]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn configure<'info>(
+        _ctx: Context<'info, Configure<'info>>,
+        seed: [u8; 16],
+        owner: Pubkey,
+        label: String,
+        fee: u16,
+    ) -> Result<()> {
+        if seed == SEED && owner == OWNER && label == LABEL {
+            Err(ProgramError::Custom(fee.into()))
+        } else {
+            Err(ProgramError::InvalidInstructionData)
+        }
+    }
+}
+
+fn build(seed: &[u8]) -> Option<solana_sdk::instruction::Instruction> {
+    let schema: ParsedSchema = serde_json::from_slice(client_args::MCP_SCHEMA_BYTES).ok()?;
+    let seed = base64::engine::general_purpose::STANDARD.encode(seed);
+    let owner = solana_sdk::pubkey::Pubkey::new_from_array(OWNER).to_string();
+    McpClient::new("http://localhost:8899")
+        .build_instruction(
+            &solana_sdk::pubkey::Pubkey::new_from_array(PROGRAM_ID),
+            "configure",
+            &[("admin", solana_sdk::pubkey::Pubkey::new_from_array([1; 32]))],
+            &[("seed", &seed), ("owner", &owner), ("label", LABEL), ("fee", "9")],
+            &schema,
+        )
+        .ok()
+}
+
+#[test]
+fn test_args_round_trip() {
+    let schema = core::str::from_utf8(client_args::MCP_SCHEMA_BYTES).unwrap();
+    assert!(schema.contains(r#""seed":"[u8;16]","owner":"pubkey","label":"str","fee":"u16""#));
+
+    // Written as-is after the discriminator, like the dispatcher reads it
+    let ix = build(&SEED).unwrap();
+    assert_eq!(ix.data.len(), 8 + 16 + 32 + 4 + LABEL.len() + 2);
+    assert_eq!(ix.data[8..24], SEED);
+    assert_eq!(ix.data[24..56], OWNER);
+
+    // The key argument is data, not an account
+    assert_eq!(ix.accounts.len(), 1);
+
+    // The typed builder lays the arguments out the same way
+    let typed = client_args::instruction::configure(
+        &solana_sdk::pubkey::Pubkey::new_from_array(PROGRAM_ID),
+        &[solana_sdk::pubkey::Pubkey::new_from_array([1; 32])],
+        SEED,
+        OWNER,
+        LABEL.to_string(),
+        9,
+    );
+    assert_eq!(typed, ix);
+
+    let infos = common::infos(&[TestAccount::new(1).signer()]);
+    let result = client_args::process_instruction(&PROGRAM_ID, &infos.infos, &ix.data);
+    assert_eq!(result, Err(ProgramError::Custom(9)));
+
+    assert!(build(&SEED[..15]).is_none());
+}
@@ -0,0 +1,226 @@
+//! Account infos laid out like the runtime's input buffer, shared by the
+//! integration tests.
+
+// Each test crate uses a different part of the harness
+#![allow(dead_code, unused_macros, unused_imports)]
+
+use core::mem::MaybeUninit;
+use std::time::Instant;
+
+use mcpsol::prelude::*;
+use pinocchio::account_info::MAX_PERMITTED_DATA_INCREASE;
+
+pub const PROGRAM_ID: Pubkey = [7; 32];
+const MAX_ACCOUNTS: usize = 8;
+
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub data: Vec<u8>,
+}
+
+impl TestAccount {
+    pub const fn new(key: u8) -> Self {
+        Self {
+            key: [key; 32],
+            owner: PROGRAM_ID,
+            is_signer: false,
+            is_writable: false,
+            data: Vec::new(),
+        }
+    }
+
+    pub const fn owner(mut self, owner: Pubkey) -> Self {
+        self.owner = owner;
+        self
+    }
+
+    pub const fn signer(mut self) -> Self {
+        self.is_signer = true;
+        self
+    }
+
+    pub const fn writable(mut self) -> Self {
+        self.is_writable = true;
+        self
+    }
+
+    pub fn data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+}
+
+/// Account infos backed by a serialized input buffer, which must outlive them.
+pub struct Infos {
+    _input: Vec<u64>,
+    pub infos: Vec<AccountInfo>,
+}
+
+/// Serialize accounts the way the loader does and let pinocchio parse them.
+pub fn infos(accounts: &[TestAccount]) -> Infos {
+    let mut bytes = (accounts.len() as u64).to_le_bytes().to_vec();
+    for acc in accounts {
+        bytes.push(u8::MAX); // not a duplicate
+        bytes.push(acc.is_signer.into());
+        bytes.push(acc.is_writable.into());
+        bytes.push(0); // executable
+        bytes.extend_from_slice(&[0; 4]); // original data length
+        bytes.extend_from_slice(&acc.key);
+        bytes.extend_from_slice(&acc.owner);
+        bytes.extend_from_slice(&1_000_000u64.to_le_bytes());
+        bytes.extend_from_slice(&(acc.data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&acc.data);
+        bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+        bytes.extend_from_slice(&0u64.to_le_bytes()); // rent epoch
+    }
+    bytes.extend_from_slice(&0u64.to_le_bytes()); // instruction data length
+    bytes.extend_from_slice(&PROGRAM_ID);
+
+    // u64 backing keeps the buffer 8-byte aligned like the runtime's
+    let mut input = vec![0u64; bytes.len().div_ceil(8)];
+    bytemuck::cast_slice_mut::<u64, u8>(&mut input)[..bytes.len()].copy_from_slice(&bytes);
+
+    let mut slots = [const { MaybeUninit::<AccountInfo>::uninit() }; MAX_ACCOUNTS];
+    // SAFETY: the buffer follows the loader's serialization format
+    let (_, count, _) = unsafe {
+        pinocchio::entrypoint::deserialize::<MAX_ACCOUNTS>(input.as_mut_ptr().cast(), &mut slots)
+    };
+    // SAFETY: deserialize initialized the first `count` slots
+    let infos = slots[..count]
+        .iter()
+        .map(|slot| unsafe { slot.assume_init_ref() }.clone())
+        .collect();
+
+    Infos {
+        _input: input,
+        infos,
+    }
+}
+
+/// Tool count of [`wide_program!`], enough that every strategy applies
+pub const WIDE_TOOLS: u32 = 24;
+
+/// Declare `#[mcp_program]` module `$module` with [`WIDE_TOOLS`] no-Context
+/// tools `t00`, `t01`, .., each failing with its own index as the error code
+/// so tests can see which handler ran, dispatched with strategy `$dispatch`.
+///
+/// A test crate can hold only one program, since each defines the
+/// entrypoint symbol.
+macro_rules! wide_program {
+    ($module:ident, $dispatch:tt) => {
+        wide_program!(@tools $module, $dispatch, [
+                t00 = 0, t01 = 1, t02 = 2, t03 = 3, t04 = 4, t05 = 5,
+                t06 = 6, t07 = 7, t08 = 8, t09 = 9, t10 = 10, t11 = 11,
+                t12 = 12, t13 = 13, t14 = 14, t15 = 15, t16 = 16, t17 = 17,
+                t18 = 18, t19 = 19, t20 = 20, t21 = 21, t22 = 22, t23 = 23
+        ]);
+    };
+    (@tools $module:ident, $dispatch:tt, [$($tool:ident = $code:literal),*]) => {
+        #[mcp_program(name = "wide", schema_overflow = "paginate", dispatch = $dispatch)]
+        pub mod $module {
+            use super::*;
+
+            $(
+                #[mcp_instruction(description = "Fail with this tool's index")]
+                pub const fn $tool(_program_id: &Pubkey, _accounts: &[AccountInfo]) -> Result<()> {
+                    Err(ProgramError::Custom($code))
+                }
+            )*
+        }
+    };
+}
+
+pub(crate) use wide_program;
+
+/// Declare a [`wide_program!`] dispatched with strategy `$dispatch`, with
+/// the routing test every strategy shares and its benchmark, which runs
+/// with `--ignored`.
+macro_rules! dispatch_tests {
+    ($dispatch:tt) => {
+        use common::wide_program;
+
+        wide_program!(wide, $dispatch);
+
+        #[test]
+        fn test_routes_every_tool() {
+            common::assert_routes(wide::process_instruction);
+        }
+
+        #[test]
+        #[ignore]
+        fn benchmark_dispatch() {
+            common::bench_dispatch($dispatch, wide::process_instruction);
+        }
+    };
+}
+
+pub(crate) use dispatch_tests;
+
+/// Generated `process_instruction` of a program
+pub type Dispatch = fn(&Pubkey, &[AccountInfo], &[u8]) -> mcpsol::Result<()>;
+
+/// Check that the dispatcher of a [`wide_program!`] routes every tool and
+/// `list_tools`, and rejects everything else.
+pub fn assert_routes(dispatch: Dispatch) {
+    for code in 0..WIDE_TOOLS {
+        let disc = mcpsol::core::instruction_discriminator(&format!("t{:02}", code));
+        assert_eq!(
+            dispatch(&[1; 32], &[], &disc),
+            Err(ProgramError::Custom(code))
+        );
+
+        // Keeping any single byte of a tool's discriminator is not enough
+        for byte in 0..8 {
+            let mut other = disc.map(|b| !b);
+            other[byte] = disc[byte];
+            let result = dispatch(&[1; 32], &[], &other);
+            assert_eq!(result, Err(ProgramError::InvalidInstructionData));
+        }
+    }
+
+    let list_tools = mcpsol::core::instruction_discriminator("list_tools");
+    assert_eq!(dispatch(&[1; 32], &[], &list_tools), Ok(()));
+    assert_eq!(
+        dispatch(&[1; 32], &[], &[0; 8]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+    assert_eq!(
+        dispatch(&[1; 32], &[], &[0; 7]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
+
+/// Time the dispatcher of a [`wide_program!`] cycling through every tool, as
+/// a mix of instructions would. Estimates CU like `core/tests/overhead.rs`
+/// (1 CU ≈ 10ns on host) and only reports it, since timing depends on the host.
+pub fn bench_dispatch(strategy: &str, dispatch: Dispatch) {
+    const ITERATIONS: usize = 100_000;
+    let routes: Vec<[u8; 8]> = (0..WIDE_TOOLS)
+        .map(|code| mcpsol::core::instruction_discriminator(&format!("t{:02}", code)))
+        .collect();
+
+    // Warmup
+    for disc in &routes {
+        let _ = dispatch(&[1; 32], &[], disc);
+    }
+
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        let disc = std::hint::black_box(&routes[i % routes.len()]);
+        let _ = std::hint::black_box(dispatch(&[1; 32], &[], disc));
+    }
+    let per_op_ns = start.elapsed().as_nanos() / ITERATIONS as u128;
+    let estimated_cu = per_op_ns / 10;
+
+    println!(
+        "OVERHEAD: dispatch_{} routes={} per_op_ns={} estimated_cu={}",
+        strategy,
+        routes.len() + 1,
+        per_op_ns,
+        estimated_cu
+    );
+}
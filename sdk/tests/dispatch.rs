@@ -0,0 +1,157 @@
+//! The dispatcher `#[mcp_program]` generates, run in-process with hooks, a
+//! fallback and the account checks of no-Context handlers.
+
+mod common;
+
+use std::cell::{Cell, RefCell};
+
+use common::{infos, TestAccount};
+use mcpsol::prelude::*;
+
+thread_local! {
+    static CALLS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static PAUSED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn record(call: impl Into<String>) {
+    CALLS.with(|calls| calls.borrow_mut().push(call.into()));
+}
+
+fn take_calls() -> Vec<String> {
+    CALLS.with(|calls| calls.take())
+}
+
+/// Global pause switch
+fn before(tool: &str, _accounts: &[AccountInfo]) -> mcpsol::Result<()> {
+    record(format!("before {}", tool));
+    if PAUSED.with(Cell::get) {
+        return Err(ProgramError::Custom(99));
+    }
+    Ok(())
+}
+
+// Hooks return Result even when they cannot fail
+#[allow(clippy::unnecessary_wraps)]
+fn after(tool: &str, accounts: &[AccountInfo]) -> mcpsol::Result<()> {
+    record(format!("after {} with {} accounts", tool, accounts.len()));
+    Ok(())
+}
+
+#[allow(clippy::unnecessary_wraps)]
+fn legacy(_program_id: &Pubkey, _accounts: &[AccountInfo], data: &[u8]) -> mcpsol::Result<()> {
+    record(format!("fallback {:?}", data));
+    Ok(())
+}
+
+#[mcp_program(name = "hooked", before = before, after = after, fallback = legacy)]
+pub mod hooked {
+    use super::*;
+
+    #[mcp_instruction(description = "Succeed unless told to fail")]
+    pub fn ping(_program_id: &Pubkey, _accounts: &[AccountInfo], fail: bool) -> Result<()> {
+        record("ping");
+        if fail {
+            return Err(ProgramError::Custom(1));
+        }
+        Ok(())
+    }
+
+    #[mcp_instruction(
+        description = "Move lamports between accounts",
+        accounts = "from:signer+mut, to:mut, clock"
+    )]
+    pub fn transfer(_program_id: &Pubkey, _accounts: &[AccountInfo], _amount: u64) -> Result<()> {
+        record("transfer");
+        Ok(())
+    }
+
+    #[mcp_instruction(
+        description = "Sweep whatever is left, checking accounts itself",
+        accounts = "from:signer+mut",
+        unchecked
+    )]
+    pub fn sweep(_program_id: &Pubkey, _accounts: &[AccountInfo]) -> Result<()> {
+        record("sweep");
+        Ok(())
+    }
+}
+
+fn ping(fail: bool) -> mcpsol::Result<()> {
+    let mut data = hooked::ping::DISCRIMINATOR.to_vec();
+    data.push(fail.into());
+    hooked::process_instruction(&[1; 32], &[], &data)
+}
+
+#[test]
+fn test_hooks_wrap_tools() {
+    assert_eq!(ping(false), Ok(()));
+    assert_eq!(take_calls(), ["before ping", "ping", "after ping with 0 accounts"]);
+
+    // after only runs when the handler succeeds
+    assert_eq!(ping(true), Err(ProgramError::Custom(1)));
+    assert_eq!(take_calls(), ["before ping", "ping"]);
+
+    // A failing before hook stops the instruction
+    PAUSED.with(|paused| paused.set(true));
+    assert_eq!(ping(false), Err(ProgramError::Custom(99)));
+    assert_eq!(take_calls(), ["before ping"]);
+
+    // list_tools stays available and unhooked
+    let result = hooked::process_instruction(&[1; 32], &[], &hooked::LIST_TOOLS_DISCRIMINATOR);
+    assert_eq!(result, Ok(()));
+    assert!(take_calls().is_empty());
+}
+
+#[test]
+fn test_bool_args_are_0_or_1() {
+    let mut data = hooked::ping::DISCRIMINATOR.to_vec();
+    data.push(2);
+    let result = hooked::process_instruction(&[1; 32], &[], &data);
+    assert_eq!(result, Err(ProgramError::InvalidInstructionData));
+}
+
+#[test]
+fn test_fallback_takes_unmatched_data() {
+    assert_eq!(hooked::process_instruction(&[1; 32], &[], &[7; 8]), Ok(()));
+    assert_eq!(hooked::process_instruction(&[1; 32], &[], &[3]), Ok(()));
+    assert_eq!(take_calls(), ["fallback [7, 7, 7, 7, 7, 7, 7, 7]", "fallback [3]"]);
+}
+
+fn transfer(from: TestAccount, to: TestAccount, clock: Option<TestAccount>) -> mcpsol::Result<()> {
+    let mut data = hooked::transfer::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&5u64.to_le_bytes());
+    let accounts: Vec<_> = [from, to].into_iter().chain(clock).collect();
+    hooked::process_instruction(&[1; 32], &infos(&accounts).infos, &data)
+}
+
+#[test]
+fn test_declared_accounts_checked() {
+    let from = || TestAccount::new(1).signer().writable();
+    let to = || TestAccount::new(2).writable();
+    let clock = || Some(TestAccount::new(3));
+
+    assert_eq!(transfer(from(), to(), clock()), Ok(()));
+    assert_eq!(
+        take_calls(),
+        ["before transfer", "transfer", "after transfer with 3 accounts"]
+    );
+
+    let missing = transfer(from(), to(), None);
+    assert_eq!(missing, Err(McpSolError::MissingAccount.into()));
+
+    let unsigned = transfer(TestAccount::new(1).writable(), to(), clock());
+    assert_eq!(unsigned, Err(McpSolError::MissingSigner.into()));
+
+    let readonly = transfer(from(), TestAccount::new(2), clock());
+    assert_eq!(readonly, Err(McpSolError::NotWritable.into()));
+
+    // Checks run after the before hook and stop the handler
+    assert_eq!(take_calls(), ["before transfer"; 3]);
+}
+
+#[test]
+fn test_unchecked_skips_account_checks() {
+    let data = hooked::sweep::DISCRIMINATOR;
+    assert_eq!(hooked::process_instruction(&[1; 32], &[], &data), Ok(()));
+    assert_eq!(take_calls(), ["before sweep", "sweep", "after sweep with 0 accounts"]);
+}
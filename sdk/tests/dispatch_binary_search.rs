@@ -0,0 +1,7 @@
+//! A many-tool program dispatched by binary search over the sorted discriminators.
+
+mod common;
+
+use mcpsol::prelude::*;
+
+common::dispatch_tests!("binary_search");
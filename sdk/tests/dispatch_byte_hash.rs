@@ -0,0 +1,7 @@
+//! A many-tool program dispatched by a table lookup on one discriminator byte.
+
+mod common;
+
+use mcpsol::prelude::*;
+
+common::dispatch_tests!("byte_hash");
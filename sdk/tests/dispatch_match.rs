@@ -0,0 +1,7 @@
+//! A many-tool program dispatched by a `match` on the discriminator.
+
+mod common;
+
+use mcpsol::prelude::*;
+
+common::dispatch_tests!("match");
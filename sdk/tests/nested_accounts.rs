@@ -0,0 +1,253 @@
+//! `#[derive(Accounts)]` structs composed of nested `Accounts` fields and
+//! `Option` accounts, and the schema built from them.
+
+mod common;
+
+use common::{TestAccount, PROGRAM_ID};
+use mcpsol::prelude::*;
+
+/// Reusable accounts of a token transfer
+#[derive(Accounts)]
+pub struct TokenTransfer<'info> {
+    /// Source of the "tokens"
+    #[account(mut)]
+    pub from: &'info AccountInfo,
+    #[account(mut)]
+    pub to: &'info AccountInfo,
+    #[account(signer)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pay<'info> {
+    #[account(signer, mut)]
+    pub payer: Signer<'info>,
+    #[account(nested)]
+    pub transfer: TokenTransfer<'info>,
+    /// Earns a share of the fee
+    #[account(signer, constraint = referrer.key() != payer.key())]
+    pub referrer: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    #[account(nested)]
+    pub pay: Pay<'info>,
+    #[account(mut, close = receiver)]
+    pub escrow: &'info AccountInfo,
+    pub receiver: &'info AccountInfo,
+}
+
+const TOKEN_PROGRAM_ID: Pubkey = [9; 32];
+
+/// A transfer through the token program
+#[derive(Accounts)]
+pub struct Forward<'info> {
+    #[account(nested)]
+    pub transfer: TokenTransfer<'info>,
+    #[account(address = TOKEN_PROGRAM_ID)]
+    pub token_program: Program<'info>,
+}
+
+#[mcp_program(name = "nested")]
+pub mod nested {
+    use super::*;
+
+    #[mcp_instruction(description = "Pay with an optional referrer")]
+    pub const fn pay<'info>(ctx: &Context<'info, Pay<'info>>, amount: u64) -> Result<()> {
+        match ctx.accounts.referrer {
+            Some(_) => Err(ProgramError::Custom(amount as u32 + 1)),
+            None => Err(ProgramError::Custom(amount as u32)),
+        }
+    }
+
+    #[mcp_instruction(
+        description = "Settle a payment",
+        accounts = "pay_payer:signer+mut, pay_transfer_from:mut, pay_transfer_to:mut, \
+                    pay_transfer_authority:signer, pay_referrer:signer+optional, escrow:mut, receiver"
+    )]
+    pub const fn settle<'info>(ctx: &Context<'info, Settle<'info>>) -> Result<()> {
+        let _ = ctx.accounts.pay.transfer.authority;
+        Ok(())
+    }
+}
+
+fn pay_accounts(referrer: TestAccount) -> Vec<TestAccount> {
+    vec![
+        TestAccount::new(1).signer().writable(),
+        TestAccount::new(2).writable(),
+        TestAccount::new(3).writable(),
+        TestAccount::new(4).signer(),
+        referrer,
+    ]
+}
+
+fn absent() -> TestAccount {
+    TestAccount { key: PROGRAM_ID, ..TestAccount::new(0) }
+}
+
+#[test]
+fn test_nested_fields_take_a_sub_range() {
+    assert_eq!(<TokenTransfer as Accounts>::LEN, 3);
+    assert_eq!(<Pay as Accounts>::LEN, 5);
+    assert_eq!(<Settle as Accounts>::LEN, 7);
+
+    let mut accounts = pay_accounts(TestAccount::new(5).signer());
+    accounts.push(TestAccount::new(6).writable());
+    accounts.push(TestAccount::new(7));
+    accounts.push(TestAccount::new(8));
+    let infos = common::infos(&accounts);
+    let ctx = ContextBuilder::new(&PROGRAM_ID, &infos.infos)
+        .build::<Settle>()
+        .unwrap();
+
+    assert_eq!(ctx.accounts.pay.transfer.from.key(), &[2; 32]);
+    assert_eq!(ctx.accounts.pay.transfer.authority.key(), &[4; 32]);
+    assert_eq!(ctx.accounts.escrow.key(), &[6; 32]);
+    assert_eq!(ctx.remaining_accounts.len(), 1);
+
+    // Nested constraints still apply
+    let mut accounts = pay_accounts(absent());
+    accounts[3] = TestAccount::new(4);
+    let infos = common::infos(&accounts);
+    let result = Pay::try_accounts(&PROGRAM_ID, &infos.infos).map(|_| ());
+    assert_eq!(result, Err(McpSolError::MissingSigner.into()));
+
+    let infos = common::infos(&pay_accounts(absent())[..4]);
+    let result = Pay::try_accounts(&PROGRAM_ID, &infos.infos).map(|_| ());
+    assert_eq!(result, Err(McpSolError::MissingAccount.into()));
+}
+
+#[test]
+fn test_program_fields_are_single_accounts() {
+    assert_eq!(<Forward as Accounts>::LEN, 4);
+
+    // The transfer accounts of a payment, then the token program
+    let mut accounts = pay_accounts(TestAccount::new(9));
+    accounts.remove(0);
+    let infos = common::infos(&accounts);
+    let forward = Forward::try_accounts(&PROGRAM_ID, &infos.infos).unwrap();
+    assert_eq!(forward.token_program.program_id, &TOKEN_PROGRAM_ID);
+    assert_eq!(forward.transfer.to.key(), &[3; 32]);
+
+    accounts[3] = TestAccount::new(8);
+    let infos = common::infos(&accounts);
+    let result = Forward::try_accounts(&PROGRAM_ID, &infos.infos).map(|_| ());
+    assert_eq!(result, Err(McpSolError::AddressMismatch.into()));
+}
+
+#[test]
+fn test_program_id_leaves_optional_account_out() {
+    let infos = common::infos(&pay_accounts(absent()));
+    let pay = Pay::try_accounts(&PROGRAM_ID, &infos.infos).unwrap();
+    assert!(pay.referrer.is_none());
+
+    let infos = common::infos(&pay_accounts(TestAccount::new(5).signer()));
+    let pay = Pay::try_accounts(&PROGRAM_ID, &infos.infos).unwrap();
+    assert_eq!(pay.referrer.map(|r| *r.key()), Some([5; 32]));
+
+    // A present optional account is checked like any other
+    let infos = common::infos(&pay_accounts(TestAccount::new(5)));
+    let result = Pay::try_accounts(&PROGRAM_ID, &infos.infos).map(|_| ());
+    assert_eq!(result, Err(McpSolError::MissingSigner.into()));
+
+    // Constraints only run against a present account
+    let infos = common::infos(&pay_accounts(TestAccount::new(1).signer()));
+    let result = Pay::try_accounts(&PROGRAM_ID, &infos.infos).map(|_| ());
+    assert_eq!(result, Err(McpSolError::ConstraintViolation.into()));
+}
+
+#[test]
+fn test_nested_close_runs_on_exit() {
+    let mut accounts = pay_accounts(absent());
+    accounts.push(TestAccount::new(6).writable());
+    accounts.push(TestAccount::new(7).writable());
+    let infos = common::infos(&accounts);
+
+    Settle::exit(&PROGRAM_ID, &infos.infos).unwrap();
+    assert_eq!(infos.infos[5].lamports(), 0);
+    assert_eq!(infos.infos[6].lamports(), 2_000_000);
+}
+
+#[test]
+fn test_accounts_meta_flattens_with_prefixes() {
+    let names: Vec<_> = <Settle as AccountsMeta>::ACCOUNTS.iter().map(|def| def.name).collect();
+    assert_eq!(
+        names,
+        [
+            "pay_payer",
+            "pay_transfer_from",
+            "pay_transfer_to",
+            "pay_transfer_authority",
+            "pay_referrer",
+            "escrow",
+            "receiver",
+        ]
+    );
+    let defs = <Pay as AccountsMeta>::ACCOUNTS;
+    assert_eq!(defs[1].description, "Source of the \"tokens\"");
+    assert!(defs[3].is_signer && !defs[3].is_optional);
+    assert!(defs[4].is_signer && defs[4].is_optional);
+
+    assert_eq!(
+        <Pay as AccountsMeta>::COMPACT_PROPERTIES,
+        r#""payer_sw":"pubkey","transfer_from_w":"pubkey","transfer_to_w":"pubkey","#.to_owned()
+            + r#""transfer_authority_s":"pubkey","referrer_s":"pubkey?""#
+    );
+    assert_eq!(
+        <Pay as AccountsMeta>::COMPACT_REQUIRED,
+        r#""payer_sw","transfer_from_w","transfer_to_w","transfer_authority_s","referrer_s""#
+    );
+}
+
+#[test]
+fn test_fragments_match_core_rendering() {
+    let metas: Vec<_> = <Settle as AccountsMeta>::ACCOUNTS
+        .iter()
+        .map(|def| mcpsol::core::McpAccountMeta {
+            name: def.name.into(),
+            description: (!def.description.is_empty()).then(|| def.description.into()),
+            is_signer: def.is_signer,
+            is_writable: def.is_writable,
+            is_optional: def.is_optional,
+        })
+        .collect();
+    assert_eq!(
+        <Settle as AccountsMeta>::VERBOSE_PROPERTIES,
+        mcpsol::core::generate_verbose_accounts(&metas)
+    );
+    assert!(<Settle as AccountsMeta>::VERBOSE_PROPERTIES
+        .contains(r#""pay_transfer_from":{"type":"pubkey","writable":true,"description":"Source of the \"tokens\""}"#));
+
+    let bare: Vec<_> = metas
+        .into_iter()
+        .map(|meta| mcpsol::core::McpAccountMeta { description: None, ..meta })
+        .collect();
+    assert_eq!(
+        <Settle as AccountsMeta>::VERBOSE_PROPERTIES_STRIPPED,
+        mcpsol::core::generate_verbose_accounts(&bare)
+    );
+}
+
+#[test]
+fn test_schema_lists_nested_and_optional_accounts() {
+    let schema = core::str::from_utf8(nested::MCP_SCHEMA_BYTES).unwrap();
+    assert!(schema.contains(
+        r#""r":["payer_sw","transfer_from_w","transfer_to_w","transfer_authority_s","referrer_s","amount"]"#
+    ));
+    assert!(schema.contains(r#""pay_referrer_s":"pubkey?","escrow_w":"pubkey","receiver":"pubkey""#));
+
+    let page = core::str::from_utf8(nested::MCP_SCHEMA_PAGES[0]).unwrap();
+    assert!(page.contains(r#""referrer":{"type":"pubkey?","signer":true,"description":"Earns a share of the fee"}"#));
+}
+
+#[test]
+fn test_dispatch_with_optional_account() {
+    let data = [&nested::pay::DISCRIMINATOR[..], &7u64.to_le_bytes()].concat();
+    let run = |referrer| {
+        let infos = common::infos(&pay_accounts(referrer));
+        nested::process_instruction(&PROGRAM_ID, &infos.infos, &data)
+    };
+    assert_eq!(run(absent()), Err(ProgramError::Custom(7)));
+    assert_eq!(run(TestAccount::new(5).signer()), Err(ProgramError::Custom(8)));
+}
@@ -0,0 +1,84 @@
+//! Handlers returning values through return data, and the layouts the
+//! schema publishes for them.
+
+mod common;
+
+use mcpsol::prelude::*;
+
+/// Snapshot of a vault for clients
+#[derive(McpReturn, Clone, Copy, Debug, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct VaultInfo {
+    pub balance: u64,
+    pub owner: Pubkey,
+    pub flags: [u8; 8],
+}
+
+#[mcp_program(name = "returns")]
+pub mod returns {
+    use super::*;
+
+    #[mcp_instruction(description = "Double a number")]
+    pub const fn double(_program_id: &Pubkey, _accounts: &[AccountInfo], value: u64) -> Result<u64> {
+        match value.checked_mul(2) {
+            Some(doubled) => Ok(doubled),
+            None => Err(ProgramError::ArithmeticOverflow),
+        }
+    }
+
+    #[mcp_instruction(description = "This program's id")]
+    pub const fn whoami(program_id: &Pubkey, _accounts: &[AccountInfo]) -> Result<Pubkey> {
+        Ok(*program_id)
+    }
+
+    #[mcp_instruction(description = "Describe a vault")]
+    pub const fn info(program_id: &Pubkey, _accounts: &[AccountInfo], balance: u64) -> Result<VaultInfo> {
+        Ok(VaultInfo { balance, owner: *program_id, flags: [0; 8] })
+    }
+
+    #[mcp_instruction(description = "Return nothing")]
+    pub const fn noop(_program_id: &Pubkey, _accounts: &[AccountInfo]) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn call(discriminator: [u8; 8], args: &[u8]) -> mcpsol::Result<()> {
+    let data = [&discriminator[..], args].concat();
+    returns::process_instruction(&[1; 32], &[], &data)
+}
+
+#[test]
+fn test_returning_handlers_dispatch() {
+    assert_eq!(call(returns::double::DISCRIMINATOR, &21u64.to_le_bytes()), Ok(()));
+    assert_eq!(call(returns::whoami::DISCRIMINATOR, &[]), Ok(()));
+    assert_eq!(call(returns::info::DISCRIMINATOR, &5u64.to_le_bytes()), Ok(()));
+    assert_eq!(call(returns::noop::DISCRIMINATOR, &[]), Ok(()));
+
+    // Errors propagate without touching return data
+    let overflow = call(returns::double::DISCRIMINATOR, &u64::MAX.to_le_bytes());
+    assert_eq!(overflow, Err(ProgramError::ArithmeticOverflow));
+}
+
+#[test]
+fn test_derived_return_is_pod_bytes() {
+    let info = returns::info(&[3; 32], &[], 9).unwrap();
+    assert_eq!(info.with_bytes(<[u8]>::to_vec), bytemuck::bytes_of(&info));
+    assert_eq!(
+        VaultInfo::LAYOUT,
+        r#"{"balance":"u64","owner":"pubkey","flags":"[u8;8]"}"#
+    );
+}
+
+#[test]
+fn test_schema_publishes_return_layouts() {
+    let schema = core::str::from_utf8(returns::MCP_SCHEMA_BYTES).unwrap();
+    assert!(schema.contains(r#""r":["value"],"o":"u64"}"#));
+    assert!(schema.contains(r#""o":"pubkey"}"#));
+    assert!(schema.contains(r#","o":{"balance":"u64","owner":"pubkey","flags":"[u8;8]"}"#));
+    assert_eq!(schema.matches(r#""o":"#).count(), 3);
+
+    let page = core::str::from_utf8(returns::MCP_SCHEMA_PAGES[2]).unwrap();
+    assert!(page.contains(r#""returns":{"balance":"u64","owner":"pubkey","flags":"[u8;8]"}"#));
+    let page = core::str::from_utf8(returns::MCP_SCHEMA_PAGES[3]).unwrap();
+    assert!(!page.contains(r#""returns":"#));
+}
@@ -0,0 +1,82 @@
+//! A program built with `discriminator_len = 2`, dispatched on 2-byte tags.
+
+mod common;
+
+use mcpsol::prelude::*;
+
+#[mcp_program(name = "short", discriminator_len = 2)]
+pub mod short {
+    use super::*;
+
+    #[mcp_instruction(description = "Store a value", aliases = ["put"])]
+    pub const fn set(_program_id: &Pubkey, _accounts: &[AccountInfo], value: u32) -> Result<()> {
+        Err(ProgramError::Custom(value))
+    }
+
+    #[mcp_instruction(description = "Attach a label")]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn label(_program_id: &Pubkey, _accounts: &[AccountInfo], text: String) -> Result<()> {
+        Err(ProgramError::Custom(text.len() as u32))
+    }
+}
+
+fn call(tag: &[u8], args: &[u8]) -> mcpsol::Result<()> {
+    let data = [tag, args].concat();
+    short::process_instruction(&[1; 32], &[], &data)
+}
+
+#[test]
+fn test_two_byte_tags_dispatch() {
+    let value = 7u32.to_le_bytes();
+    assert_eq!(
+        call(&short::set::DISCRIMINATOR[..2], &value),
+        Err(ProgramError::Custom(7))
+    );
+
+    let text = [&3u32.to_le_bytes()[..], b"abc"].concat();
+    assert_eq!(
+        call(&short::label::DISCRIMINATOR[..2], &text),
+        Err(ProgramError::Custom(3))
+    );
+
+    // Aliases are truncated like tool names
+    let put = instruction_discriminator("put");
+    assert_eq!(call(&put[..2], &value), Err(ProgramError::Custom(7)));
+}
+
+#[test]
+fn test_list_tools_keeps_full_discriminator() {
+    assert_eq!(call(&short::LIST_TOOLS_DISCRIMINATOR, &[]), Ok(()));
+    assert_eq!(
+        call(&short::LIST_TOOLS_DISCRIMINATOR[..2], &[]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn test_rejects_short_or_unknown_tags() {
+    assert_eq!(call(&[], &[]), Err(ProgramError::InvalidInstructionData));
+    assert_eq!(
+        call(&short::set::DISCRIMINATOR[..1], &[]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+
+    let mut unknown = short::set::DISCRIMINATOR;
+    unknown[0] ^= 0xff;
+    assert_eq!(
+        call(&unknown[..2], &[0; 4]),
+        Err(ProgramError::InvalidInstructionData)
+    );
+}
+
+#[test]
+fn test_schema_advertises_tag_length() {
+    let schema = core::str::from_utf8(short::MCP_SCHEMA_BYTES).unwrap();
+    assert!(schema.contains(r#""name":"short","dl":2"#));
+
+    let tag = short::set::DISCRIMINATOR[..2]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    assert!(schema.contains(&format!(r#""d":"{}""#, tag)));
+}
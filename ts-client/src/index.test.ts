@@ -83,6 +83,101 @@ describe('McpClient', () => {
         Buffer.from([100, 0, 0, 0, 0, 0, 0, 0])
       );
     });
+
+    it('encodes fixed-length bytes without a length prefix', () => {
+      const tool = {
+        name: 'configure',
+        discriminator: '0102030405060708',
+        params: { seed: '[u8;4]', fee: 'u16' },
+        required: ['seed', 'fee'],
+      };
+      const programId = Keypair.generate().publicKey;
+
+      const ix = client.buildInstruction(programId, tool, {}, {
+        seed: Buffer.from([9, 8, 7, 6]).toString('base64'),
+        fee: 5,
+      });
+      expect(ix.data.slice(8)).toEqual(Buffer.from([9, 8, 7, 6, 5, 0]));
+
+      expect(() =>
+        client.buildInstruction(programId, tool, {}, { seed: Buffer.from([1]), fee: 5 })
+      ).toThrow('Expected 4 bytes');
+    });
+
+    it('encodes pubkey params passed as args', () => {
+      const tool = {
+        name: 'set_owner',
+        discriminator: '0102030405060708',
+        params: { vault_w: 'pubkey', owner: 'pubkey' },
+        required: ['vault_w', 'owner'],
+      };
+
+      const vault = Keypair.generate().publicKey;
+      const owner = Keypair.generate().publicKey;
+      const programId = Keypair.generate().publicKey;
+
+      const ix = client.buildInstruction(programId, tool, { vault }, { owner });
+
+      expect(ix.keys.length).toBe(1);
+      expect(ix.keys[0].pubkey.equals(vault)).toBe(true);
+      expect(ix.data.slice(8)).toEqual(owner.toBuffer());
+    });
+  });
+
+  describe('remaining accounts', () => {
+    it('appends pubkey[] accounts after the fixed ones', () => {
+      const tool = {
+        name: 'approve',
+        discriminator: '0102030405060708',
+        params: { proposal_w: 'pubkey', signers_s: 'pubkey[]', index: 'u8' },
+        required: ['proposal_w', 'index'],
+      };
+      const proposal = Keypair.generate().publicKey;
+      const signers = [Keypair.generate().publicKey, Keypair.generate().publicKey];
+      const programId = Keypair.generate().publicKey;
+
+      const ix = client.buildInstruction(programId, tool, { proposal, signers }, { index: 3 });
+
+      expect(ix.keys.map((k) => k.pubkey.toBase58())).toEqual(
+        [proposal, ...signers].map((k) => k.toBase58())
+      );
+      expect(ix.keys[1].isSigner).toBe(true);
+      expect(ix.keys[2].isWritable).toBe(false);
+      expect(ix.data.slice(8)).toEqual(Buffer.from([3]));
+
+      // The list may be empty
+      const bare = client.buildInstruction(programId, tool, { proposal }, { index: 3 });
+      expect(bare.keys.length).toBe(1);
+    });
+  });
+
+  describe('optional accounts', () => {
+    const tool = {
+      name: 'pay',
+      discriminator: '0102030405060708',
+      params: { user_s: 'pubkey', referrer_w: 'pubkey?', amount: 'u8' },
+      required: ['user_s', 'referrer_w', 'amount'],
+    };
+    const user = Keypair.generate().publicKey;
+    const programId = Keypair.generate().publicKey;
+
+    it('passes the program id for a left-out account', () => {
+      const ix = client.buildInstruction(programId, tool, { user }, { amount: 1 });
+
+      expect(ix.keys.length).toBe(2);
+      expect(ix.keys[1].pubkey.equals(programId)).toBe(true);
+      expect(ix.keys[1].isWritable).toBe(false);
+      expect(ix.keys[1].isSigner).toBe(false);
+      expect(ix.data.slice(8)).toEqual(Buffer.from([1]));
+    });
+
+    it('passes a given account with its flags', () => {
+      const referrer = Keypair.generate().publicKey;
+      const ix = client.buildInstruction(programId, tool, { user, referrer }, { amount: 1 });
+
+      expect(ix.keys[1].pubkey.equals(referrer)).toBe(true);
+      expect(ix.keys[1].isWritable).toBe(true);
+    });
   });
 
   describe('findTool', () => {
@@ -94,17 +189,33 @@ describe('McpClient', () => {
           { name: 'foo', discriminator: '00', params: {}, required: [] },
           { name: 'bar', discriminator: '01', params: {}, required: [] },
         ],
+        errors: [],
+        discriminatorLength: 8,
       };
 
       const tool = client.findTool(schema, 'bar');
       expect(tool?.name).toBe('bar');
     });
 
+    it('resolves deprecated aliases to their replacement', () => {
+      const schema = client.parseCompactSchema({
+        v: '2024-11-05',
+        name: 'counter',
+        tools: [{ n: 'increment', d: '0b12680968ae3b21', a: { add: 'ec211a2b4e5c2d9c' } }],
+      });
+
+      expect(schema.tools[0].aliases).toEqual({ add: 'ec211a2b4e5c2d9c' });
+      expect(client.findTool(schema, 'add')?.name).toBe('increment');
+      expect(client.findTool(schema, 'increment')?.name).toBe('increment');
+    });
+
     it('returns undefined for missing tool', () => {
       const schema = {
         v: '2024-11-05',
         name: 'test',
         tools: [],
+        errors: [],
+        discriminatorLength: 8,
       };
 
       const tool = client.findTool(schema, 'missing');
@@ -112,6 +223,99 @@ describe('McpClient', () => {
     });
   });
 
+  describe('short discriminators', () => {
+    it('writes dl-byte tags', () => {
+      const schema = client.parseCompactSchema({
+        v: '2024-11-05',
+        name: 'counter',
+        dl: 2,
+        tools: [{ n: 'increment', d: '0b12', p: { amount: 'u64' }, r: ['amount'] }],
+      });
+      expect(schema.discriminatorLength).toBe(2);
+
+      const programId = Keypair.generate().publicKey;
+      const ix = client.buildInstruction(programId, schema.tools[0], {}, { amount: 7n });
+      expect(ix.data).toEqual(Buffer.from([0x0b, 0x12, 7, 0, 0, 0, 0, 0, 0, 0]));
+    });
+
+    it('rejects discriminators of another length', () => {
+      expect(() =>
+        client.parseCompactSchema({
+          v: '2024-11-05',
+          name: 'counter',
+          dl: 2,
+          tools: [{ n: 'increment', d: '0b12680968ae3b21' }],
+        })
+      ).toThrow('not 2 bytes');
+
+      const defaults = client.parseCompactSchema({
+        v: '2024-11-05',
+        name: 'counter',
+        tools: [{ n: 'increment', d: '0b12680968ae3b21' }],
+      });
+      expect(defaults.discriminatorLength).toBe(8);
+    });
+  });
+
+  describe('decodeReturn', () => {
+    it('decodes values and structs by layout', () => {
+      const schema = client.parseCompactSchema({
+        v: '2024-11-05',
+        name: 'vault',
+        tools: [
+          { n: 'balance', d: '0102030405060708', o: 'u64' },
+          {
+            n: 'get_info',
+            d: '0807060504030201',
+            o: { balance: 'u64', owner: 'pubkey', flags: '[u8;2]', ok: 'bool' },
+          },
+        ],
+      });
+      const [balance, info] = schema.tools;
+
+      expect(client.decodeReturn(balance, Buffer.from([5, 0, 0, 0, 0, 0, 0, 0]))).toBe(5n);
+
+      const owner = Keypair.generate().publicKey;
+      const data = Buffer.concat([
+        Buffer.from([1, 0, 0, 0, 0, 0, 0, 0]),
+        owner.toBuffer(),
+        Buffer.from([7, 9, 1]),
+      ]);
+      const decoded = client.decodeReturn(info, data);
+      expect(decoded.balance).toBe(1n);
+      expect(decoded.owner.equals(owner)).toBe(true);
+      expect(decoded.flags).toEqual([7, 9]);
+      expect(decoded.ok).toBe(true);
+
+      expect(() => client.decodeReturn(balance, Buffer.from([5]))).toThrow('too short');
+      expect(() => client.decodeReturn(balance, Buffer.alloc(9))).toThrow('trailing');
+    });
+  });
+
+  describe('errors', () => {
+    it('parses declared errors', () => {
+      const schema = client.parseCompactSchema({
+        v: '2024-11-05',
+        name: 'counter',
+        tools: [],
+        e: [{ c: 6000, n: 'Overflow', m: 'Count would overflow' }, { c: 6001, n: 'Frozen' }],
+      });
+
+      expect(client.findError(schema, 6000)).toEqual({
+        code: 6000,
+        name: 'Overflow',
+        message: 'Count would overflow',
+      });
+      expect(client.findError(schema, 6001)?.message).toBeUndefined();
+      expect(client.findError(schema, 0)).toBeUndefined();
+    });
+
+    it('defaults to no errors', () => {
+      const schema = client.parseCompactSchema({ v: '2024-11-05', name: 'counter', tools: [] });
+      expect(schema.errors).toEqual([]);
+    });
+  });
+
   describe('LIST_TOOLS_DISCRIMINATOR', () => {
     it('has correct value', () => {
       expect(LIST_TOOLS_DISCRIMINATOR.toString('hex')).toBe('42195e6a55fd41c0');
@@ -39,6 +39,20 @@ export interface McpSchema {
   name: string;
   /** Available tools */
   tools: McpTool[];
+  /** Custom program errors (e in compact format) */
+  errors: McpError[];
+  /** Bytes of each discriminator that tag instruction data (dl, default 8) */
+  discriminatorLength: number;
+}
+
+/** Custom program error declared in the schema */
+export interface McpError {
+  /** `ProgramError::Custom` code (c in compact format) */
+  code: number;
+  /** Error name (n in compact format) */
+  name: string;
+  /** Message (m in compact format) */
+  message?: string;
 }
 
 /** MCP tool definition */
@@ -47,14 +61,21 @@ export interface McpTool {
   name: string;
   /** Description (i in compact format) */
   description?: string;
-  /** Discriminator hex (d in compact format) */
+  /** Discriminator hex (d in compact format), `discriminatorLength` bytes */
   discriminator: string;
-  /** Parameters: name -> type */
+  /** Parameters: name -> type; `pubkey[]` marks remaining accounts */
   params: Record<string, string>;
   /** Required parameters in order */
   required: string[];
+  /** Deprecated former names -> discriminator hex (a in compact format) */
+  aliases?: Record<string, string>;
+  /** Return data layout (o in compact format): a type name or struct fields */
+  returns?: ReturnLayout;
 }
 
+/** Return data layout: a type such as `u64` or `[u8;8]`, or fields in order */
+export type ReturnLayout = string | { [field: string]: ReturnLayout };
+
 /** Parsed PDA seeds from description */
 export interface PdaSeeds {
   /** Literal seed strings */
@@ -119,7 +140,15 @@ export class McpClient {
   /**
    * Parse compact schema format to full McpSchema
    */
-  private parseCompactSchema(compact: any): McpSchema {
+  parseCompactSchema(compact: any): McpSchema {
+    const discriminatorLength: number = compact.dl ?? 8;
+    for (const t of compact.tools) {
+      const tags: string[] = [t.d, ...Object.values<string>(t.a || {})];
+      if (tags.some((d) => d.length !== discriminatorLength * 2)) {
+        throw new Error(`Discriminators of ${t.n} are not ${discriminatorLength} bytes`);
+      }
+    }
+
     return {
       v: compact.v,
       name: compact.name,
@@ -129,36 +158,57 @@ export class McpClient {
         discriminator: t.d,
         params: t.p || {},
         required: t.r || [],
+        aliases: t.a,
+        returns: t.o,
       })),
+      errors: (compact.e || []).map((e: any) => ({
+        code: e.c,
+        name: e.n,
+        message: e.m,
+      })),
+      discriminatorLength,
     };
   }
 
   /**
    * Build instruction from tool name and parameters
+   *
+   * Key arguments are typed `pubkey` like read-only accounts, so a `pubkey`
+   * parameter given in `args` is encoded as data. Remaining accounts, typed
+   * `pubkey[]`, are passed as an array and follow the fixed accounts. An
+   * optional account, typed `pubkey?`, left out of `accounts` is passed as
+   * the program id.
    */
   buildInstruction(
     programId: PublicKey,
     tool: McpTool,
-    accounts: Record<string, PublicKey>,
+    accounts: Record<string, PublicKey | PublicKey[]>,
     args: Record<string, any>
   ): TransactionInstruction {
     const keys: AccountMeta[] = [];
     const data: number[] = [];
 
-    // Add discriminator
+    // Add discriminator, as long as the schema's dl
     const discBytes = Buffer.from(tool.discriminator, 'hex');
+    if (![1, 2, 4, 8].includes(discBytes.length)) {
+      throw new Error(`Invalid discriminator length: ${discBytes.length} bytes`);
+    }
     data.push(...discBytes);
 
     // Process required params in order
     for (const param of tool.required) {
       const type = tool.params[param];
 
-      if (type === 'pubkey') {
+      if ((type === 'pubkey' || type === 'pubkey?') && !(param in args)) {
         // It's an account
         const baseName = this.getBaseName(param);
         const pubkey = accounts[baseName] || accounts[param];
 
-        if (!pubkey) {
+        if (!pubkey && type === 'pubkey?') {
+          keys.push({ pubkey: programId, isSigner: false, isWritable: false });
+          continue;
+        }
+        if (!pubkey || Array.isArray(pubkey)) {
           throw new Error(`Missing account: ${param}`);
         }
 
@@ -179,6 +229,20 @@ export class McpClient {
       }
     }
 
+    // Variadic trailing accounts, in the order given
+    for (const [param, type] of Object.entries(tool.params)) {
+      if (type !== 'pubkey[]') continue;
+
+      const given = accounts[this.getBaseName(param)] || accounts[param] || [];
+      for (const pubkey of Array.isArray(given) ? given : [given]) {
+        keys.push({
+          pubkey,
+          isSigner: this.isSigner(param),
+          isWritable: this.isWritable(param),
+        });
+      }
+    }
+
     return new TransactionInstruction({
       programId,
       keys,
@@ -256,9 +320,40 @@ export class McpClient {
 
   /**
    * Find a tool by name
+   *
+   * A deprecated name finds the tool that replaced it, so new instructions
+   * are built under the current discriminator.
    */
   findTool(schema: McpSchema, name: string): McpTool | undefined {
-    return schema.tools.find((t) => t.name === name);
+    return (
+      schema.tools.find((t) => t.name === name) ??
+      schema.tools.find((t) => t.aliases !== undefined && name in t.aliases)
+    );
+  }
+
+  /**
+   * Decode the return data of a call to `tool` by its advertised layout
+   *
+   * Integers up to 32 bits become numbers and wider ones bigints, pubkeys
+   * PublicKeys, arrays arrays and structs objects in field order.
+   */
+  decodeReturn(tool: McpTool, data: Buffer): any {
+    if (tool.returns === undefined) {
+      throw new Error(`Tool ${tool.name} declares no return data`);
+    }
+    const cursor = { offset: 0 };
+    const value = this.decodeLayout(tool.returns, data, cursor);
+    if (cursor.offset !== data.length) {
+      throw new Error(`${data.length - cursor.offset} trailing bytes in return data`);
+    }
+    return value;
+  }
+
+  /**
+   * Find the error a failed transaction's `Custom` code stands for
+   */
+  findError(schema: McpSchema, code: number): McpError | undefined {
+    return schema.errors.find((e) => e.code === code);
   }
 
   /**
@@ -352,13 +447,76 @@ export class McpClient {
         buf.push(...bytes);
         break;
       }
-      default:
-        throw new Error(`Unknown type: ${type}`);
+      default: {
+        // Fixed-length bytes, [u8;N], written without a length prefix
+        const fixed = type.match(/^\[u8;(\d+)\]$/);
+        if (!fixed) {
+          throw new Error(`Unknown type: ${type}`);
+        }
+        const bytes = Buffer.isBuffer(value) ? value : Buffer.from(value, 'base64');
+        if (bytes.length !== Number(fixed[1])) {
+          throw new Error(`Expected ${fixed[1]} bytes for ${type}, got ${bytes.length}`);
+        }
+        buf.push(...bytes);
+        break;
+      }
     }
 
     return buf;
   }
 
+  /**
+   * Read one value of `layout` at `cursor.offset`
+   */
+  private decodeLayout(layout: ReturnLayout, data: Buffer, cursor: { offset: number }): any {
+    if (typeof layout !== 'string') {
+      const object: Record<string, any> = {};
+      for (const [field, fieldLayout] of Object.entries(layout)) {
+        object[field] = this.decodeLayout(fieldLayout, data, cursor);
+      }
+      return object;
+    }
+
+    // Fixed-size arrays are written `[type;len]`
+    const array = layout.match(/^\[(.+);\s*(\d+)\]$/);
+    if (array) {
+      return Array.from({ length: Number(array[2]) }, () =>
+        this.decodeLayout(array[1].trim(), data, cursor)
+      );
+    }
+
+    const sizes: Record<string, number> = {
+      u8: 1, i8: 1, bool: 1, u16: 2, i16: 2, u32: 4, i32: 4,
+      u64: 8, i64: 8, u128: 16, i128: 16, pubkey: 32,
+    };
+    const size = sizes[layout];
+    if (size === undefined) {
+      throw new Error(`Unknown return type: ${layout}`);
+    }
+    if (cursor.offset + size > data.length) {
+      throw new Error(`Return data too short for ${layout}`);
+    }
+    const at = cursor.offset;
+    cursor.offset += size;
+
+    switch (layout) {
+      case 'u8': return data.readUInt8(at);
+      case 'i8': return data.readInt8(at);
+      case 'bool': return data[at] !== 0;
+      case 'u16': return data.readUInt16LE(at);
+      case 'i16': return data.readInt16LE(at);
+      case 'u32': return data.readUInt32LE(at);
+      case 'i32': return data.readInt32LE(at);
+      case 'u64': return data.readBigUInt64LE(at);
+      case 'i64': return data.readBigInt64LE(at);
+      case 'u128':
+        return data.readBigUInt64LE(at) + (data.readBigUInt64LE(at + 8) << BigInt(64));
+      case 'i128':
+        return data.readBigUInt64LE(at) + (data.readBigInt64LE(at + 8) << BigInt(64));
+      default: return new PublicKey(data.subarray(at, at + 32));
+    }
+  }
+
   /** Get the connection */
   get conn(): Connection {
     return this.connection;